@@ -0,0 +1,52 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::network::Network;
+
+/// Opérations on-chain utilisées par la ligne de commande et les scénarios.
+///
+/// Implémenté par `BitcoinNode` (bitcoind via RPC) et, dans les tests, par
+/// une chaîne simulée en mémoire.
+#[async_trait]
+pub trait BitcoinBackend: Send + Sync {
+    /// Réseau sur lequel tourne le nœud.
+    fn network(&self) -> Network;
+
+    /// Résumé de `getblockchaininfo`, au format JSON.
+    async fn get_blockchain_info(&self) -> Result<String>;
+
+    /// Crée ou charge le portefeuille utilisé pour financer les nœuds.
+    async fn ensure_wallet(&self) -> Result<()>;
+
+    /// Mine `count` blocs vers une adresse du portefeuille (regtest uniquement).
+    async fn generate_blocks(&self, count: u64) -> Result<Vec<String>>;
+
+    async fn generate_to_address(&self, blocks: u64, address: &str) -> Result<Vec<String>>;
+
+    /// Envoie `amount` BTC à `address` et renvoie l'identifiant de la transaction.
+    async fn send_to_address(&self, address: &str, amount: f64) -> Result<String>;
+
+    async fn get_new_address(&self) -> Result<String>;
+
+    /// Confirmations et sorties d'une transaction quelconque (`txindex=1`),
+    /// en indiquant celles qui ont déjà été dépensées.
+    async fn get_transaction_status(&self, txid: &str) -> Result<TransactionStatus>;
+}
+
+/// État on-chain d'une transaction, par exemple celle qui ferme un canal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionStatus {
+    pub txid: String,
+    /// 0 tant que la transaction est dans la mempool.
+    pub confirmations: u64,
+    pub outputs: Vec<TransactionOutput>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionOutput {
+    pub vout: u32,
+    pub amount_sat: u64,
+    pub address: Option<String>,
+    /// Sortie consommée par une transaction confirmée ou en mempool.
+    pub spent: bool,
+}
@@ -0,0 +1,213 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::backend::{BitcoinBackend, TransactionOutput, TransactionStatus};
+use super::network::Network;
+
+/// Transaction simulée : un unique paiement vers `address`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockTransaction {
+    pub txid: String,
+    pub address: String,
+    pub amount_sat: u64,
+    /// Hauteur du bloc qui l'a confirmée, `None` tant qu'elle est dans la mempool.
+    pub block_height: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct ChainState {
+    height: u64,
+    next_id: u64,
+    transactions: Vec<MockTransaction>,
+    /// Sorties dépensées, `(txid, vout)`.
+    spent: Vec<(String, u32)>,
+    failures: HashMap<&'static str, String>,
+}
+
+impl ChainState {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn check(&self, method: &'static str) -> Result<()> {
+        match self.failures.get(method) {
+            Some(message) => Err(anyhow::anyhow!("{}", message)),
+            None => Ok(()),
+        }
+    }
+
+    fn mine(&mut self, count: u64) -> Vec<String> {
+        let mut hashes = Vec::new();
+        for _ in 0..count {
+            self.height += 1;
+            let height = self.height;
+            for tx in self.transactions.iter_mut().filter(|tx| tx.block_height.is_none()) {
+                tx.block_height = Some(height);
+            }
+            hashes.push(format!("{:064x}", 0xb10c_0000_0000 + height));
+        }
+        hashes
+    }
+}
+
+/// Chaîne Bitcoin en mémoire, déterministe, pour les tests sans bitcoind.
+///
+/// Le clone partage la même chaîne : un `MockLightning` relié à une copie voit
+/// les fonds envoyés à ses adresses et les confirmations de ses ouvertures.
+#[derive(Debug, Clone, Default)]
+pub struct MockBitcoin {
+    state: Arc<Mutex<ChainState>>,
+}
+
+impl MockBitcoin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fait échouer chaque appel à `method` avec `message`.
+    pub fn fail_on(&self, method: &'static str, message: &str) {
+        self.lock().failures.insert(method, message.to_string());
+    }
+
+    pub fn height(&self) -> u64 {
+        self.lock().height
+    }
+
+    pub fn transactions(&self) -> Vec<MockTransaction> {
+        self.lock().transactions.clone()
+    }
+
+    /// Publie une transaction dans la mempool et renvoie son identifiant.
+    pub fn broadcast(&self, address: &str, amount_sat: u64) -> String {
+        let mut state = self.lock();
+        let txid = format!("{:064x}", state.next_id());
+        state.transactions.push(MockTransaction {
+            txid: txid.clone(),
+            address: address.to_string(),
+            amount_sat,
+            block_height: None,
+        });
+        txid
+    }
+
+    pub fn transaction(&self, txid: &str) -> Option<MockTransaction> {
+        self.lock().transactions.iter().find(|tx| tx.txid == txid).cloned()
+    }
+
+    /// Nombre de confirmations d'une transaction, 0 si elle est dans la mempool.
+    pub fn confirmations(&self, txid: &str) -> Option<u64> {
+        let state = self.lock();
+        let tx = state.transactions.iter().find(|tx| tx.txid == txid)?;
+        Some(tx.block_height.map_or(0, |height| state.height - height + 1))
+    }
+
+    /// Marque la sortie `vout` de `txid` comme dépensée.
+    pub fn spend(&self, txid: &str, vout: u32) {
+        self.lock().spent.push((txid.to_string(), vout));
+    }
+
+    /// Transactions reçues par `address`, dans l'ordre de publication.
+    pub fn received(&self, address: &str) -> Vec<MockTransaction> {
+        self.lock()
+            .transactions
+            .iter()
+            .filter(|tx| tx.address == address)
+            .cloned()
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ChainState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait]
+impl BitcoinBackend for MockBitcoin {
+    fn network(&self) -> Network {
+        Network::Regtest
+    }
+
+    async fn get_blockchain_info(&self) -> Result<String> {
+        let state = self.lock();
+        state.check("get_blockchain_info")?;
+        Ok(serde_json::to_string_pretty(&serde_json::json!({
+            "chain": "regtest",
+            "blocks": state.height,
+        }))?)
+    }
+
+    async fn ensure_wallet(&self) -> Result<()> {
+        self.lock().check("ensure_wallet")
+    }
+
+    async fn generate_blocks(&self, count: u64) -> Result<Vec<String>> {
+        let mut state = self.lock();
+        state.check("generate_blocks")?;
+        Ok(state.mine(count))
+    }
+
+    async fn generate_to_address(&self, blocks: u64, _address: &str) -> Result<Vec<String>> {
+        let mut state = self.lock();
+        state.check("generate_to_address")?;
+        Ok(state.mine(blocks))
+    }
+
+    async fn send_to_address(&self, address: &str, amount: f64) -> Result<String> {
+        self.lock().check("send_to_address")?;
+        let amount_sat = (amount * 100_000_000.0).round() as u64;
+        Ok(self.broadcast(address, amount_sat))
+    }
+
+    async fn get_new_address(&self) -> Result<String> {
+        let mut state = self.lock();
+        state.check("get_new_address")?;
+        Ok(format!("bcrt1qminer{}", state.next_id()))
+    }
+
+    async fn get_transaction_status(&self, txid: &str) -> Result<TransactionStatus> {
+        self.lock().check("get_transaction_status")?;
+        let tx = self
+            .transaction(txid)
+            .ok_or_else(|| anyhow::anyhow!("No such mempool or blockchain transaction: {}", txid))?;
+        let state = self.lock();
+        Ok(TransactionStatus {
+            confirmations: tx.block_height.map_or(0, |height| state.height - height + 1),
+            outputs: vec![TransactionOutput {
+                vout: 0,
+                amount_sat: tx.amount_sat,
+                spent: state.spent.contains(&(tx.txid.clone(), 0)),
+                address: Some(tx.address),
+            }],
+            txid: tx.txid,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn mining_confirms_the_mempool() {
+        let chain = MockBitcoin::new();
+        let txid = chain.send_to_address("bcrt1qalice", 0.5).await.unwrap();
+        assert_eq!(chain.confirmations(&txid), Some(0));
+        assert_eq!(chain.received("bcrt1qalice")[0].amount_sat, 50_000_000);
+
+        chain.generate_blocks(6).await.unwrap();
+        assert_eq!(chain.height(), 6);
+        assert_eq!(chain.confirmations(&txid), Some(6));
+    }
+
+    #[tokio::test]
+    async fn injected_failures_are_returned() {
+        let chain = MockBitcoin::new();
+        chain.fail_on("send_to_address", "Insufficient funds");
+        let err = chain.send_to_address("bcrt1qalice", 1.0).await.unwrap_err();
+        assert_eq!(err.to_string(), "Insufficient funds");
+        assert!(chain.transactions().is_empty());
+    }
+}
@@ -1,3 +1,12 @@
-mod node;
-
-pub use node::{BitcoinConfig, BitcoinNode}; 
\ No newline at end of file
+mod backend;
+#[cfg(test)]
+mod mock;
+mod network;
+mod node;
+mod rpc;
+
+pub use backend::{BitcoinBackend, TransactionStatus};
+#[cfg(test)]
+pub use mock::MockBitcoin;
+pub use network::Network;
+pub use node::{BitcoinConfig, BitcoinNode};
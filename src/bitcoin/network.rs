@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Réseau Bitcoin sur lequel tournent bitcoind et les nœuds Lightning.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    /// Réseau principal, appelé `bitcoin` par lightningd.
+    #[serde(alias = "bitcoin")]
+    Mainnet,
+    Testnet,
+    Signet,
+    #[default]
+    Regtest,
+}
+
+impl Network {
+    /// Nom du sous-répertoire de `lightning_dir` propre à ce réseau.
+    pub fn lightning_dir_name(self) -> &'static str {
+        match self {
+            Network::Mainnet => "bitcoin",
+            Network::Testnet => "testnet",
+            Network::Signet => "signet",
+            Network::Regtest => "regtest",
+        }
+    }
+
+    /// Option de ligne de commande de bitcoind sélectionnant ce réseau.
+    pub fn bitcoind_flag(self) -> Option<&'static str> {
+        match self {
+            Network::Mainnet => None,
+            Network::Testnet => Some("-testnet"),
+            Network::Signet => Some("-signet"),
+            Network::Regtest => Some("-regtest"),
+        }
+    }
+
+    /// Seul regtest permet de miner des blocs à la demande.
+    pub fn allows_mining(self) -> bool {
+        self == Network::Regtest
+    }
+
+    /// Renvoie une erreur pour les opérations réservées à regtest.
+    pub fn require_mining(self, operation: &str) -> anyhow::Result<()> {
+        if self.allows_mining() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("{} n'est possible qu'en regtest (réseau configuré : {})", operation, self))
+        }
+    }
+}
+
+impl From<Network> for bitcoin::Network {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::Mainnet => bitcoin::Network::Bitcoin,
+            Network::Testnet => bitcoin::Network::Testnet,
+            Network::Signet => bitcoin::Network::Signet,
+            Network::Regtest => bitcoin::Network::Regtest,
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Signet => "signet",
+            Network::Regtest => "regtest",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lightningd_name_is_accepted_for_mainnet() {
+        let network: Network = serde_json::from_str("\"bitcoin\"").unwrap();
+        assert_eq!(network, Network::Mainnet);
+        assert_eq!(network.lightning_dir_name(), "bitcoin");
+        assert_eq!(network.bitcoind_flag(), None);
+    }
+
+    #[test]
+    fn mining_is_refused_outside_regtest() {
+        assert!(Network::Regtest.require_mining("generatetoaddress").is_ok());
+        let err = Network::Signet.require_mining("generatetoaddress").unwrap_err();
+        assert!(err.to_string().contains("signet"), "{}", err);
+    }
+}
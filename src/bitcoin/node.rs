@@ -1,189 +1,191 @@
-use anyhow::Result;
-use bitcoincore_rpc::{Auth, Client, RpcApi};
-use bitcoincore_rpc::bitcoin::{Address, Amount as BitcoinAmount, Network};
-use serde::{Deserialize, Serialize};
-use std::process::{Command, Child};
-use std::path::PathBuf;
-use std::str::FromStr;
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct BitcoinConfig {
-    pub rpc_host: String,
-    pub rpc_port: u16,
-    pub rpc_user: String,
-    pub rpc_password: String,
-    pub network: String,
-    pub bitcoin_path: Option<String>,
-}
-
-pub struct BitcoinNode {
-    client: Client,
-    process: Option<Child>,
-}
-
-impl BitcoinNode {
-    pub fn new(config: BitcoinConfig) -> Result<Self> {
-        let rpc_url = format!("http://{}:{}", config.rpc_host, config.rpc_port);
-        let auth = Auth::UserPass(config.rpc_user, config.rpc_password);
-        let client = Client::new(&rpc_url, auth)?;
-        
-        Ok(Self { 
-            client,
-            process: None 
-        })
-    }
-
-    pub async fn get_blockchain_info(&self) -> Result<String> {
-        let info = self.client.get_blockchain_info()?;
-        Ok(serde_json::to_string_pretty(&info)?)
-    }
-
-    pub async fn generate_blocks(&self, count: u64) -> Result<Vec<String>> {
-        // Vérifier que le portefeuille est chargé
-        self.ensure_wallet().await?;
-        
-        let address = self.client.get_new_address(None, None)?
-            .require_network(Network::Regtest)?;
-        
-        println!("Generating {} blocks to address: {}", count, address);
-        
-        let block_hashes = self.client.generate_to_address(count, &address)?;
-        
-        println!("Generated blocks successfully!");
-        
-        Ok(block_hashes.iter().map(|h| h.to_string()).collect())
-    }
-
-    pub async fn ensure_wallet(&self) -> Result<()> {
-        for _ in 0..5 {  // Essayer 5 fois
-            match self.client.create_wallet("default", None, None, None, None) {
-                Ok(_) => {
-                    println!("Created new wallet 'default'");
-                }
-                Err(e) => {
-                    if !e.to_string().contains("Database already exists") {
-                        println!("Warning: {}", e);
-                    }
-                    println!("Wallet 'default' already exists");
-                }
-            }
-
-            // Essayer de charger le portefeuille
-            match self.client.load_wallet("default") {
-                Ok(_) => {
-                    println!("Loaded wallet 'default'");
-                    return Ok(());
-                }
-                Err(e) => {
-                    if e.to_string().contains("already loaded") {
-                        println!("Wallet was already loaded");
-                        return Ok(());
-                    }
-                    println!("Waiting for Bitcoin Core to be ready...");
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                }
-            }
-        }
-        Err(anyhow::anyhow!("Failed to setup wallet after multiple attempts"))
-    }
-
-    pub async fn start_daemon(&mut self, config: &BitcoinConfig) -> Result<()> {
-        match self.client.get_blockchain_info() {
-            Ok(_) => {
-                println!("Bitcoin Core est déjà en cours d'exécution");
-            }
-            Err(_) => {
-                let bitcoin_path = if let Some(path) = &config.bitcoin_path {
-                    PathBuf::from(path)
-                } else {
-                    PathBuf::from(r"C:\Program Files\Bitcoin\daemon\bitcoind.exe")
-                };
-
-                if !bitcoin_path.exists() {
-                    return Err(anyhow::anyhow!("Bitcoin executable not found at: {:?}", bitcoin_path));
-                }
-
-                println!("Starting Bitcoin Core from: {:?}", bitcoin_path);
-                
-                let process = Command::new(bitcoin_path)
-                    .arg("-regtest")
-                    .spawn()?;
-
-                self.process = Some(process);
-                
-                // Attendre plus longtemps pour l'initialisation
-                println!("Waiting for Bitcoin Core to initialize...");
-                for _ in 0..30 {  // Attendre jusqu'à 30 secondes
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                    if self.client.get_blockchain_info().is_ok() {
-                        println!("Bitcoin Core is ready!");
-                        break;
-                    }
-                }
-            }
-        }
-        
-        // Attendre encore un peu avant de créer le portefeuille
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-        
-        match self.ensure_wallet().await {
-            Ok(_) => println!("Wallet setup completed"),
-            Err(e) => println!("Warning: Wallet setup failed: {}", e),
-        }
-        
-        Ok(())
-    }
-
-    pub async fn stop_daemon(&mut self) -> Result<()> {
-        if let Some(mut process) = self.process.take() {
-            process.kill()?;
-            process.wait()?;
-        }
-        Ok(())
-    }
-
-    pub async fn send_to_address(&self, address: &str, amount: f64) -> Result<String> {
-        // Convertir l'adresse string en Address Bitcoin
-        let bitcoin_address = Address::from_str(address)?.require_network(Network::Regtest)?;
-        // Convertir le montant f64 en Amount Bitcoin (en BTC)
-        let bitcoin_amount = BitcoinAmount::from_btc(amount)?;
-        
-        let txid = self.client.send_to_address(
-            &bitcoin_address,
-            bitcoin_amount,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None
-        )?;
-        
-        Ok(txid.to_string())
-    }
-
-    pub async fn generate_to_address(&self, blocks: u64, address: &str) -> Result<Vec<String>> {
-        // Convertir l'adresse string en Address Bitcoin
-        let bitcoin_address = Address::from_str(address)?.require_network(Network::Regtest)?;
-        
-        let block_hashes = self.client.generate_to_address(blocks, &bitcoin_address)?;
-        
-        // Convertir les BlockHash en String
-        Ok(block_hashes.iter().map(|h| h.to_string()).collect())
-    }
-
-    pub async fn get_new_address(&self) -> Result<String> {
-        let address = self.client.get_new_address(None, None)?
-            .require_network(Network::Regtest)?;
-        Ok(address.to_string())
-    }
-}
-
-impl Drop for BitcoinNode {
-    fn drop(&mut self) {
-        if let Some(mut process) = self.process.take() {
-            let _ = process.kill();
-            let _ = process.wait();
-        }
-    }
-} 
\ No newline at end of file
+use anyhow::Result;
+use async_trait::async_trait;
+use bitcoin::{Address, Amount as BitcoinAmount};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::backend::{BitcoinBackend, TransactionOutput, TransactionStatus};
+use super::network::Network;
+use super::rpc::{rpc_error_code, BitcoinRpc, RetryPolicy, RPC_WALLET_ALREADY_LOADED, RPC_WALLET_NOT_FOUND};
+
+/// Portefeuille bitcoind utilisé pour financer les nœuds et miner.
+const WALLET_NAME: &str = "default";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BitcoinConfig {
+    pub rpc_host: String,
+    pub rpc_port: u16,
+    pub rpc_user: String,
+    pub rpc_password: String,
+    #[serde(default)]
+    pub network: Network,
+    /// Exécutable bitcoind lancé par `--start-daemons`, `bitcoind` du `PATH` par défaut.
+    pub bitcoin_path: Option<String>,
+    /// Délai maximal d'une requête RPC, en secondes.
+    #[serde(default = "default_rpc_timeout_secs")]
+    pub rpc_timeout_secs: u64,
+    /// Nombre de tentatives d'un appel quand bitcoind est injoignable ou démarre.
+    #[serde(default = "default_rpc_attempts")]
+    pub rpc_attempts: u32,
+}
+
+fn default_rpc_timeout_secs() -> u64 {
+    30
+}
+
+fn default_rpc_attempts() -> u32 {
+    5
+}
+
+pub struct BitcoinNode {
+    rpc: BitcoinRpc,
+    network: Network,
+}
+
+impl BitcoinNode {
+    pub fn new(config: BitcoinConfig) -> Result<Self> {
+        let rpc_url = format!("http://{}:{}", config.rpc_host, config.rpc_port);
+        let retry = RetryPolicy {
+            attempts: config.rpc_attempts.max(1),
+            ..RetryPolicy::default()
+        };
+        let rpc = BitcoinRpc::new(
+            rpc_url,
+            config.rpc_user,
+            config.rpc_password,
+            Duration::from_secs(config.rpc_timeout_secs),
+            retry,
+        )?;
+
+        Ok(Self {
+            rpc,
+            network: config.network,
+        })
+    }
+
+    /// Vérifie qu'une adresse est valide pour le réseau du nœud.
+    fn checked_address(&self, address: &str) -> Result<Address> {
+        Ok(Address::from_str(address)?.require_network(self.network.into())?)
+    }
+
+    /// Vérifie que bitcoind répond, sans réessayer.
+    pub async fn is_ready(&self) -> bool {
+        self.rpc.try_call::<Value>("getblockchaininfo", json!([])).await.is_ok()
+    }
+
+    /// Demande à bitcoind de s'arrêter (`stop`).
+    pub async fn stop(&self) -> Result<()> {
+        self.rpc.try_call::<Value>("stop", json!([])).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BitcoinBackend for BitcoinNode {
+    async fn get_blockchain_info(&self) -> Result<String> {
+        let info: Value = self.rpc.call("getblockchaininfo", json!([])).await?;
+        Ok(serde_json::to_string_pretty(&info)?)
+    }
+
+    async fn ensure_wallet(&self) -> Result<()> {
+        let loaded: Vec<String> = self.rpc.call("listwallets", json!([])).await?;
+        if loaded.iter().any(|wallet| wallet == WALLET_NAME) {
+            return Ok(());
+        }
+
+        match self.rpc.call::<Value>("loadwallet", json!([WALLET_NAME])).await {
+            Ok(_) => {
+                tracing::debug!("Portefeuille {} chargé", WALLET_NAME);
+                Ok(())
+            }
+            Err(e) => match rpc_error_code(&e) {
+                Some(RPC_WALLET_ALREADY_LOADED) => Ok(()),
+                Some(RPC_WALLET_NOT_FOUND) => {
+                    self.rpc.call::<Value>("createwallet", json!([WALLET_NAME])).await?;
+                    tracing::debug!("Portefeuille {} créé", WALLET_NAME);
+                    Ok(())
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    fn network(&self) -> Network {
+        self.network
+    }
+
+    async fn generate_blocks(&self, count: u64) -> Result<Vec<String>> {
+        self.network.require_mining("Le minage de blocs")?;
+        // Vérifier que le portefeuille est chargé
+        self.ensure_wallet().await?;
+
+        let address = self.get_new_address().await?;
+        tracing::debug!("Minage de {} blocs vers {}", count, address);
+
+        self.generate_to_address(count, &address).await
+    }
+
+    async fn generate_to_address(&self, blocks: u64, address: &str) -> Result<Vec<String>> {
+        self.network.require_mining("Le minage de blocs")?;
+        let address = self.checked_address(address)?;
+        self.rpc.try_call("generatetoaddress", json!([blocks, address])).await
+    }
+
+    async fn send_to_address(&self, address: &str, amount: f64) -> Result<String> {
+        let address = self.checked_address(address)?;
+        // Valide le montant avant de l'envoyer à bitcoind
+        let amount = BitcoinAmount::from_btc(amount)?;
+        self.rpc
+            .call_wallet_once(WALLET_NAME, "sendtoaddress", json!([address, amount.to_btc()]))
+            .await
+    }
+
+    async fn get_new_address(&self) -> Result<String> {
+        let address: String = self.rpc.call_wallet(WALLET_NAME, "getnewaddress", json!([])).await?;
+        Ok(self.checked_address(&address)?.to_string())
+    }
+
+    async fn get_transaction_status(&self, txid: &str) -> Result<TransactionStatus> {
+        let tx: RawTransaction = self.rpc.call("getrawtransaction", json!([txid, true])).await?;
+        let mut outputs = Vec::with_capacity(tx.vout.len());
+        for output in tx.vout {
+            // `gettxout` renvoie null pour une sortie déjà dépensée
+            let unspent: Option<Value> = self.rpc.call("gettxout", json!([txid, output.n, true])).await?;
+            outputs.push(TransactionOutput {
+                vout: output.n,
+                amount_sat: BitcoinAmount::from_btc(output.value)?.to_sat(),
+                address: output.script_pub_key.address,
+                spent: unspent.is_none(),
+            });
+        }
+        Ok(TransactionStatus {
+            txid: tx.txid,
+            confirmations: tx.confirmations,
+            outputs,
+        })
+    }
+}
+
+/// Réponse de `getrawtransaction` en mode verbeux.
+#[derive(Deserialize)]
+struct RawTransaction {
+    txid: String,
+    #[serde(default)]
+    confirmations: u64,
+    vout: Vec<RawOutput>,
+}
+
+#[derive(Deserialize)]
+struct RawOutput {
+    value: f64,
+    n: u32,
+    #[serde(rename = "scriptPubKey")]
+    script_pub_key: RawScriptPubKey,
+}
+
+#[derive(Deserialize)]
+struct RawScriptPubKey {
+    address: Option<String>,
+}
@@ -0,0 +1,261 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Bitcoin Core est en cours de démarrage (`RPC_IN_WARMUP`).
+pub const RPC_IN_WARMUP: i64 = -28;
+/// Le portefeuille demandé n'existe pas (`RPC_WALLET_NOT_FOUND`).
+pub const RPC_WALLET_NOT_FOUND: i64 = -18;
+/// Le portefeuille est déjà chargé (`RPC_WALLET_ALREADY_LOADED`).
+pub const RPC_WALLET_ALREADY_LOADED: i64 = -35;
+
+/// Erreur renvoyée par bitcoind dans le champ `error` d'une réponse JSON-RPC.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bitcoind RPC error {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Nombre de tentatives et délai entre deux tentatives d'un même appel.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(4),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Une seule tentative, pour sonder un démon qui peut ne pas être lancé.
+    pub fn once() -> Self {
+        Self { attempts: 1, ..Self::default() }
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    result: Option<Value>,
+    error: Option<RpcError>,
+}
+
+/// Client JSON-RPC asynchrone pour Bitcoin Core.
+///
+/// Les connexions refusées et `RPC_IN_WARMUP`, où la requête n'a pas été
+/// exécutée, sont réessayées selon la `RetryPolicy`. Un délai dépassé ne l'est
+/// pas : bitcoind a pu exécuter l'appel sans que la réponse arrive. Les autres
+/// erreurs RPC sont renvoyées immédiatement sous forme de `RpcError`.
+#[derive(Debug, Clone)]
+pub struct BitcoinRpc {
+    http: reqwest::Client,
+    url: String,
+    user: String,
+    password: String,
+    retry: RetryPolicy,
+}
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+impl BitcoinRpc {
+    pub fn new(url: String, user: String, password: String, timeout: Duration, retry: RetryPolicy) -> Result<Self> {
+        let http = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(Self { http, url, user, password, retry })
+    }
+
+    /// Appelle une méthode sans portefeuille, ex. `getblockchaininfo`.
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        self.call_with(&self.url, method, params, self.retry).await
+    }
+
+    /// Appelle une méthode sur le portefeuille `wallet` (`/wallet/<nom>`).
+    pub async fn call_wallet<T: DeserializeOwned>(&self, wallet: &str, method: &str, params: Value) -> Result<T> {
+        let url = format!("{}/wallet/{}", self.url, wallet);
+        self.call_with(&url, method, params, self.retry).await
+    }
+
+    /// Appelle une seule fois une méthode du portefeuille `wallet` qui n'est
+    /// pas idempotente, comme `sendtoaddress` : la rejouer enverrait deux fois
+    /// les fonds.
+    pub async fn call_wallet_once<T: DeserializeOwned>(&self, wallet: &str, method: &str, params: Value) -> Result<T> {
+        let url = format!("{}/wallet/{}", self.url, wallet);
+        self.call_with(&url, method, params, RetryPolicy::once()).await
+    }
+
+    /// Appelle une méthode une seule fois, sans réessayer : pour sonder un
+    /// démon, ou pour une méthode non idempotente comme `generatetoaddress`.
+    pub async fn try_call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        self.call_with(&self.url, method, params, RetryPolicy::once()).await
+    }
+
+    async fn call_with<T: DeserializeOwned>(&self, url: &str, method: &str, params: Value, retry: RetryPolicy) -> Result<T> {
+        let mut backoff = retry.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match self.send(url, method, &params).await {
+                Ok(value) => return Ok(serde_json::from_value(value)?),
+                Err(e) if attempt < retry.attempts && is_retryable(&e) => {
+                    tracing::debug!("{} a échoué (tentative {}/{}) : {}", method, attempt, retry.attempts, e);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(retry.max_backoff);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.context(format!("Appel RPC {} vers bitcoind", method))),
+            }
+        }
+    }
+
+    async fn send(&self, url: &str, method: &str, params: &Value) -> Result<Value> {
+        let body = json!({
+            "jsonrpc": "1.0",
+            "id": NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+        let response = self
+            .http
+            .post(url)
+            .basic_auth(&self.user, Some(&self.password))
+            .json(&body)
+            .send()
+            .await?;
+
+        // bitcoind renvoie les erreurs RPC avec un statut HTTP 404 ou 500 :
+        // seul un corps illisible est une erreur de transport.
+        let status = response.status();
+        let text = response.text().await?;
+        let response: Response = serde_json::from_str(&text)
+            .map_err(|_| anyhow::anyhow!("Réponse HTTP {} inattendue de bitcoind : {}", status, text.trim()))?;
+        match (response.error, response.result) {
+            (Some(error), _) => Err(error.into()),
+            (None, result) => Ok(result.unwrap_or(Value::Null)),
+        }
+    }
+}
+
+fn is_retryable(error: &anyhow::Error) -> bool {
+    if let Some(rpc) = error.downcast_ref::<RpcError>() {
+        return rpc.code == RPC_IN_WARMUP;
+    }
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect())
+}
+
+/// Code de l'erreur RPC à l'origine de `error`, s'il y en a une.
+pub fn rpc_error_code(error: &anyhow::Error) -> Option<i64> {
+    error.chain().find_map(|cause| cause.downcast_ref::<RpcError>()).map(|e| e.code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serveur HTTP minimal qui répond aux requêtes successives avec `bodies`.
+    async fn serve(bodies: Vec<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            for body in bodies {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0; 4096];
+                let _ = socket.read(&mut buffer).await.unwrap();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        url
+    }
+
+    fn client(url: String, timeout: Duration) -> BitcoinRpc {
+        let retry = RetryPolicy {
+            attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+        };
+        BitcoinRpc::new(url, "user".to_string(), "pass".to_string(), timeout, retry).unwrap()
+    }
+
+    #[tokio::test]
+    async fn warmup_errors_are_retried() {
+        let url = serve(vec![
+            r#"{"result":null,"error":{"code":-28,"message":"Loading block index..."},"id":1}"#,
+            r#"{"result":{"blocks":101},"error":null,"id":2}"#,
+        ])
+        .await;
+        let info: Value = client(url, Duration::from_secs(5)).call("getblockchaininfo", json!([])).await.unwrap();
+        assert_eq!(info["blocks"], 101);
+    }
+
+    #[tokio::test]
+    async fn other_rpc_errors_are_returned_with_their_code() {
+        let url = serve(vec![r#"{"result":null,"error":{"code":-18,"message":"Requested wallet does not exist"},"id":1}"#]).await;
+        let err = client(url, Duration::from_secs(5))
+            .call_wallet::<String>("default", "getnewaddress", json!([]))
+            .await
+            .unwrap_err();
+        assert_eq!(rpc_error_code(&err), Some(RPC_WALLET_NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn unresponsive_server_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let started = std::time::Instant::now();
+        let result: Result<Value> = client(url, Duration::from_millis(50)).call("getblockchaininfo", json!([])).await;
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn timed_out_send_is_not_sent_again() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(AtomicU64::new(0));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            // bitcoind reçoit la requête mais répond trop tard
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buffer = vec![0; 4096];
+                let _ = socket.read(&mut buffer).await;
+                received.fetch_add(1, Ordering::SeqCst);
+                sockets.push(socket);
+            }
+        });
+
+        let rpc = client(url, Duration::from_millis(50));
+        let result: Result<String> = rpc.call_wallet_once("default", "sendtoaddress", json!(["bcrt1q", 1.0])).await;
+        assert!(result.is_err());
+        let result: Result<String> = rpc.call_wallet("default", "getnewaddress", json!([])).await;
+        assert!(result.is_err());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(requests.load(Ordering::SeqCst), 2, "a timed out call is never replayed");
+    }
+}
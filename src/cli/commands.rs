@@ -0,0 +1,747 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{demo, Cli, Command, OutputFormat};
+use crate::bitcoin::{BitcoinBackend, BitcoinNode, TransactionStatus};
+use crate::dashboard;
+use crate::lightning::{
+    BackendKind, ChannelEntry, CloseType, LightningBackend, LightningNode, LndNode, Payment, PaymentStatus,
+};
+use crate::scenario::{ChannelWorkflow, Orchestrator, Scenario, ScenarioNodeHandle};
+use crate::settings::Settings;
+use crate::supervisor::{self, Supervisor};
+use crate::visualization::{self, NetworkGraph};
+
+/// Délai d'attente de la confirmation d'une fermeture de canal.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Point d'entrée de la ligne de commande : charge la configuration puis
+/// exécute la sous-commande demandée.
+pub async fn run(cli: Cli) -> Result<()> {
+    if cli.start_daemons && matches!(cli.command, Command::Up) {
+        return Err(anyhow::anyhow!("up démarre déjà les démons : --start-daemons ne s'y ajoute pas"));
+    }
+    let mut settings = Settings::load(&cli.config)?;
+    if let Command::Scenario { file, .. } = &cli.command {
+        // Les nœuds propres au scénario doivent exister avant --start-daemons
+        Scenario::load(file)?.register_nodes(&mut settings)?;
+    }
+
+    let supervisor = if cli.start_daemons {
+        Some(Supervisor::start(&settings).await?)
+    } else {
+        None
+    };
+
+    let bitcoin_node = BitcoinNode::new(settings.bitcoin.clone())?;
+    let result = dispatch(cli.command, &Session::new(&settings, &bitcoin_node)).await;
+
+    match supervisor {
+        Some(supervisor) => command_result(result, supervisor.shutdown().await),
+        None => result,
+    }
+}
+
+/// L'erreur de la commande prime sur celle de l'arrêt des démons, qui n'est
+/// alors que journalisée.
+fn command_result(result: Result<()>, shutdown: Result<()>) -> Result<()> {
+    match (result, shutdown) {
+        (Err(e), Err(shutdown_error)) => {
+            tracing::warn!("Arrêt des démons incomplet : {:#}", shutdown_error);
+            Err(e)
+        }
+        (result, Ok(())) => result,
+        (Ok(()), Err(shutdown_error)) => Err(shutdown_error),
+    }
+}
+
+/// Connexions ouvertes pendant l'exécution d'une commande. Chaque nœud
+/// Lightning n'est connecté qu'une fois, à sa première utilisation.
+pub(super) struct Session<'a> {
+    pub settings: &'a Settings,
+    pub bitcoin: &'a dyn BitcoinBackend,
+    nodes: Mutex<HashMap<String, Arc<dyn LightningBackend>>>,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(settings: &'a Settings, bitcoin: &'a dyn BitcoinBackend) -> Self {
+        Self {
+            settings,
+            bitcoin,
+            nodes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Remplace la connexion au nœud `backend.id()`, par exemple par un nœud simulé.
+    #[cfg(test)]
+    pub fn with_node(self, backend: Arc<dyn LightningBackend>) -> Self {
+        self.nodes.lock().unwrap().insert(backend.id().to_string(), backend);
+        self
+    }
+
+    /// Backend du nœud `id`, connecté au premier appel.
+    pub async fn node(&self, id: &str) -> Result<Arc<dyn LightningBackend>> {
+        if let Some(node) = self.nodes.lock().unwrap().get(id) {
+            return Ok(node.clone());
+        }
+        let node = connect_node(self.settings, id).await?;
+        self.nodes.lock().unwrap().insert(id.to_string(), node.clone());
+        Ok(node)
+    }
+}
+
+async fn dispatch(command: Command, session: &Session<'_>) -> Result<()> {
+    let settings = session.settings;
+    let bitcoin_node = session.bitcoin;
+    match command {
+        Command::Info { node } => {
+            let blockchain_info = bitcoin_node.get_blockchain_info().await?;
+            println!("Réseau : {}", bitcoin_node.network());
+            println!("Blockchain : {}", blockchain_info);
+
+            for node_settings in &settings.lightning {
+                if node.as_deref().is_some_and(|id| id != node_settings.id) {
+                    continue;
+                }
+                let lightning_node = session.node(&node_settings.id).await?;
+                let (node_info, funds) =
+                    tokio::try_join!(lightning_node.get_node_info(), lightning_node.list_funds())?;
+                println!("\n[{}] {} ({})", lightning_node.id(), node_info.display_name(), node_info.id);
+                println!("  Réseau : {}, hauteur : {}", node_info.network, node_info.blockheight);
+                println!(
+                    "  Pairs : {}, canaux actifs : {}, en attente : {}",
+                    node_info.num_peers, node_info.num_active_channels, node_info.num_pending_channels
+                );
+                println!(
+                    "  On-chain confirmé : {} sats, dans les canaux : {} sats",
+                    funds.confirmed_onchain_msat() / 1000,
+                    funds.channel_balance_msat() / 1000
+                );
+            }
+        }
+        Command::Fund { node, amount, blocks } => {
+            let lightning_node = session.node(&node).await?;
+            let address = lightning_node.get_new_address().await?;
+            println!("Adresse Lightning obtenue : {}", address);
+
+            bitcoin_node.ensure_wallet().await?;
+            let tx_id = bitcoin_node.send_to_address(&address, amount).await?;
+            println!("Transaction envoyée : {}", tx_id);
+
+            if bitcoin_node.network().allows_mining() {
+                let block_hashes = bitcoin_node.generate_blocks(blocks).await?;
+                println!("{} blocs générés", block_hashes.len());
+            } else {
+                println!("Réseau {} : attendez que la transaction soit confirmée", bitcoin_node.network());
+            }
+        }
+        Command::Connect { node, peer } => {
+            let lightning_node = session.node(&node).await?;
+            let peer_settings = settings.lightning_node(&peer)?;
+            let peer_id = node_pubkey(session.node(&peer).await?.as_ref()).await?;
+
+            lightning_node
+                .connect_peer(&peer_id, &peer_settings.host, supervisor::node_port(peer_settings)?)
+                .await?;
+            println!("Connecté à {} ({})", peer, peer_id);
+        }
+        Command::OpenChannel { node, peer, amount_sat, push_msat } => {
+            let lightning_node = session.node(&node).await?;
+            let peer_id = node_pubkey(session.node(&peer).await?.as_ref()).await?;
+
+            let opening = lightning_node.open_channel(&peer_id, amount_sat, push_msat).await?;
+            println!("Canal ouvert vers {} : {}:{}", peer, opening.txid, opening.outnum);
+        }
+        Command::CloseChannel { node, channel, unilateral_timeout, force, confirmations } => {
+            let lightning_node = session.node(&node).await?;
+            let channel = find_channel(session, lightning_node.as_ref(), &channel).await?;
+            let unilateral_timeout = if force {
+                Some(Duration::ZERO)
+            } else {
+                unilateral_timeout.map(Duration::from_secs)
+            };
+
+            if bitcoin_node.network().allows_mining() {
+                let workflow = ChannelWorkflow::new(bitcoin_node, confirmations, CLOSE_TIMEOUT).await?;
+                let (closing, status) = workflow.close(lightning_node.as_ref(), &channel, unilateral_timeout).await?;
+                println!("Fermeture {} du canal {}", closing.close_type, channel.funding_txid);
+                print_transaction(&status);
+                if closing.close_type == CloseType::Unilateral {
+                    println!("Fermeture forcée : les fonds du nœud seront récupérés après son délai (to_self_delay)");
+                }
+            } else {
+                let closing = lightning_node.close_channel(&channel, unilateral_timeout).await?;
+                println!(
+                    "Fermeture {} du canal {} : {}",
+                    closing.close_type,
+                    channel.funding_txid,
+                    closing.txid.as_deref().unwrap_or("aucune transaction")
+                );
+            }
+        }
+        Command::Invoice { node, amount_msat, label, description } => {
+            let lightning_node = session.node(&node).await?;
+            let label = label.unwrap_or_else(|| {
+                format!("invoice_{}", chrono::Utc::now().timestamp_millis())
+            });
+
+            let invoice = lightning_node.create_invoice(amount_msat, &label, &description).await?;
+            println!("Facture créée : {}", invoice.bolt11);
+        }
+        Command::Pay { node, bolt11 } => {
+            let lightning_node = session.node(&node).await?;
+            let payment = lightning_node.pay(&bolt11).await?;
+            print_payment(&payment);
+            payment.ensure_complete()?;
+        }
+        Command::Keysend { node, peer, amount_msat } => {
+            let lightning_node = session.node(&node).await?;
+            let peer_id = node_pubkey(session.node(&peer).await?.as_ref()).await?;
+            let payment = lightning_node.keysend(&peer_id, amount_msat).await?;
+            print_payment(&payment);
+            payment.ensure_complete()?;
+        }
+        Command::Decode { node, bolt11 } => {
+            let invoice = session.node(&node).await?.decode_invoice(&bolt11).await?;
+            println!("Destinataire : {}", invoice.payee);
+            println!("Hash : {}", invoice.payment_hash);
+            match invoice.amount_msat {
+                Some(amount) => println!("Montant : {} msat", amount),
+                None => println!("Montant : libre"),
+            }
+            if let Some(description) = &invoice.description {
+                println!("Description : {}", description);
+            }
+            println!("Créée le {}, valable {} s", invoice.created_at, invoice.expiry);
+        }
+        Command::Payments { node } => {
+            let payments = session.node(&node).await?.list_payments().await?;
+            for payment in &payments {
+                print_payment(payment);
+            }
+            println!("{} paiements", payments.len());
+        }
+        Command::Invoices { node } => {
+            let invoices = session.node(&node).await?.list_invoices().await?;
+            for invoice in &invoices {
+                println!(
+                    "{} {} : {} msat ({})",
+                    invoice.label.as_deref().unwrap_or("-"),
+                    invoice.status,
+                    invoice.amount_msat.unwrap_or_default(),
+                    invoice.payment_hash
+                );
+            }
+            println!("{} factures", invoices.len());
+        }
+        Command::WaitInvoice { node, payment_hash } => {
+            let invoice = session.node(&node).await?.wait_invoice(&payment_hash).await?;
+            println!(
+                "Facture {} : {}, {} msat reçus",
+                invoice.payment_hash,
+                invoice.status,
+                invoice.amount_received_msat.unwrap_or_default()
+            );
+        }
+        Command::Snapshot { output, format, local_only } => {
+            let network = snapshot(session, !local_only).await?;
+            let output = output.unwrap_or_else(|| format.default_output());
+            write_graph(&network, format, &output)?;
+        }
+        Command::Render { input, output, format } => {
+            let source = std::fs::read_to_string(&input)
+                .with_context(|| format!("Impossible de lire {:?}", input))?;
+            let output = output.unwrap_or_else(|| format.default_output());
+            // Un snapshot JSON se redessine dans tous les formats et un SVG se
+            // convertit sans Graphviz ; une source DOT passe par Graphviz
+            match source.trim_start().chars().next() {
+                Some('{') => write_graph(&NetworkGraph::from_json(&source)?, format, &output)?,
+                Some('<') => convert_svg(&source, format, &output)?,
+                _ => render_graph(&source, format, &output)?,
+            }
+        }
+        Command::Demo => demo::run(session, &OutputFormat::Json.default_output()).await?,
+        Command::Scenario { file, output, format } => {
+            let scenario = Scenario::load(&file)?;
+            scenario.validate()?;
+            let mut nodes = HashMap::new();
+            for node in &scenario.nodes {
+                let node_settings = settings.lightning_node(&node.id)?;
+                nodes.insert(node.id.clone(), ScenarioNodeHandle {
+                    backend: session.node(&node.id).await?,
+                    host: node_settings.host.clone(),
+                    port: supervisor::node_port(node_settings)?,
+                });
+            }
+            Orchestrator::new(bitcoin_node, nodes).run(&scenario).await?;
+
+            if let Some(output) = output {
+                let network = snapshot(session, true).await?;
+                write_graph(&network, format, &output)?;
+            }
+        }
+        Command::Up => {
+            let supervisor = Supervisor::start(settings).await?;
+            println!("Démons prêts, Ctrl-C pour les arrêter");
+            tokio::signal::ctrl_c().await?;
+            supervisor.shutdown().await?;
+        }
+        Command::Dashboard { interval } => {
+            let mut nodes = Vec::new();
+            for node_settings in &settings.lightning {
+                nodes.push(session.node(&node_settings.id).await?);
+            }
+            dashboard::run(&nodes, Duration::from_secs(interval.max(1))).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Crée le backend du nœud `id` selon sa configuration (CLN ou LND) et ouvre sa connexion.
+async fn connect_node(settings: &Settings, id: &str) -> Result<Arc<dyn LightningBackend>> {
+    let node_settings = settings.lightning_node(id)?;
+    let backend: Arc<dyn LightningBackend> = match node_settings.backend {
+        BackendKind::Cln => {
+            let mut node = LightningNode::new(settings.lightning_config(node_settings), node_settings.id.clone());
+            node.connect_rpc()
+                .await
+                .with_context(|| format!("Connexion RPC au nœud {} impossible", id))?;
+            Arc::new(node)
+        }
+        BackendKind::Lnd => Arc::new(LndNode::new(settings.lnd_config(node_settings)?, node_settings.id.clone())?),
+    };
+    Ok(backend)
+}
+
+/// Canal non fermé de `node` désigné par son short channel id, son channel id,
+/// son txid de financement ou son pair (identifiant de la configuration ou clé publique).
+async fn find_channel(session: &Session<'_>, node: &dyn LightningBackend, channel: &str) -> Result<ChannelEntry> {
+    let peer_id = match session.settings.lightning_node(channel) {
+        Ok(_) => Some(node_pubkey(session.node(channel).await?.as_ref()).await?),
+        Err(_) => None,
+    };
+    node.list_funds()
+        .await?
+        .channels
+        .into_iter()
+        .filter(|c| !c.state.is_closing())
+        .find(|c| {
+            [c.short_channel_id.as_deref(), c.channel_id.as_deref(), Some(c.funding_txid.as_str()), Some(c.peer_id.as_str())]
+                .contains(&Some(channel))
+                || peer_id.as_deref() == Some(c.peer_id.as_str())
+        })
+        .ok_or_else(|| anyhow::anyhow!("Aucun canal {:?} à fermer sur le nœud {}", channel, node.id()))
+}
+
+/// Affiche les confirmations et les sorties d'une transaction on-chain.
+fn print_transaction(status: &TransactionStatus) {
+    println!("Transaction {} : {} confirmations", status.txid, status.confirmations);
+    for output in &status.outputs {
+        println!(
+            "  #{} {} sats vers {} ({})",
+            output.vout,
+            output.amount_sat,
+            output.address.as_deref().unwrap_or("?"),
+            if output.spent { "dépensée" } else { "non dépensée" }
+        );
+    }
+}
+
+/// Affiche l'issue d'un paiement, sa route et ses frais.
+fn print_payment(payment: &Payment) {
+    println!(
+        "Paiement {} : {} msat envoyés, dont {} msat de frais ({})",
+        payment.status,
+        payment.amount_sent_msat,
+        payment.fee_msat(),
+        payment.payment_hash
+    );
+    match &payment.route {
+        Some(route) => {
+            for hop in route {
+                println!(
+                    "  → {} via {} : {} msat",
+                    hop.node_id,
+                    hop.short_channel_id.as_deref().unwrap_or("?"),
+                    hop.amount_msat
+                );
+            }
+        }
+        None if payment.status == PaymentStatus::Complete => println!("  Route non communiquée par le nœud"),
+        None => {}
+    }
+    if let Some(reason) = &payment.failure_reason {
+        println!("  Échec : {}", reason);
+    }
+}
+
+async fn node_pubkey(node: &dyn LightningBackend) -> Result<String> {
+    Ok(node.get_node_info().await?.id)
+}
+
+/// Interroge chaque nœud de la configuration et construit le graphe du réseau,
+/// complété si demandé par la vue gossip de chaque nœud.
+async fn snapshot(session: &Session<'_>, with_gossip: bool) -> Result<NetworkGraph> {
+    let mut network = NetworkGraph::new();
+    for node_settings in &session.settings.lightning {
+        let node = session.node(&node_settings.id).await?;
+        network.update_from_backend(node.as_ref(), with_gossip).await?;
+        println!("Nœud {} ajouté au graphe", node.id());
+    }
+    println!("{} nœuds, {} canaux", network.node_count(), network.channel_count());
+    Ok(network)
+}
+
+/// Écrit un graphe construit par l'outil ou relu d'un snapshot JSON : le PNG,
+/// le PDF et la page HTML sont tirés du rendu SVG natif, sans Graphviz.
+fn write_graph(network: &NetworkGraph, format: OutputFormat, output: &Path) -> Result<()> {
+    match format {
+        OutputFormat::Dot => return render_graph(&network.to_dot(), format, output),
+        OutputFormat::Html => write_file(output, network.to_html())?,
+        OutputFormat::Json => write_file(output, network.to_json()?)?,
+        OutputFormat::Graphml => write_file(output, network.to_graphml())?,
+        OutputFormat::Gexf => write_file(output, network.to_gexf())?,
+        OutputFormat::Mermaid => write_file(output, network.to_mermaid())?,
+        OutputFormat::Plantuml => write_file(output, network.to_plantuml())?,
+        other => return convert_svg(&network.to_svg(), other, output),
+    }
+    println!("Graphe écrit dans {:?}", absolute(output));
+    Ok(())
+}
+
+/// Convertit une source DOT avec Graphviz, ou la recopie telle quelle.
+fn render_graph(dot_source: &str, format: OutputFormat, output: &Path) -> Result<()> {
+    match format {
+        OutputFormat::Dot => write_file(output, dot_source)?,
+        OutputFormat::Png | OutputFormat::Svg | OutputFormat::Pdf => {
+            visualization::render_dot(dot_source, format.extension(), output)?
+        }
+        other => {
+            return Err(anyhow::anyhow!(
+                "Le format {0} se construit depuis le graphe : `snapshot --format {0}`, ou `render` d'un snapshot JSON",
+                other.extension()
+            ))
+        }
+    }
+    println!("Graphe écrit dans {:?}", absolute(output));
+    Ok(())
+}
+
+/// Convertit un document SVG en PNG, en PDF ou en page HTML dans le processus, ou le recopie.
+fn convert_svg(svg: &str, format: OutputFormat, output: &Path) -> Result<()> {
+    match format {
+        OutputFormat::Svg => write_file(output, svg)?,
+        OutputFormat::Png => write_file(output, visualization::svg_to_png(svg)?)?,
+        OutputFormat::Pdf => write_file(output, visualization::svg_to_pdf(svg)?)?,
+        OutputFormat::Html => write_file(output, visualization::svg_to_html(svg))?,
+        other => return Err(anyhow::anyhow!("Un fichier SVG ne peut pas être converti en {}", other.extension())),
+    }
+    println!("Graphe écrit dans {:?}", absolute(output));
+    Ok(())
+}
+
+fn write_file(output: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    std::fs::write(output, contents).with_context(|| format!("Impossible d'écrire {:?}", output))
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::env::current_dir()
+        .map(|dir| dir.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::bitcoin::MockBitcoin;
+    use crate::lightning::{gossip_channel, gossip_node, ChannelState, MockLightning, MOCK_MIN_DEPTH};
+    use clap::Parser;
+
+    use crate::lightning::PaymentStatus;
+    use crate::visualization::fixtures::{channel, two_node_network, ALICE, BOB};
+
+    pub(in crate::cli) fn two_node_settings() -> Settings {
+        Settings::from_toml(
+            r#"
+            [bitcoin]
+            rpc_host = "127.0.0.1"
+            rpc_port = 18443
+            rpc_user = "user"
+            rpc_password = "password"
+            network = "regtest"
+
+            [[lightning]]
+            id = "node1"
+            lightning_dir = "/tmp/node1"
+
+            [[lightning]]
+            id = "node2"
+            lightning_dir = "/tmp/node2"
+            port = 9736
+            "#,
+        )
+        .unwrap()
+    }
+
+    /// Deux nœuds simulés, `node1` (ALICE) relié à la chaîne et `node2` (BOB).
+    pub(in crate::cli) fn two_nodes(chain: &MockBitcoin) -> (Arc<MockLightning>, Arc<MockLightning>) {
+        (
+            Arc::new(MockLightning::new("node1", ALICE).with_chain(chain)),
+            Arc::new(MockLightning::new("node2", BOB).with_chain(chain)),
+        )
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("lightning_viz_{}_{}", std::process::id(), name))
+    }
+
+    #[tokio::test]
+    async fn up_refuses_start_daemons() {
+        let cli = Cli::try_parse_from(["lightning_viz", "--start-daemons", "up"]).unwrap();
+        let message = run(cli).await.unwrap_err().to_string();
+        assert!(message.contains("--start-daemons"), "{}", message);
+    }
+
+    #[test]
+    fn command_error_wins_over_shutdown_error() {
+        let failed = || Err(anyhow::anyhow!("commande"));
+        let shutdown_failed = || Err(anyhow::anyhow!("arrêt"));
+        assert_eq!(command_result(failed(), shutdown_failed()).unwrap_err().to_string(), "commande");
+        assert_eq!(command_result(Ok(()), shutdown_failed()).unwrap_err().to_string(), "arrêt");
+        assert_eq!(command_result(failed(), Ok(())).unwrap_err().to_string(), "commande");
+        assert!(command_result(Ok(()), Ok(())).is_ok());
+    }
+
+    #[tokio::test]
+    async fn fund_sends_coins_and_mines_confirmations() {
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let (node1, node2) = two_nodes(&chain);
+        let session = Session::new(&settings, &chain).with_node(node1.clone()).with_node(node2);
+
+        dispatch(Command::Fund { node: "node1".to_string(), amount: 0.5, blocks: 6 }, &session)
+            .await
+            .unwrap();
+
+        assert_eq!(chain.height(), 6);
+        let funds = node1.list_funds().await.unwrap();
+        assert_eq!(funds.confirmed_onchain_msat(), 50_000_000_000);
+    }
+
+    #[tokio::test]
+    async fn channel_is_opened_to_the_configured_peer() {
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let (node1, node2) = two_nodes(&chain);
+        let session = Session::new(&settings, &chain).with_node(node1.clone()).with_node(node2);
+
+        for command in [
+            Command::Fund { node: "node1".to_string(), amount: 1.0, blocks: 1 },
+            Command::Connect { node: "node1".to_string(), peer: "node2".to_string() },
+            Command::OpenChannel { node: "node1".to_string(), peer: "node2".to_string(), amount_sat: 100_000, push_msat: 0 },
+        ] {
+            dispatch(command, &session).await.unwrap();
+        }
+        chain.generate_blocks(MOCK_MIN_DEPTH).await.unwrap();
+
+        assert_eq!(node1.peers(), vec![BOB.to_string()]);
+        let channels = node1.list_funds().await.unwrap().channels;
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].peer_id, BOB);
+        assert_eq!(channels[0].state, ChannelState::ChanneldNormal);
+    }
+
+    #[tokio::test]
+    async fn info_only_queries_the_requested_node() {
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let (node1, node2) = two_nodes(&chain);
+        node1.fail_on("get_node_info", "node1 is down");
+        let session = Session::new(&settings, &chain).with_node(node1).with_node(node2);
+
+        dispatch(Command::Info { node: Some("node2".to_string()) }, &session).await.unwrap();
+        let error = dispatch(Command::Info { node: None }, &session).await.unwrap_err();
+        assert!(error.to_string().contains("node1 is down"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn invoice_is_paid_by_another_node() {
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let (_, node2) = two_nodes(&chain);
+        let node1 = MockLightning::new("node1", ALICE).with_channel(channel(BOB, Some("103x1x0"), 700_000_000));
+        let node1 = Arc::new(node1);
+        let session = Session::new(&settings, &chain).with_node(node1.clone()).with_node(node2.clone());
+
+        let invoice = Command::Invoice {
+            node: "node2".to_string(),
+            amount_msat: 5_000_000,
+            label: Some("coffee".to_string()),
+            description: "café".to_string(),
+        };
+        dispatch(invoice, &session).await.unwrap();
+        let invoice = node2.list_invoices().await.unwrap().remove(0);
+        assert_eq!(invoice.label.as_deref(), Some("coffee"));
+
+        let bolt11 = invoice.bolt11.unwrap();
+        dispatch(Command::Pay { node: "node1".to_string(), bolt11 }, &session).await.unwrap();
+        let payments = node1.list_payments().await.unwrap();
+        assert_eq!(payments[0].payment_hash, invoice.payment_hash);
+        assert_eq!(payments[0].status, PaymentStatus::Complete);
+    }
+
+    #[tokio::test]
+    async fn failed_payment_is_an_error() {
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let (node1, node2) = two_nodes(&chain);
+        let session = Session::new(&settings, &chain).with_node(node1.clone()).with_node(node2.clone());
+        let invoice = node2.create_invoice(5_000_000, "coffee", "").await.unwrap();
+
+        let pay = Command::Pay { node: "node1".to_string(), bolt11: invoice.bolt11 };
+        assert!(dispatch(pay, &session).await.is_err(), "node1 has no channel to pay through");
+        assert_eq!(node1.list_payments().await.unwrap()[0].status, PaymentStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn json_snapshot_is_rendered_again() {
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let session = Session::new(&settings, &chain);
+        let (input, output) = (temp_path("render.json"), temp_path("render.dot"));
+        std::fs::write(&input, two_node_network().to_json().unwrap()).unwrap();
+
+        let render = Command::Render { input: input.clone(), output: Some(output.clone()), format: OutputFormat::Dot };
+        dispatch(render, &session).await.unwrap();
+        let dot = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&input).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        assert!(dot.contains(ALICE) && dot.contains("103x1x0"), "{}", dot);
+    }
+
+    #[tokio::test]
+    async fn default_render_reads_the_json_snapshot_without_graphviz() {
+        let Command::Render { input, format, .. } = Cli::try_parse_from(["lightning_viz", "render"]).unwrap().command
+        else {
+            panic!("render expected");
+        };
+        assert_eq!(input, OutputFormat::Json.default_output());
+
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let session = Session::new(&settings, &chain);
+        let (input, output) = (temp_path("default_render.json"), temp_path("default_render.png"));
+        std::fs::write(&input, two_node_network().to_json().unwrap()).unwrap();
+        let render = Command::Render { input: input.clone(), output: Some(output.clone()), format };
+        dispatch(render, &session).await.unwrap();
+        let png = std::fs::read(&output).unwrap();
+        std::fs::remove_file(&input).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        assert!(png.starts_with(b"\x89PNG"), "format {:?}", format);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn channel_is_closed_by_peer_name() {
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let (node1, node2) = two_nodes(&chain);
+        let session = Session::new(&settings, &chain).with_node(node1.clone()).with_node(node2);
+
+        for command in [
+            Command::Fund { node: "node1".to_string(), amount: 1.0, blocks: 1 },
+            Command::Connect { node: "node1".to_string(), peer: "node2".to_string() },
+            Command::OpenChannel { node: "node1".to_string(), peer: "node2".to_string(), amount_sat: 100_000, push_msat: 0 },
+        ] {
+            dispatch(command, &session).await.unwrap();
+        }
+        chain.generate_blocks(MOCK_MIN_DEPTH).await.unwrap();
+        let close = || Command::CloseChannel {
+            node: "node1".to_string(),
+            channel: "node2".to_string(),
+            unilateral_timeout: None,
+            force: false,
+            confirmations: 2,
+        };
+        dispatch(close(), &session).await.unwrap();
+
+        let channel = &node1.list_funds().await.unwrap().channels[0];
+        assert_eq!(channel.state, ChannelState::Onchain);
+        let err = dispatch(close(), &session).await.unwrap_err();
+        assert!(err.to_string().contains("à fermer"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn keysend_is_listed_with_its_route() {
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let (node1, node2) = two_nodes(&chain);
+        let session = Session::new(&settings, &chain).with_node(node1.clone()).with_node(node2);
+
+        for command in [
+            Command::Fund { node: "node1".to_string(), amount: 1.0, blocks: 1 },
+            Command::Connect { node: "node1".to_string(), peer: "node2".to_string() },
+            Command::OpenChannel { node: "node1".to_string(), peer: "node2".to_string(), amount_sat: 100_000, push_msat: 0 },
+        ] {
+            dispatch(command, &session).await.unwrap();
+        }
+        let keysend = || Command::Keysend { node: "node1".to_string(), peer: "node2".to_string(), amount_msat: 5_000_000 };
+        let err = dispatch(keysend(), &session).await.unwrap_err().to_string();
+        assert!(err.contains("Ran out of routes"), "channel is not confirmed yet: {}", err);
+
+        chain.generate_blocks(MOCK_MIN_DEPTH).await.unwrap();
+        dispatch(keysend(), &session).await.unwrap();
+        dispatch(Command::Payments { node: "node1".to_string() }, &session).await.unwrap();
+
+        let payments = node1.list_payments().await.unwrap();
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[1].route.as_ref().unwrap()[0].node_id, BOB);
+        assert_eq!(node1.list_funds().await.unwrap().channel_balance_msat(), 95_000_000);
+    }
+
+    #[tokio::test]
+    async fn snapshot_merges_gossip_from_every_node() {
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let gossip = (
+            vec![gossip_node(ALICE, "alice"), gossip_node(BOB, "bob")],
+            vec![gossip_channel(ALICE, BOB, "103x1x0", 100_000), gossip_channel(BOB, ALICE, "103x1x0", 100_000)],
+        );
+        let node1 = MockLightning::new("node1", ALICE).with_gossip(gossip.0.clone(), gossip.1.clone());
+        let node2 = MockLightning::new("node2", BOB).with_gossip(gossip.0, gossip.1);
+        let session = Session::new(&settings, &chain)
+            .with_node(Arc::new(node1))
+            .with_node(Arc::new(node2));
+
+        let network = snapshot(&session, true).await.unwrap();
+        assert_eq!(network.node_count(), 2);
+        assert_eq!(network.channel_count(), 1);
+
+        let output = temp_path("snapshot.dot");
+        dispatch(
+            Command::Snapshot { output: Some(output.clone()), format: OutputFormat::Dot, local_only: false },
+            &session,
+        )
+        .await
+        .unwrap();
+        let dot = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\"", ALICE, BOB)), "{}", dot);
+    }
+
+    #[tokio::test]
+    async fn backend_errors_are_reported() {
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let node1 = MockLightning::new("node1", ALICE).failing("list_funds", "lightningd is shutting down");
+        let session = Session::new(&settings, &chain).with_node(Arc::new(node1));
+
+        let err = dispatch(Command::Info { node: Some("node1".to_string()) }, &session)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "lightningd is shutting down");
+    }
+}
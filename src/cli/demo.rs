@@ -0,0 +1,137 @@
+use anyhow::Result;
+use std::path::Path;
+use std::time::Duration;
+
+use super::commands::Session;
+use crate::scenario::ChannelWorkflow;
+use crate::supervisor;
+use crate::visualization::NetworkGraph;
+
+/// Capacité du canal ouvert par la démonstration.
+const DEMO_CHANNEL_SAT: u64 = 500_000;
+/// Blocs minés après l'ouverture ; 6 suffisent à annoncer le canal.
+const DEMO_CHANNEL_CONFIRMATIONS: u64 = 6;
+/// Délai maximal d'attente de chaque étape.
+const DEMO_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Scénario de démonstration : finance le premier nœud, lui ouvre un canal
+/// vers le second, attend qu'il soit `CHANNELD_NORMAL`, le traverse avec un
+/// paiement et écrit le graphe obtenu en snapshot JSON dans `output`, que
+/// `render` convertit ensuite dans tous les formats sans Graphviz.
+pub async fn run(session: &Session<'_>, output: &Path) -> Result<()> {
+    let settings = session.settings;
+    let bitcoin_node = session.bitcoin;
+    if settings.lightning.len() < 2 {
+        return Err(anyhow::anyhow!(
+            "Ce scénario nécessite au moins deux nœuds [[lightning]] dans la configuration"
+        ));
+    }
+    // Le scénario mine ses propres blocs de confirmation
+    bitcoin_node.network().require_mining("Le scénario de démonstration")?;
+    let node1_settings = &settings.lightning[0];
+    let node2_settings = &settings.lightning[1];
+
+    let lightning_node = session.node(&node1_settings.id).await?;
+    let lightning_node2 = session.node(&node2_settings.id).await?;
+    let node2_info = lightning_node2.get_node_info().await?;
+    let node2_id = node2_info.id.as_str();
+
+    // Envoyer des fonds au nœud Lightning et attendre leur confirmation
+    let workflow = ChannelWorkflow::new(bitcoin_node, DEMO_CHANNEL_CONFIRMATIONS, DEMO_TIMEOUT).await?;
+    println!("Financement de {}...", node1_settings.id);
+    let tx_id = workflow.fund(lightning_node.as_ref(), 1.0).await?;
+    println!("Transaction confirmée : {}", tx_id);
+
+    println!("Connexion de {} à {}...", node1_settings.id, node2_settings.id);
+    lightning_node
+        .connect_peer(node2_id, &node2_settings.host, supervisor::node_port(node2_settings)?)
+        .await?;
+
+    // Réutiliser un canal déjà actif vers le second nœud, sinon en ouvrir un
+    let funds = lightning_node.list_funds().await?;
+    let existing = funds
+        .channels
+        .into_iter()
+        .find(|c| c.peer_id == node2_id && c.state.is_normal());
+    let channel = match existing {
+        Some(channel) => {
+            println!("\nCanal existant réutilisé : {}", channel.funding_txid);
+            channel
+        }
+        None => {
+            println!(
+                "\nOuverture d'un canal de {} sats, puis {} blocs de confirmation...",
+                DEMO_CHANNEL_SAT, DEMO_CHANNEL_CONFIRMATIONS
+            );
+            workflow.open(lightning_node.as_ref(), node2_id, DEMO_CHANNEL_SAT, 0).await?
+        }
+    };
+    println!(
+        "Canal {} : {} ({} msat, dont {} msat de notre côté)",
+        channel.short_channel_id.as_deref().unwrap_or(&channel.funding_txid),
+        channel.state,
+        channel.amount_msat,
+        channel.our_amount_msat
+    );
+
+    // Traverser le canal avec un paiement
+    let invoice = lightning_node2
+        .create_invoice(
+            1_000_000, // 1000 sats
+            &format!("demo_invoice_{}", chrono::Utc::now().timestamp_millis()),
+            "lightning_viz demo payment",
+        )
+        .await?;
+    let payment = lightning_node.pay(&invoice.bolt11).await?.ensure_complete()?;
+    println!(
+        "Paiement {} : {} msat, {}, {} msat de frais",
+        payment.status,
+        payment.amount_sent_msat,
+        payment.route_summary(),
+        payment.fee_msat()
+    );
+
+    // Graphe final : les canaux partagés par les deux nœuds sont fusionnés plutôt que dupliqués
+    let mut network = NetworkGraph::new();
+    for node in [&lightning_node, &lightning_node2] {
+        let (node_info, funds) = tokio::try_join!(node.get_node_info(), node.list_funds())?;
+        network.update_from_node_info(&node_info, &funds)?;
+    }
+    std::fs::write(output, network.to_json()?)?;
+    println!("\nGraphe écrit dans {}", output.display());
+    println!("Pour en obtenir une image : render --input {} --format png|svg|pdf", output.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin::MockBitcoin;
+    use crate::lightning::{ChannelState, LightningBackend};
+    use crate::cli::commands::tests::{two_node_settings, two_nodes};
+    use crate::visualization::fixtures::{ALICE, BOB};
+
+    #[tokio::test(start_paused = true)]
+    async fn demo_opens_a_normal_channel_and_pays_through_it() {
+        let settings = two_node_settings();
+        let chain = MockBitcoin::new();
+        let (node1, node2) = two_nodes(&chain);
+        let session = Session::new(&settings, &chain).with_node(node1.clone()).with_node(node2);
+        let output = std::env::temp_dir().join(format!("lightning_viz_{}_demo.json", std::process::id()));
+
+        run(&session, &output).await.unwrap();
+
+        assert_eq!(chain.height(), 1 + DEMO_CHANNEL_CONFIRMATIONS);
+        let funds = node1.list_funds().await.unwrap();
+        assert_eq!(funds.confirmed_onchain_msat(), 100_000_000_000 - DEMO_CHANNEL_SAT * 1000);
+        assert_eq!(node1.peers(), vec![BOB.to_string()]);
+        assert_eq!(funds.channels.len(), 1);
+        assert_eq!(funds.channels[0].state, ChannelState::ChanneldNormal);
+        assert_eq!(funds.channels[0].our_amount_msat, DEMO_CHANNEL_SAT * 1000 - 1_000_000);
+
+        let network = NetworkGraph::from_json(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        assert!(network.to_dot().contains(ALICE) && network.to_dot().contains(BOB));
+    }
+}
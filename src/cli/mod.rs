@@ -0,0 +1,224 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::settings::DEFAULT_CONFIG_PATH;
+
+mod commands;
+mod demo;
+
+pub use commands::run;
+
+#[derive(Debug, Parser)]
+#[command(name = "lightning_viz", version, about = "Visualisation d'un réseau Lightning en regtest")]
+pub struct Cli {
+    /// Fichier de configuration TOML
+    #[arg(short, long, global = true, default_value = DEFAULT_CONFIG_PATH)]
+    pub config: PathBuf,
+
+    /// Démarre bitcoind et les nœuds lightningd qui ne répondent pas, puis les
+    /// arrête à la fin de la commande
+    #[arg(long, global = true, alias = "start-bitcoind")]
+    pub start_daemons: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Affiche l'état de bitcoind et des nœuds Lightning
+    Info {
+        /// Limite l'affichage à un seul nœud Lightning
+        #[arg(short, long)]
+        node: Option<String>,
+    },
+    /// Envoie des fonds on-chain à un nœud Lightning et mine les blocs de confirmation
+    Fund {
+        #[arg(short, long)]
+        node: String,
+        /// Montant en BTC
+        #[arg(short, long, default_value_t = 1.0)]
+        amount: f64,
+        /// Nombre de blocs à miner après l'envoi
+        #[arg(short, long, default_value_t = 6)]
+        blocks: u64,
+    },
+    /// Connecte un nœud Lightning à un autre nœud de la configuration
+    Connect {
+        #[arg(short, long)]
+        node: String,
+        #[arg(short, long)]
+        peer: String,
+    },
+    /// Ouvre un canal entre deux nœuds de la configuration
+    OpenChannel {
+        #[arg(short, long)]
+        node: String,
+        #[arg(short, long)]
+        peer: String,
+        /// Capacité du canal en satoshis
+        #[arg(short, long)]
+        amount_sat: u64,
+        /// Montant cédé au pair à l'ouverture, en millisatoshis
+        #[arg(long, default_value_t = 0)]
+        push_msat: u64,
+    },
+    /// Ferme un canal et suit sa transaction de fermeture on-chain
+    CloseChannel {
+        #[arg(short, long)]
+        node: String,
+        /// Canal à fermer : short channel id, channel id, txid de financement
+        /// ou pair (identifiant de la configuration ou clé publique)
+        #[arg(long)]
+        channel: String,
+        /// Ferme de force si le pair n'a pas accepté la fermeture coopérative
+        /// dans ce délai, en secondes
+        #[arg(long, conflicts_with = "force")]
+        unilateral_timeout: Option<u64>,
+        /// Ferme de force immédiatement
+        #[arg(long)]
+        force: bool,
+        /// Blocs minés après la fermeture (regtest)
+        #[arg(long, default_value_t = 6)]
+        confirmations: u64,
+    },
+    /// Crée une facture BOLT11
+    Invoice {
+        #[arg(short, long)]
+        node: String,
+        #[arg(short, long)]
+        amount_msat: u64,
+        /// Label unique de la facture (généré si absent)
+        #[arg(short, long)]
+        label: Option<String>,
+        #[arg(short, long, default_value = "lightning_viz")]
+        description: String,
+    },
+    /// Paie une facture BOLT11 et affiche la route empruntée
+    Pay {
+        #[arg(short, long)]
+        node: String,
+        bolt11: String,
+    },
+    /// Envoie un paiement spontané (keysend) à un autre nœud de la configuration
+    Keysend {
+        #[arg(short, long)]
+        node: String,
+        #[arg(short, long)]
+        peer: String,
+        #[arg(short, long)]
+        amount_msat: u64,
+    },
+    /// Décode une facture BOLT11 sans la payer
+    Decode {
+        #[arg(short, long)]
+        node: String,
+        bolt11: String,
+    },
+    /// Liste les paiements émis par un nœud
+    Payments {
+        #[arg(short, long)]
+        node: String,
+    },
+    /// Liste les factures émises par un nœud
+    Invoices {
+        #[arg(short, long)]
+        node: String,
+    },
+    /// Attend qu'une facture soit payée ou expire
+    WaitInvoice {
+        #[arg(short, long)]
+        node: String,
+        payment_hash: String,
+    },
+    /// Interroge tous les nœuds et écrit le graphe du réseau
+    Snapshot {
+        /// Fichier de sortie (par défaut selon le format)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Format de sortie
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Dot)]
+        format: OutputFormat,
+        /// N'utilise que `listfunds` et ignore la vue gossip du réseau
+        #[arg(long)]
+        local_only: bool,
+    },
+    /// Convertit un fichier DOT, SVG ou un snapshot JSON existant
+    Render {
+        /// Snapshot JSON, SVG ou fichier DOT à convertir ; seul le DOT passe par Graphviz
+        #[arg(short, long, default_value = "network.json")]
+        input: PathBuf,
+        /// Fichier de sortie (par défaut selon le format)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Format de sortie
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Png)]
+        format: OutputFormat,
+    },
+    /// Exécute le scénario de démonstration à deux nœuds
+    Demo,
+    /// Construit une topologie décrite dans un fichier de scénario TOML
+    Scenario {
+        /// Fichier de scénario (nœuds, financement, canaux, paiements)
+        file: PathBuf,
+        /// Écrit le graphe obtenu dans ce fichier une fois le scénario terminé
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Format du graphe écrit avec `--output`
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Dot)]
+        format: OutputFormat,
+    },
+    /// Démarre bitcoind et les nœuds lightningd puis attend Ctrl-C pour les arrêter
+    Up,
+    /// Tableau de bord plein écran du réseau, rafraîchi en continu
+    Dashboard {
+        /// Intervalle de rafraîchissement, en secondes
+        #[arg(short, long, default_value_t = 5)]
+        interval: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Dot,
+    Png,
+    Svg,
+    Pdf,
+    /// Page HTML autonome, avec zoom, infobulles et recherche
+    Html,
+    /// Snapshot JSON versionné, que `render` redessine sans se connecter aux nœuds
+    Json,
+    /// GraphML typé, pour yEd
+    Graphml,
+    /// GEXF typé, pour Gephi
+    Gexf,
+    /// Diagramme Mermaid, à inclure dans du Markdown
+    Mermaid,
+    /// Diagramme PlantUML
+    Plantuml,
+}
+
+impl OutputFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Dot => "dot",
+            OutputFormat::Png => "png",
+            OutputFormat::Svg => "svg",
+            OutputFormat::Pdf => "pdf",
+            OutputFormat::Html => "html",
+            OutputFormat::Json => "json",
+            OutputFormat::Graphml => "graphml",
+            OutputFormat::Gexf => "gexf",
+            OutputFormat::Mermaid => "mmd",
+            OutputFormat::Plantuml => "puml",
+        }
+    }
+
+    /// Fichier écrit quand `--output` n'est pas précisé.
+    pub fn default_output(self) -> PathBuf {
+        match self {
+            OutputFormat::Dot => PathBuf::from("lightning_network.dot"),
+            other => PathBuf::from(format!("network.{}", other.extension())),
+        }
+    }
+}
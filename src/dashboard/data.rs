@@ -0,0 +1,218 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::cmp::Reverse;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::lightning::{ClosedChannel, Funds, GossipChannel, GossipNode, LightningBackend, NodeInfo, Payment};
+use crate::visualization::{ChannelKind, NetworkGraph, TerminalGraph};
+
+/// Nombre de paiements affichés, tous nœuds confondus.
+pub const RECENT_PAYMENTS: usize = 10;
+
+/// Délai accordé à chaque nœud pour répondre, au-delà duquel il est affiché
+/// comme injoignable.
+pub const NODE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// État du réseau relevé à un rafraîchissement du tableau de bord.
+pub struct Dashboard {
+    pub refreshed_at: DateTime<Local>,
+    pub nodes: Vec<NodeSummary>,
+    pub channels: Vec<ChannelRow>,
+    pub payments: Vec<RecentPayment>,
+    pub graph: TerminalGraph,
+}
+
+/// Nœud de la configuration, ou l'erreur qui a empêché de l'interroger.
+pub struct NodeSummary {
+    pub id: String,
+    pub status: Result<NodeStatus, String>,
+}
+
+pub struct NodeStatus {
+    pub info: NodeInfo,
+    pub onchain_sat: u64,
+    /// Soldes des canaux non fermés, de notre côté et de celui des pairs.
+    pub local_sat: u64,
+    pub remote_sat: u64,
+}
+
+impl NodeStatus {
+    fn new(info: NodeInfo, funds: &Funds) -> Self {
+        let open = || funds.channels.iter().filter(|c| !c.state.is_closing());
+        Self {
+            info,
+            onchain_sat: funds.confirmed_onchain_msat() / 1000,
+            local_sat: open().map(|c| c.our_amount_msat).sum::<u64>() / 1000,
+            remote_sat: open().map(|c| c.their_amount_msat()).sum::<u64>() / 1000,
+        }
+    }
+}
+
+/// Canal du graphe fusionné, vu par tous les nœuds et leur gossip.
+pub struct ChannelRow {
+    pub short_channel_id: Option<String>,
+    pub source: String,
+    pub target: String,
+    pub kind: ChannelKind,
+    pub capacity_sat: u64,
+    pub source_balance_sat: Option<u64>,
+    pub target_balance_sat: Option<u64>,
+}
+
+/// Paiement émis par le nœud `node` de la configuration.
+pub struct RecentPayment {
+    pub node: String,
+    pub payment: Payment,
+}
+
+/// Tout ce qu'un rafraîchissement lit d'un nœud, en une seule série d'appels.
+struct Snapshot {
+    info: NodeInfo,
+    funds: Funds,
+    payments: Vec<Payment>,
+    closed: Vec<ClosedChannel>,
+    gossip_nodes: Vec<GossipNode>,
+    gossip_channels: Vec<GossipChannel>,
+}
+
+async fn fetch(node: &dyn LightningBackend) -> Result<Snapshot> {
+    let (info, funds, payments, closed, gossip_nodes, gossip_channels) = tokio::try_join!(
+        node.get_node_info(),
+        node.list_funds(),
+        node.list_payments(),
+        node.list_closed_channels(),
+        node.list_nodes(),
+        node.list_channels()
+    )?;
+    Ok(Snapshot { info, funds, payments, closed, gossip_nodes, gossip_channels })
+}
+
+/// Interroge tous les nœuds en parallèle. Un nœud en erreur ou qui ne répond
+/// pas dans `NODE_TIMEOUT` est signalé dans sa ligne sans empêcher
+/// l'affichage des autres.
+pub async fn collect(nodes: &[Arc<dyn LightningBackend>]) -> Dashboard {
+    let tasks: Vec<_> = nodes
+        .iter()
+        .map(|node| {
+            let node = node.clone();
+            tokio::spawn(async move {
+                match tokio::time::timeout(NODE_TIMEOUT, fetch(node.as_ref())).await {
+                    Ok(snapshot) => snapshot.map_err(|e| format!("{:#}", e)),
+                    Err(_) => Err(format!("injoignable : pas de réponse en {} s", NODE_TIMEOUT.as_secs())),
+                }
+            })
+        })
+        .collect();
+
+    let mut network = NetworkGraph::new();
+    let mut summaries = Vec::new();
+    let mut payments = Vec::new();
+    for (node, task) in nodes.iter().zip(tasks) {
+        let snapshot = task.await.unwrap_or_else(|e| Err(e.to_string()));
+        let status = snapshot.and_then(|snapshot| {
+            network.update_from_gossip(&snapshot.gossip_nodes, &snapshot.gossip_channels);
+            network.update_from_node_info(&snapshot.info, &snapshot.funds).map_err(|e| format!("{:#}", e))?;
+            network.update_from_closed_channels(&snapshot.info.id, &snapshot.closed);
+            // Les backends listent les paiements du plus ancien au plus récent
+            payments.extend(
+                snapshot
+                    .payments
+                    .into_iter()
+                    .rev()
+                    .map(|payment| RecentPayment { node: node.id().to_string(), payment }),
+            );
+            Ok(NodeStatus::new(snapshot.info, &snapshot.funds))
+        });
+        summaries.push(NodeSummary { id: node.id().to_string(), status });
+    }
+    payments.sort_by_key(|recent| Reverse(recent.payment.created_at));
+    payments.truncate(RECENT_PAYMENTS);
+
+    let channels = network
+        .channels()
+        .map(|(source, target, channel)| ChannelRow {
+            short_channel_id: channel.short_channel_id.clone(),
+            source: source.display_name(),
+            target: target.display_name(),
+            kind: ChannelKind::of(channel),
+            capacity_sat: channel.capacity_msat / 1000,
+            source_balance_sat: channel.source.balance_msat.map(|msat| msat / 1000),
+            target_balance_sat: channel.target.balance_msat.map(|msat| msat / 1000),
+        })
+        .collect();
+
+    Dashboard {
+        refreshed_at: Local::now(),
+        nodes: summaries,
+        channels,
+        payments,
+        graph: network.to_terminal(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lightning::{ChannelEntry, ChannelState, MockLightning, PaymentStatus};
+
+    use crate::visualization::fixtures::{ALICE, BOB};
+
+    fn channel(peer_id: &str, our_amount_msat: u64) -> ChannelEntry {
+        crate::visualization::fixtures::channel(peer_id, Some("103x1x0"), our_amount_msat)
+    }
+
+    #[tokio::test]
+    async fn nodes_channels_and_payments_are_collected() {
+        let alice = MockLightning::new("node1", ALICE)
+            .with_alias("alice")
+            .with_onchain_balance(50_000)
+            .with_channel(channel(BOB, 1_000_000_000));
+        alice.keysend(BOB, 1_000_000).await.unwrap();
+        alice.keysend(BOB, 2_000_000).await.unwrap();
+        // Les nœuds simulés ne se transmettent pas les paiements
+        let bob = MockLightning::new("node2", BOB).with_alias("bob").with_channel(channel(ALICE, 3_000_000));
+
+        let dashboard = collect(&[Arc::new(alice), Arc::new(bob)]).await;
+
+        let status = dashboard.nodes[0].status.as_ref().unwrap();
+        assert_eq!(status.info.display_name(), "alice");
+        assert_eq!((status.onchain_sat, status.local_sat, status.remote_sat), (50_000, 997_000, 3_000));
+
+        assert_eq!(dashboard.channels.len(), 1, "both ends of 103x1x0 are merged");
+        let row = &dashboard.channels[0];
+        assert_eq!((row.source.as_str(), row.target.as_str()), ("alice", "bob"));
+        assert_eq!(row.kind, ChannelKind::State(ChannelState::ChanneldNormal));
+        assert_eq!(row.source_balance_sat, Some(997_000));
+
+        let amounts: Vec<u64> = dashboard.payments.iter().map(|recent| recent.payment.amount_msat).collect();
+        assert_eq!(amounts, [2_000_000, 1_000_000], "most recent payment first");
+        assert!(dashboard.payments.iter().all(|recent| recent.node == "node1"));
+        assert!(dashboard.payments.iter().all(|recent| recent.payment.status == PaymentStatus::Complete));
+        assert!(!dashboard.graph.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unreachable_node_is_reported_in_its_row() {
+        let alice = MockLightning::new("node1", ALICE).failing("list_funds", "connection refused");
+        let bob = MockLightning::new("node2", BOB);
+
+        let dashboard = collect(&[Arc::new(alice), Arc::new(bob)]).await;
+
+        let error = dashboard.nodes[0].status.as_ref().err().unwrap();
+        assert!(error.contains("connection refused"), "{}", error);
+        assert!(dashboard.nodes[1].status.is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stalled_node_is_reported_as_unreachable() {
+        let alice = MockLightning::new("node1", ALICE).stalling("list_payments");
+        let bob = MockLightning::new("node2", BOB).with_alias("bob");
+
+        let dashboard = collect(&[Arc::new(alice), Arc::new(bob)]).await;
+
+        let error = dashboard.nodes[0].status.as_ref().err().unwrap();
+        assert!(error.contains("injoignable"), "{}", error);
+        assert_eq!(dashboard.nodes[1].status.as_ref().unwrap().info.display_name(), "bob");
+    }
+}
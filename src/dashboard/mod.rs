@@ -0,0 +1,51 @@
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::DefaultTerminal;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::lightning::LightningBackend;
+
+mod data;
+mod ui;
+
+/// Tableau de bord plein écran : interroge `nodes` toutes les `interval` et
+/// affiche nœuds, canaux, soldes, derniers paiements et graphe du réseau,
+/// jusqu'à `q`, `Échap` ou `Ctrl-C`. `r` force un rafraîchissement.
+pub async fn run(nodes: &[Arc<dyn LightningBackend>], interval: Duration) -> Result<()> {
+    let mut terminal = ratatui::try_init().context("Impossible de passer le terminal en plein écran")?;
+    let result = event_loop(&mut terminal, nodes, interval).await;
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(terminal: &mut DefaultTerminal, nodes: &[Arc<dyn LightningBackend>], interval: Duration) -> Result<()> {
+    let mut dashboard = data::collect(nodes).await;
+    let mut next_refresh = Instant::now() + interval;
+    loop {
+        terminal.draw(|frame| ui::draw(frame, &dashboard, interval))?;
+
+        // crossterm attend les touches de façon bloquante, hors des tâches du runtime
+        let timeout = next_refresh.saturating_duration_since(Instant::now());
+        let event = tokio::task::block_in_place(|| -> std::io::Result<Option<Event>> {
+            Ok(if event::poll(timeout)? { Some(event::read()?) } else { None })
+        })?;
+
+        let refresh = match event {
+            Some(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                // Le mode brut du terminal n'envoie plus SIGINT
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                KeyCode::Char('r') => true,
+                _ => false,
+            },
+            // Redimensionnement ou autre événement : il suffit de redessiner
+            Some(_) => false,
+            None => true,
+        };
+        if refresh || Instant::now() >= next_refresh {
+            dashboard = data::collect(nodes).await;
+            next_refresh = Instant::now() + interval;
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use chrono::{DateTime, Local};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table};
+use ratatui::Frame;
+use std::time::Duration;
+
+use super::data::{ChannelRow, Dashboard, NodeSummary, RecentPayment};
+use crate::lightning::PaymentStatus;
+use crate::visualization::terminal_color;
+
+/// Dessine le tableau de bord : les nœuds et le graphe en haut, les canaux et
+/// les derniers paiements en bas.
+pub fn draw(frame: &mut Frame, dashboard: &Dashboard, interval: Duration) {
+    let [header, top, bottom] =
+        Layout::vertical([Constraint::Length(1), Constraint::Percentage(50), Constraint::Fill(1)]).areas(frame.area());
+    let [nodes, graph] = Layout::horizontal([Constraint::Percentage(60), Constraint::Fill(1)]).areas(top);
+    let [channels, payments] = Layout::horizontal([Constraint::Percentage(60), Constraint::Fill(1)]).areas(bottom);
+
+    frame.render_widget(header_line(dashboard, interval), header);
+    frame.render_widget(nodes_table(&dashboard.nodes), nodes);
+    draw_graph(frame, dashboard, graph);
+    frame.render_widget(channels_table(&dashboard.channels), channels);
+    frame.render_widget(payments_table(&dashboard.payments), payments);
+}
+
+fn header_line(dashboard: &Dashboard, interval: Duration) -> Paragraph<'static> {
+    Paragraph::new(Line::from(vec![
+        " Réseau Lightning ".bold().reversed(),
+        format!(
+            "  rafraîchi à {}, toutes les {} s  ",
+            dashboard.refreshed_at.format("%H:%M:%S"),
+            interval.as_secs()
+        )
+        .into(),
+        "r".bold(),
+        " rafraîchir  ".into(),
+        "q".bold(),
+        " quitter".into(),
+    ]))
+}
+
+fn titled(title: &str) -> Block<'_> {
+    Block::bordered().title(format!(" {} ", title))
+}
+
+fn header_row<const N: usize>(titles: [&'static str; N]) -> Row<'static> {
+    Row::new(titles).style(Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn nodes_table(nodes: &[NodeSummary]) -> Table<'_> {
+    let rows = nodes.iter().map(|node| match &node.status {
+        Ok(status) => Row::new(vec![
+            Cell::from(node.id.as_str()),
+            Cell::from(status.info.display_name()).fg(terminal_color(&format!("#{}", status.info.color))),
+            Cell::from(status.info.blockheight.to_string()),
+            Cell::from(status.info.num_peers.to_string()),
+            Cell::from(format!(
+                "{}/{}/{}",
+                status.info.num_active_channels, status.info.num_pending_channels, status.info.num_inactive_channels
+            )),
+            Cell::from(status.onchain_sat.to_string()),
+            Cell::from(status.local_sat.to_string()),
+            Cell::from(status.remote_sat.to_string()),
+        ]),
+        Err(error) => Row::new(vec![Cell::from(node.id.as_str()), Cell::from(error.as_str()).fg(Color::Red)]),
+    });
+    let widths = [
+        Constraint::Length(10),
+        Constraint::Fill(1),
+        Constraint::Length(7),
+        Constraint::Length(5),
+        Constraint::Length(9),
+        Constraint::Length(11),
+        Constraint::Length(11),
+        Constraint::Length(11),
+    ];
+    Table::new(rows, widths)
+        .header(header_row(["Nœud", "Alias", "Hauteur", "Pairs", "Canaux", "On-chain", "Local", "Distant"]))
+        .block(titled("Nœuds (canaux actifs/en attente/inactifs, soldes en sats)"))
+}
+
+fn draw_graph(frame: &mut Frame, dashboard: &Dashboard, area: Rect) {
+    let block = titled("Graphe");
+    if dashboard.graph.is_empty() {
+        frame.render_widget(Paragraph::new("Aucun nœud").block(block), area);
+    } else {
+        frame.render_widget(dashboard.graph.canvas(block), area);
+    }
+}
+
+/// Soldes de la source et de la cible, `?` quand aucun nœud du canal n'a été interrogé.
+fn balances(channel: &ChannelRow) -> String {
+    let sats = |balance: Option<u64>| balance.map_or_else(|| "?".to_string(), |sat| sat.to_string());
+    format!("{} / {}", sats(channel.source_balance_sat), sats(channel.target_balance_sat))
+}
+
+fn channels_table(channels: &[ChannelRow]) -> Table<'_> {
+    let rows = channels.iter().map(|channel| {
+        Row::new(vec![
+            Cell::from(channel.short_channel_id.as_deref().unwrap_or("-")),
+            Cell::from(format!("{} → {}", channel.source, channel.target)),
+            Cell::from(channel.kind.label()).fg(terminal_color(channel.kind.style().color)),
+            Cell::from(channel.capacity_sat.to_string()),
+            Cell::from(balances(channel)),
+        ])
+    });
+    let widths = [
+        Constraint::Length(14),
+        Constraint::Fill(1),
+        Constraint::Length(18),
+        Constraint::Length(11),
+        Constraint::Length(23),
+    ];
+    Table::new(rows, widths)
+        .header(header_row(["Canal", "Source → cible", "État", "Capacité", "Soldes"]))
+        .block(titled("Canaux (sats)"))
+}
+
+fn payment_time(created_at: Option<u64>) -> String {
+    created_at
+        .and_then(|secs| DateTime::from_timestamp(secs as i64, 0))
+        .map(|date| date.with_timezone(&Local).format("%H:%M:%S").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn payments_table(payments: &[RecentPayment]) -> Table<'_> {
+    let rows = payments.iter().map(|recent| {
+        let payment = &recent.payment;
+        let color = match payment.status {
+            PaymentStatus::Complete => Color::Green,
+            PaymentStatus::Pending => Color::Yellow,
+            PaymentStatus::Failed => Color::Red,
+        };
+        Row::new(vec![
+            Cell::from(payment_time(payment.created_at)),
+            Cell::from(recent.node.as_str()),
+            Cell::from(payment.status.to_string()).fg(color),
+            Cell::from(payment.amount_msat.to_string()),
+            Cell::from(payment.fee_msat().to_string()),
+        ])
+    });
+    let widths = [
+        Constraint::Length(8),
+        Constraint::Fill(1),
+        Constraint::Length(8),
+        Constraint::Length(12),
+        Constraint::Length(8),
+    ];
+    Table::new(rows, widths)
+        .header(header_row(["Heure", "Nœud", "Statut", "Montant", "Frais"]))
+        .block(titled("Derniers paiements (msat)"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dashboard::data::NodeStatus;
+    use crate::visualization::fixtures::{node_info, ALICE};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn every_panel_is_drawn() {
+        let dashboard = Dashboard {
+            refreshed_at: Local::now(),
+            nodes: vec![
+                NodeSummary {
+                    id: "node1".to_string(),
+                    status: Ok(NodeStatus {
+                        info: node_info(ALICE, "alice"),
+                        onchain_sat: 50_000,
+                        local_sat: 700,
+                        remote_sat: 300,
+                    }),
+                },
+                NodeSummary { id: "node2".to_string(), status: Err("connection refused".to_string()) },
+            ],
+            channels: Vec::new(),
+            payments: Vec::new(),
+            graph: crate::visualization::NetworkGraph::new().to_terminal(),
+        };
+        let mut terminal = Terminal::new(TestBackend::new(160, 30)).unwrap();
+        terminal.draw(|frame| draw(frame, &dashboard, Duration::from_secs(5))).unwrap();
+
+        let text: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        for expected in ["toutes les 5 s", "alice", "50000", "connection refused", "Aucun nœud", "Canaux", "Derniers paiements"] {
+            assert!(text.contains(expected), "{:?} missing from {}", expected, text);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::types::{
+    ChannelClosing, ChannelEntry, ChannelOpening, ClosedChannel, DecodedInvoice, Funds, GossipChannel, GossipNode,
+    Invoice, InvoiceEntry, NodeInfo, Payment,
+};
+
+/// Opérations communes à toutes les implémentations Lightning (CLN, LND...).
+///
+/// Le graphe et la ligne de commande ne manipulent que ce trait, ce qui
+/// permet de mélanger plusieurs implémentations dans un même réseau.
+#[async_trait]
+pub trait LightningBackend: Send + Sync {
+    /// Identifiant du nœud dans la configuration.
+    fn id(&self) -> &str;
+
+    async fn get_node_info(&self) -> Result<NodeInfo>;
+
+    async fn create_invoice(&self, amount_msat: u64, label: &str, description: &str) -> Result<Invoice>;
+
+    /// Ouvre un canal de `amount_sat` vers `peer_id`, en cédant `push_msat` au pair.
+    async fn open_channel(&self, peer_id: &str, amount_sat: u64, push_msat: u64) -> Result<ChannelOpening>;
+
+    /// Ferme `channel`, de préférence en accord avec le pair. Si celui-ci ne
+    /// répond pas dans `unilateral_timeout`, notre transaction d'engagement est
+    /// publiée (fermeture forcée, immédiate avec `Duration::ZERO`) ; `None`
+    /// attend l'accord du pair sans limite.
+    async fn close_channel(&self, channel: &ChannelEntry, unilateral_timeout: Option<Duration>) -> Result<ChannelClosing>;
+
+    /// Canaux fermés dont les sorties on-chain ont été entièrement traitées.
+    async fn list_closed_channels(&self) -> Result<Vec<ClosedChannel>>;
+
+    /// Paie une facture BOLT11. Un paiement qui n'aboutit pas (pas de route,
+    /// échec en cours de route) est renvoyé avec le statut `Failed` et sa
+    /// raison ; `Err` est réservé aux erreurs du nœud lui-même.
+    async fn pay(&self, bolt11: &str) -> Result<Payment>;
+
+    /// Paiement spontané (keysend) de `amount_msat` vers `destination`, sans facture.
+    async fn keysend(&self, destination: &str, amount_msat: u64) -> Result<Payment>;
+
+    async fn decode_invoice(&self, bolt11: &str) -> Result<DecodedInvoice>;
+
+    /// Paiements émis par le nœud, y compris ceux en cours ou échoués.
+    async fn list_payments(&self) -> Result<Vec<Payment>>;
+
+    /// Factures émises par le nœud.
+    async fn list_invoices(&self) -> Result<Vec<InvoiceEntry>>;
+
+    /// Attend que la facture `payment_hash` soit payée ou expire.
+    async fn wait_invoice(&self, payment_hash: &str) -> Result<InvoiceEntry>;
+
+    async fn connect_peer(&self, node_id: &str, host: &str, port: u16) -> Result<()>;
+
+    async fn get_new_address(&self) -> Result<String>;
+
+    async fn list_funds(&self) -> Result<Funds>;
+
+    /// Tous les canaux connus par le gossip, une entrée par direction.
+    async fn list_channels(&self) -> Result<Vec<GossipChannel>>;
+
+    /// Tous les nœuds connus par le gossip.
+    async fn list_nodes(&self) -> Result<Vec<GossipNode>>;
+}
+
+/// Implémentation utilisée par un nœud de la configuration. LDK, bibliothèque
+/// sans démon à piloter, n'est pas pris en charge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// Core Lightning, via le socket RPC de `lightning_dir`.
+    #[default]
+    Cln,
+    /// LND, via son API REST.
+    Lnd,
+}
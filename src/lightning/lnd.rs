@@ -0,0 +1,1078 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bitcoin::hashes::{sha256, Hash};
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use super::backend::LightningBackend;
+use super::types::{
+    ChannelClosing, ChannelEntry, ChannelLiquidity, ChannelOpening, ChannelState, ClosedChannel, CloseType, DecodedInvoice, Funds, GossipChannel,
+    GossipNode, Invoice, InvoiceEntry, InvoiceStatus, NodeInfo, OutputEntry, OutputStatus, Payment, PaymentStatus,
+    RouteHop,
+};
+
+/// Délai maximal d'une requête REST ; un paiement peut prendre du temps à aboutir.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Intervalle de sondage de `wait_invoice`, la passerelle REST ne diffusant
+/// pas les mises à jour de factures en simple requête.
+const INVOICE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Enregistrement TLV qui porte la préimage d'un paiement keysend.
+const KEYSEND_RECORD: &str = "5482373484";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LndConfig {
+    /// URL de l'API REST, ex. `https://127.0.0.1:8080`.
+    pub rest_url: String,
+    pub macaroon_path: String,
+    /// Certificat TLS auto-signé de lnd (`tls.cert`).
+    pub tls_cert_path: Option<String>,
+}
+
+/// Nœud LND piloté via son API REST.
+#[derive(Clone)]
+pub struct LndNode {
+    pub id: String,
+    client: Client,
+    config: LndConfig,
+    macaroon: String,
+}
+
+impl LndNode {
+    pub fn new(config: LndConfig, id: String) -> Result<Self> {
+        let macaroon = std::fs::read(&config.macaroon_path)
+            .with_context(|| format!("Impossible de lire le macaroon {:?}", config.macaroon_path))?;
+
+        let mut builder = Client::builder().timeout(REQUEST_TIMEOUT);
+        if let Some(cert_path) = &config.tls_cert_path {
+            let pem = std::fs::read(cert_path)
+                .with_context(|| format!("Impossible de lire le certificat {:?}", cert_path))?;
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&pem)?);
+        }
+
+        Ok(Self {
+            id,
+            client: builder.build()?,
+            macaroon: hex::encode(macaroon),
+            config,
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        let url = format!("{}{}", self.config.rest_url.trim_end_matches('/'), path);
+        self.client
+            .request(method, url)
+            .header("Grpc-Metadata-macaroon", &self.macaroon)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder, path: &str) -> Result<T> {
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Nœud LND {} injoignable ({})", self.id, path))?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            let message = serde_json::from_str::<LndError>(&body)
+                .map(|e| e.message)
+                .unwrap_or(body);
+            return Err(anyhow::anyhow!("Échec de LND {} ({}) : {}", path, status, message));
+        }
+        serde_json::from_str(&body).with_context(|| format!("Réponse LND inattendue pour {}", path))
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        self.send(self.request(reqwest::Method::GET, path), path).await
+    }
+
+    async fn post<T: DeserializeOwned>(&self, path: &str, body: serde_json::Value) -> Result<T> {
+        self.send(self.request(reqwest::Method::POST, path).json(&body), path).await
+    }
+
+    /// Graphe du réseau, y compris les canaux privés du nœud, absents du gossip.
+    async fn graph(&self) -> Result<LndGraph> {
+        self.get("/v1/graph?include_unannounced=true").await
+    }
+
+    /// Demande la fermeture d'un canal et renvoie la transaction annoncée par
+    /// le premier message du flux (`close_pending`), sans attendre sa confirmation.
+    async fn request_close(&self, channel: &ChannelEntry, force: bool) -> Result<String> {
+        let path = format!("/v1/channels/{}/{}?force={}", channel.funding_txid, channel.funding_output, force);
+        let mut response = self
+            .request(reqwest::Method::DELETE, &path)
+            .send()
+            .await
+            .with_context(|| format!("Nœud LND {} injoignable ({})", self.id, path))?;
+
+        let status = response.status();
+        let mut body = Vec::new();
+        while !body.contains(&b'\n') {
+            match response.chunk().await? {
+                Some(chunk) => body.extend_from_slice(&chunk),
+                None => break,
+            }
+        }
+        let line = body.split(|b| *b == b'\n').next().unwrap_or_default();
+        let update: LndCloseUpdate = serde_json::from_slice(line)
+            .with_context(|| format!("Réponse LND inattendue pour {} ({})", path, status))?;
+        match (update.result.and_then(|r| r.close_pending), update.error) {
+            (Some(pending), _) => {
+                let mut txid = BASE64.decode(&pending.txid)?;
+                txid.reverse();
+                Ok(hex::encode(txid))
+            }
+            (None, Some(error)) => Err(anyhow::anyhow!("Échec de LND {} ({}) : {}", path, status, error.message)),
+            (None, None) => Err(anyhow::anyhow!("LND {} n'a annoncé aucune transaction de fermeture", path)),
+        }
+    }
+
+    /// Envoie un paiement synchrone ; les échecs de routage deviennent un
+    /// `Payment` au statut `Failed`.
+    async fn send_payment(
+        &self,
+        body: serde_json::Value,
+        payment_hash: &str,
+        destination: &str,
+        amount_msat: u64,
+    ) -> Result<Payment> {
+        let payment: LndSendResponse = self.post("/v1/channels/transactions", body).await?;
+        if !payment.payment_error.is_empty() {
+            return Ok(Payment::failed(
+                payment_hash,
+                Some(destination.to_string()),
+                amount_msat,
+                payment.payment_error,
+            ));
+        }
+        let (amount_sent_msat, fees_msat) = payment
+            .payment_route
+            .as_ref()
+            .map_or((amount_msat, 0), |route| (route.total_amt_msat, route.total_fees_msat));
+        Ok(Payment {
+            payment_hash: payment_hash.to_string(),
+            payment_preimage: Some(base64_to_hex(&payment.payment_preimage)?),
+            destination: Some(destination.to_string()),
+            amount_msat: amount_sent_msat.saturating_sub(fees_msat),
+            amount_sent_msat,
+            status: PaymentStatus::Complete,
+            route: payment.payment_route.map(|route| route.hops.into_iter().map(RouteHop::from).collect()),
+            failure_reason: None,
+            created_at: None,
+        })
+    }
+}
+
+#[async_trait]
+impl LightningBackend for LndNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn get_node_info(&self) -> Result<NodeInfo> {
+        let info: LndGetInfo = self.get("/v1/getinfo").await?;
+        Ok(NodeInfo {
+            alias: Some(info.alias).filter(|a| !a.is_empty()),
+            color: info.color.trim_start_matches('#').to_string(),
+            network: info.chains.first().map(|c| c.network.clone()).unwrap_or_default(),
+            version: info.version,
+            blockheight: info.block_height,
+            num_peers: info.num_peers,
+            num_active_channels: info.num_active_channels,
+            num_pending_channels: info.num_pending_channels,
+            num_inactive_channels: info.num_inactive_channels,
+            fees_collected_msat: 0,
+            addresses: info
+                .uris
+                .iter()
+                .filter_map(|uri| uri.split_once('@').map(|(_, addr)| addr.to_string()))
+                .collect(),
+            id: info.identity_pubkey,
+        })
+    }
+
+    async fn create_invoice(&self, amount_msat: u64, _label: &str, description: &str) -> Result<Invoice> {
+        // LND n'a pas de label : seule la description est transmise.
+        let invoice: LndAddInvoice = self
+            .post("/v1/invoices", json!({
+                "value_msat": amount_msat.to_string(),
+                "memo": description,
+            }))
+            .await?;
+        Ok(Invoice {
+            bolt11: invoice.payment_request,
+            payment_hash: base64_to_hex(&invoice.r_hash)?,
+            expires_at: None,
+        })
+    }
+
+    async fn open_channel(&self, peer_id: &str, amount_sat: u64, push_msat: u64) -> Result<ChannelOpening> {
+        let point: LndChannelPoint = self
+            .post("/v1/channels", json!({
+                "node_pubkey_string": peer_id,
+                "local_funding_amount": amount_sat.to_string(),
+                "push_sat": push_sat(push_msat)?.to_string(),
+            }))
+            .await?;
+        // Le txid est renvoyé en octets dans l'ordre interne, inverse de l'affichage.
+        let mut txid = BASE64.decode(&point.funding_txid_bytes)?;
+        txid.reverse();
+        Ok(ChannelOpening {
+            txid: hex::encode(txid),
+            outnum: point.output_index,
+            channel_id: None,
+        })
+    }
+
+    /// lnd n'a pas de délai de fermeture forcée : il est reproduit ici. Le délai
+    /// dépassé n'annule pas la fermeture coopérative, que lnd continue de
+    /// négocier ; la fermeture forcée est demandée en plus, et lnd peut la
+    /// refuser tant que cette négociation est en cours.
+    async fn close_channel(&self, channel: &ChannelEntry, unilateral_timeout: Option<Duration>) -> Result<ChannelClosing> {
+        let unilateral = |txid| ChannelClosing { close_type: CloseType::Unilateral, txid: Some(txid) };
+        // Seul le délai dépassé justifie de forcer ; un refus est renvoyé tel quel
+        let txid = match unilateral_timeout {
+            Some(timeout) if timeout.is_zero() => return Ok(unilateral(self.request_close(channel, true).await?)),
+            Some(timeout) => match tokio::time::timeout(timeout, self.request_close(channel, false)).await {
+                Ok(result) => result?,
+                Err(_) => {
+                    let txid = self.request_close(channel, true).await.with_context(|| {
+                        format!(
+                            "Fermeture forcée du canal {} refusée par {} : la fermeture coopérative demandée sans \
+                             réponse après {:?} n'est pas annulée et lnd la négocie peut-être encore",
+                            channel.funding_txid, self.id, timeout
+                        )
+                    })?;
+                    return Ok(unilateral(txid));
+                }
+            },
+            None => self.request_close(channel, false).await?,
+        };
+        Ok(ChannelClosing { close_type: CloseType::Mutual, txid: Some(txid) })
+    }
+
+    async fn list_closed_channels(&self) -> Result<Vec<ClosedChannel>> {
+        let response: LndClosedChannels = self.get("/v1/channels/closed").await?;
+        response
+            .channels
+            .into_iter()
+            .map(|channel| {
+                let (funding_txid, funding_output) = split_channel_point(&channel.channel_point)?;
+                Ok(ClosedChannel {
+                    peer_id: Some(channel.remote_pubkey),
+                    short_channel_id: Some(channel.chan_id).filter(|id| *id > 0).map(scid_from_u64),
+                    funding_txid,
+                    funding_output,
+                    amount_msat: channel.capacity * 1000,
+                    final_to_us_msat: channel.settled_balance * 1000,
+                    close_cause: channel.close_type.to_lowercase(),
+                    closing_txid: Some(channel.closing_tx_hash).filter(|txid| !txid.is_empty()),
+                })
+            })
+            .collect()
+    }
+
+    async fn pay(&self, bolt11: &str) -> Result<Payment> {
+        let invoice = self.decode_invoice(bolt11).await?;
+        let amount_msat = invoice.amount_msat.unwrap_or_default();
+        self.send_payment(json!({ "payment_request": bolt11 }), &invoice.payment_hash, &invoice.payee, amount_msat)
+            .await
+    }
+
+    async fn keysend(&self, destination: &str, amount_msat: u64) -> Result<Payment> {
+        let preimage: [u8; 32] = rand::random();
+        let payment_hash = sha256::Hash::hash(&preimage).to_byte_array();
+        let body = json!({
+            "dest": BASE64.encode(hex::decode(destination)?),
+            "amt_msat": amount_msat.to_string(),
+            "payment_hash": BASE64.encode(payment_hash),
+            "dest_custom_records": { KEYSEND_RECORD: BASE64.encode(preimage) },
+        });
+        self.send_payment(body, &hex::encode(payment_hash), destination, amount_msat).await
+    }
+
+    async fn decode_invoice(&self, bolt11: &str) -> Result<DecodedInvoice> {
+        let invoice: LndPayReq = self.get(&format!("/v1/payreq/{}", bolt11)).await?;
+        Ok(DecodedInvoice {
+            payee: invoice.destination,
+            payment_hash: invoice.payment_hash,
+            amount_msat: Some(invoice.num_msat).filter(|amount| *amount > 0),
+            description: Some(invoice.description).filter(|d| !d.is_empty()),
+            created_at: invoice.timestamp,
+            expiry: invoice.expiry,
+            min_final_cltv_expiry: invoice.cltv_expiry as u32,
+        })
+    }
+
+    async fn list_payments(&self) -> Result<Vec<Payment>> {
+        let response: LndPayments = self.get("/v1/payments?include_incomplete=true").await?;
+        Ok(response.payments.into_iter().map(Payment::from).collect())
+    }
+
+    async fn list_invoices(&self) -> Result<Vec<InvoiceEntry>> {
+        let response: LndInvoices = self.get("/v1/invoices").await?;
+        response.invoices.into_iter().map(InvoiceEntry::try_from).collect()
+    }
+
+    async fn wait_invoice(&self, payment_hash: &str) -> Result<InvoiceEntry> {
+        loop {
+            let invoice: LndInvoice = self.get(&format!("/v1/invoice/{}", payment_hash)).await?;
+            let invoice = InvoiceEntry::try_from(invoice)?;
+            if invoice.status != InvoiceStatus::Unpaid {
+                return Ok(invoice);
+            }
+            tokio::time::sleep(INVOICE_POLL_INTERVAL).await;
+        }
+    }
+
+    async fn connect_peer(&self, node_id: &str, host: &str, port: u16) -> Result<()> {
+        let _: serde_json::Value = self
+            .post("/v1/peers", json!({
+                "addr": { "pubkey": node_id, "host": format!("{}:{}", host, port) },
+                "perm": false,
+            }))
+            .await?;
+        Ok(())
+    }
+
+    async fn get_new_address(&self) -> Result<String> {
+        let response: LndNewAddress = self.get("/v1/newaddress?type=WITNESS_PUBKEY_HASH").await?;
+        Ok(response.address)
+    }
+
+    async fn list_funds(&self) -> Result<Funds> {
+        let (utxos, channels, pending) = tokio::try_join!(
+            self.get::<LndUtxos>("/v1/utxos?min_confs=0&max_confs=2147483647"),
+            self.get::<LndChannels>("/v1/channels"),
+            self.get::<LndPendingChannels>("/v1/channels/pending"),
+        )?;
+
+        let outputs = utxos
+            .utxos
+            .into_iter()
+            .map(|utxo| OutputEntry {
+                txid: utxo.outpoint.txid_str,
+                output: utxo.outpoint.output_index,
+                amount_msat: utxo.amount_sat * 1000,
+                status: if utxo.confirmations > 0 {
+                    OutputStatus::Confirmed
+                } else {
+                    OutputStatus::Unconfirmed
+                },
+                address: Some(utxo.address),
+                blockheight: None,
+                reserved: false,
+            })
+            .collect();
+
+        let open = channels.channels.into_iter().map(|channel| {
+            let (funding_txid, funding_output) = split_channel_point(&channel.channel_point)?;
+            Ok(ChannelEntry {
+                peer_id: channel.remote_pubkey,
+                short_channel_id: Some(scid_from_u64(channel.chan_id)),
+                channel_id: None,
+                funding_txid,
+                funding_output,
+                state: ChannelState::ChanneldNormal,
+                connected: channel.active,
+                amount_msat: channel.capacity * 1000,
+                our_amount_msat: channel.local_balance * 1000,
+                liquidity: Some(ChannelLiquidity {
+                    our_reserve_msat: channel.local_constraints.chan_reserve_sat * 1000,
+                    their_reserve_msat: channel.remote_constraints.chan_reserve_sat * 1000,
+                    spendable_msat: channel.local_balance.saturating_sub(channel.local_constraints.chan_reserve_sat)
+                        * 1000,
+                    receivable_msat: channel.remote_balance.saturating_sub(channel.remote_constraints.chan_reserve_sat)
+                        * 1000,
+                }),
+            })
+        });
+        let opening = pending.pending_open_channels.into_iter().map(|pending| {
+            let channel = pending.channel;
+            let (funding_txid, funding_output) = split_channel_point(&channel.channel_point)?;
+            Ok(ChannelEntry {
+                peer_id: channel.remote_node_pub,
+                short_channel_id: None,
+                channel_id: None,
+                funding_txid,
+                funding_output,
+                state: ChannelState::ChanneldAwaitingLockin,
+                connected: true,
+                amount_msat: channel.capacity * 1000,
+                our_amount_msat: channel.local_balance * 1000,
+                liquidity: None,
+            })
+        });
+
+        // Fermetures publiées mais non confirmées, puis confirmées en attente
+        // de la récupération des sorties
+        let closing = pending
+            .waiting_close_channels
+            .into_iter()
+            .map(|closing| (closing.channel, ChannelState::ClosingdComplete))
+            .chain(pending.pending_force_closing_channels.into_iter().map(|closing| (closing.channel, ChannelState::Onchain)))
+            .map(|(channel, state)| {
+                let (funding_txid, funding_output) = split_channel_point(&channel.channel_point)?;
+                Ok(ChannelEntry {
+                    peer_id: channel.remote_node_pub,
+                    short_channel_id: None,
+                    channel_id: None,
+                    funding_txid,
+                    funding_output,
+                    state,
+                    connected: false,
+                    amount_msat: channel.capacity * 1000,
+                    our_amount_msat: channel.local_balance * 1000,
+                    liquidity: None,
+                })
+            });
+
+        Ok(Funds {
+            outputs,
+            channels: open.chain(opening).chain(closing).collect::<Result<_>>()?,
+        })
+    }
+
+    async fn list_channels(&self) -> Result<Vec<GossipChannel>> {
+        let (graph, local) = tokio::try_join!(self.graph(), self.get::<LndChannels>("/v1/channels"))?;
+        let private: HashSet<u64> = local.channels.iter().filter(|c| c.private).map(|c| c.chan_id).collect();
+        Ok(gossip_channels(graph, &private))
+    }
+
+    async fn list_nodes(&self) -> Result<Vec<GossipNode>> {
+        let graph = self.graph().await?;
+        Ok(graph
+            .nodes
+            .into_iter()
+            .map(|node| GossipNode {
+                id: node.pub_key,
+                alias: Some(node.alias).filter(|a| !a.is_empty()),
+                color: Some(node.color.trim_start_matches('#').to_string()).filter(|c| !c.is_empty()),
+                addresses: node.addresses.into_iter().map(|a| a.addr).collect(),
+                last_timestamp: Some(node.last_update).filter(|t| *t > 0),
+            })
+            .collect())
+    }
+}
+
+/// Décode un identifiant de canal LND (`chan_id`) au format `blocxtxxsortie`.
+fn scid_from_u64(chan_id: u64) -> String {
+    format!("{}x{}x{}", chan_id >> 40, (chan_id >> 16) & 0xFF_FFFF, chan_id & 0xFFFF)
+}
+
+/// `push_sat` de l'ouverture : lnd ne cède que des satoshis entiers, et
+/// arrondir changerait silencieusement le montant demandé.
+fn push_sat(push_msat: u64) -> Result<u64> {
+    if !push_msat.is_multiple_of(1000) {
+        return Err(anyhow::anyhow!(
+            "LND ne cède que des satoshis entiers : push_msat {} n'est pas un multiple de 1000",
+            push_msat
+        ));
+    }
+    Ok(push_msat / 1000)
+}
+
+/// Une entrée par direction annoncée de chaque arête du graphe ; `private`
+/// liste les `chan_id` des canaux non annoncés du nœud local.
+fn gossip_channels(graph: LndGraph, private: &HashSet<u64>) -> Vec<GossipChannel> {
+    let mut channels = Vec::new();
+    for edge in graph.edges {
+        let directions = [
+            (&edge.node1_pub, &edge.node2_pub, &edge.node1_policy),
+            (&edge.node2_pub, &edge.node1_pub, &edge.node2_policy),
+        ];
+        for (source, destination, policy) in directions {
+            let Some(policy) = policy else { continue };
+            channels.push(GossipChannel {
+                source: source.clone(),
+                destination: destination.clone(),
+                short_channel_id: scid_from_u64(edge.channel_id),
+                amount_msat: edge.capacity * 1000,
+                active: !policy.disabled,
+                public: !private.contains(&edge.channel_id),
+                base_fee_msat: policy.fee_base_msat as u32,
+                fee_ppm: policy.fee_rate_milli_msat as u32,
+                cltv_delta: policy.time_lock_delta,
+                htlc_minimum_msat: policy.min_htlc,
+                htlc_maximum_msat: Some(policy.max_htlc_msat).filter(|max| *max > 0),
+                last_update: policy.last_update,
+            });
+        }
+    }
+    channels
+}
+
+/// Sépare un `channel_point` lnd (`<txid>:<index>`) en sortie de financement.
+fn split_channel_point(channel_point: &str) -> Result<(String, u32)> {
+    let (txid, index) = channel_point
+        .split_once(':')
+        .ok_or_else(|| anyhow::anyhow!("channel_point LND invalide {:?} : <txid>:<index> attendu", channel_point))?;
+    let index = index
+        .parse()
+        .with_context(|| format!("channel_point LND invalide {:?} : index de sortie illisible", channel_point))?;
+    Ok((txid.to_string(), index))
+}
+
+fn base64_to_hex(value: &str) -> Result<String> {
+    Ok(hex::encode(BASE64.decode(value)?))
+}
+
+/// Les entiers 64 bits sont encodés en chaînes par la passerelle REST de lnd.
+fn de_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Text(String),
+        Int(u64),
+    }
+    match Number::deserialize(deserializer)? {
+        Number::Int(n) => Ok(n),
+        Number::Text(s) if s.is_empty() => Ok(0),
+        Number::Text(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+#[derive(Deserialize)]
+struct LndError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct LndGetInfo {
+    identity_pubkey: String,
+    #[serde(default)]
+    alias: String,
+    #[serde(default)]
+    color: String,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    block_height: u32,
+    #[serde(default)]
+    num_peers: u32,
+    #[serde(default)]
+    num_active_channels: u32,
+    #[serde(default)]
+    num_pending_channels: u32,
+    #[serde(default)]
+    num_inactive_channels: u32,
+    #[serde(default)]
+    uris: Vec<String>,
+    #[serde(default)]
+    chains: Vec<LndChain>,
+}
+
+#[derive(Deserialize)]
+struct LndChain {
+    network: String,
+}
+
+#[derive(Deserialize)]
+struct LndAddInvoice {
+    r_hash: String,
+    payment_request: String,
+}
+
+#[derive(Deserialize)]
+struct LndChannelPoint {
+    funding_txid_bytes: String,
+    #[serde(default)]
+    output_index: u32,
+}
+
+#[derive(Deserialize)]
+struct LndSendResponse {
+    #[serde(default)]
+    payment_error: String,
+    #[serde(default)]
+    payment_preimage: String,
+    payment_route: Option<LndRoute>,
+}
+
+#[derive(Deserialize, Default)]
+struct LndRoute {
+    #[serde(default, deserialize_with = "de_u64")]
+    total_amt_msat: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    total_fees_msat: u64,
+    #[serde(default)]
+    hops: Vec<LndHop>,
+}
+
+#[derive(Deserialize)]
+struct LndHop {
+    #[serde(default, deserialize_with = "de_u64")]
+    chan_id: u64,
+    pub_key: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    amt_to_forward_msat: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    fee_msat: u64,
+    #[serde(default)]
+    expiry: u32,
+}
+
+impl From<LndHop> for RouteHop {
+    fn from(hop: LndHop) -> Self {
+        RouteHop {
+            node_id: hop.pub_key,
+            short_channel_id: Some(scid_from_u64(hop.chan_id)),
+            // Montant remis au nœud : ce qu'il transfère plus ses frais
+            amount_msat: hop.amt_to_forward_msat + hop.fee_msat,
+            delay: Some(hop.expiry),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LndPayReq {
+    destination: String,
+    payment_hash: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    num_msat: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    timestamp: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    expiry: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    cltv_expiry: u64,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct LndPayments {
+    #[serde(default)]
+    payments: Vec<LndPayment>,
+}
+
+#[derive(Deserialize)]
+struct LndPayment {
+    payment_hash: String,
+    #[serde(default)]
+    payment_preimage: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    value_msat: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    fee_msat: u64,
+    #[serde(default)]
+    status: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    creation_date: u64,
+    #[serde(default)]
+    failure_reason: String,
+    #[serde(default)]
+    htlcs: Vec<LndHtlcAttempt>,
+}
+
+#[derive(Deserialize)]
+struct LndHtlcAttempt {
+    #[serde(default)]
+    status: String,
+    route: Option<LndRoute>,
+}
+
+impl From<LndPayment> for Payment {
+    fn from(payment: LndPayment) -> Self {
+        let status = match payment.status.as_str() {
+            "SUCCEEDED" => PaymentStatus::Complete,
+            "FAILED" => PaymentStatus::Failed,
+            _ => PaymentStatus::Pending,
+        };
+        // Route de la tentative réussie, ou de la dernière tentative
+        let attempt = payment
+            .htlcs
+            .iter()
+            .position(|htlc| htlc.status == "SUCCEEDED")
+            .or_else(|| payment.htlcs.len().checked_sub(1));
+        let route: Option<Vec<RouteHop>> = attempt
+            .and_then(|i| payment.htlcs.into_iter().nth(i))
+            .and_then(|htlc| htlc.route)
+            .map(|route| route.hops.into_iter().map(RouteHop::from).collect());
+        Payment {
+            payment_hash: payment.payment_hash,
+            payment_preimage: Some(payment.payment_preimage).filter(|p| !p.is_empty() && p.chars().any(|c| c != '0')),
+            destination: route.as_ref().and_then(|route| route.last()).map(|hop| hop.node_id.clone()),
+            amount_msat: payment.value_msat,
+            amount_sent_msat: payment.value_msat + payment.fee_msat,
+            status,
+            route,
+            failure_reason: Some(payment.failure_reason).filter(|r| !r.is_empty() && r != "FAILURE_REASON_NONE"),
+            created_at: Some(payment.creation_date),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LndInvoices {
+    #[serde(default)]
+    invoices: Vec<LndInvoice>,
+}
+
+#[derive(Deserialize)]
+struct LndInvoice {
+    r_hash: String,
+    #[serde(default)]
+    memo: String,
+    #[serde(default)]
+    payment_request: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    value_msat: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    amt_paid_msat: u64,
+    #[serde(default)]
+    state: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    creation_date: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    expiry: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    settle_date: u64,
+}
+
+impl TryFrom<LndInvoice> for InvoiceEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(invoice: LndInvoice) -> Result<Self> {
+        let expires_at = invoice.creation_date + invoice.expiry;
+        let status = match invoice.state.as_str() {
+            "SETTLED" => InvoiceStatus::Paid,
+            "CANCELED" => InvoiceStatus::Expired,
+            _ if invoice.expiry > 0 && chrono::Utc::now().timestamp() as u64 >= expires_at => InvoiceStatus::Expired,
+            _ => InvoiceStatus::Unpaid,
+        };
+        Ok(InvoiceEntry {
+            label: None,
+            bolt11: Some(invoice.payment_request).filter(|r| !r.is_empty()),
+            payment_hash: base64_to_hex(&invoice.r_hash)?,
+            amount_msat: Some(invoice.value_msat).filter(|amount| *amount > 0),
+            amount_received_msat: Some(invoice.amt_paid_msat).filter(|_| status == InvoiceStatus::Paid),
+            status,
+            description: Some(invoice.memo).filter(|m| !m.is_empty()),
+            paid_at: Some(invoice.settle_date).filter(|date| *date > 0),
+            expires_at: Some(expires_at),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct LndNewAddress {
+    address: String,
+}
+
+#[derive(Deserialize)]
+struct LndUtxos {
+    #[serde(default)]
+    utxos: Vec<LndUtxo>,
+}
+
+#[derive(Deserialize)]
+struct LndUtxo {
+    #[serde(default)]
+    address: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    amount_sat: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    confirmations: u64,
+    outpoint: LndOutpoint,
+}
+
+#[derive(Deserialize)]
+struct LndOutpoint {
+    txid_str: String,
+    #[serde(default)]
+    output_index: u32,
+}
+
+#[derive(Deserialize)]
+struct LndChannels {
+    #[serde(default)]
+    channels: Vec<LndChannel>,
+}
+
+#[derive(Deserialize)]
+struct LndChannel {
+    remote_pubkey: String,
+    channel_point: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    chan_id: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    capacity: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    local_balance: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    remote_balance: u64,
+    /// Contraintes imposées au nœud local, dont sa réserve.
+    #[serde(default)]
+    local_constraints: LndChannelConstraints,
+    #[serde(default)]
+    remote_constraints: LndChannelConstraints,
+    #[serde(default)]
+    active: bool,
+    /// Canal non annoncé au réseau.
+    #[serde(default)]
+    private: bool,
+}
+
+#[derive(Deserialize, Default)]
+struct LndChannelConstraints {
+    #[serde(default, deserialize_with = "de_u64")]
+    chan_reserve_sat: u64,
+}
+
+#[derive(Deserialize)]
+struct LndPendingChannels {
+    #[serde(default)]
+    pending_open_channels: Vec<LndPendingOpen>,
+    #[serde(default)]
+    waiting_close_channels: Vec<LndPendingOpen>,
+    #[serde(default)]
+    pending_force_closing_channels: Vec<LndPendingOpen>,
+}
+
+#[derive(Deserialize)]
+struct LndPendingOpen {
+    channel: LndPendingChannel,
+}
+
+#[derive(Deserialize)]
+struct LndPendingChannel {
+    remote_node_pub: String,
+    channel_point: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    capacity: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    local_balance: u64,
+}
+
+#[derive(Deserialize)]
+struct LndCloseUpdate {
+    result: Option<LndCloseResult>,
+    error: Option<LndError>,
+}
+
+#[derive(Deserialize)]
+struct LndCloseResult {
+    close_pending: Option<LndPendingUpdate>,
+}
+
+#[derive(Deserialize)]
+struct LndPendingUpdate {
+    txid: String,
+}
+
+#[derive(Deserialize)]
+struct LndClosedChannels {
+    #[serde(default)]
+    channels: Vec<LndClosedChannel>,
+}
+
+#[derive(Deserialize)]
+struct LndClosedChannel {
+    channel_point: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    chan_id: u64,
+    #[serde(default)]
+    remote_pubkey: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    capacity: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    settled_balance: u64,
+    #[serde(default)]
+    closing_tx_hash: String,
+    #[serde(default)]
+    close_type: String,
+}
+
+#[derive(Deserialize)]
+struct LndGraph {
+    #[serde(default)]
+    nodes: Vec<LndGraphNode>,
+    #[serde(default)]
+    edges: Vec<LndGraphEdge>,
+}
+
+#[derive(Deserialize)]
+struct LndGraphNode {
+    pub_key: String,
+    #[serde(default)]
+    alias: String,
+    #[serde(default)]
+    color: String,
+    #[serde(default)]
+    last_update: u32,
+    #[serde(default)]
+    addresses: Vec<LndNodeAddress>,
+}
+
+#[derive(Deserialize)]
+struct LndNodeAddress {
+    addr: String,
+}
+
+#[derive(Deserialize)]
+struct LndGraphEdge {
+    #[serde(deserialize_with = "de_u64")]
+    channel_id: u64,
+    node1_pub: String,
+    node2_pub: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    capacity: u64,
+    node1_policy: Option<LndRoutingPolicy>,
+    node2_policy: Option<LndRoutingPolicy>,
+}
+
+#[derive(Deserialize)]
+struct LndRoutingPolicy {
+    #[serde(default)]
+    time_lock_delta: u32,
+    #[serde(default, deserialize_with = "de_u64")]
+    min_htlc: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    fee_base_msat: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    fee_rate_milli_msat: u64,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default, deserialize_with = "de_u64")]
+    max_htlc_msat: u64,
+    #[serde(default)]
+    last_update: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::fixtures::{channel, BOB};
+
+    #[test]
+    fn chan_id_is_decoded_as_short_channel_id() {
+        let chan_id = (103u64 << 40) | (1 << 16) | 2;
+        assert_eq!(scid_from_u64(chan_id), "103x1x2");
+    }
+
+    #[test]
+    fn graph_edges_accept_string_encoded_integers() {
+        let edge: LndGraphEdge = serde_json::from_str(
+            r#"{
+                "channel_id": "113249697660928",
+                "node1_pub": "02aa",
+                "node2_pub": "03bb",
+                "capacity": "1000000",
+                "node1_policy": { "time_lock_delta": 80, "min_htlc": "1000", "fee_base_msat": "1000",
+                                  "fee_rate_milli_msat": "1", "disabled": false, "max_htlc_msat": "990000000" },
+                "node2_policy": null
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(scid_from_u64(edge.channel_id), "103x0x0");
+        assert_eq!(edge.capacity, 1_000_000);
+        assert_eq!(edge.node1_policy.unwrap().max_htlc_msat, 990_000_000);
+    }
+
+    #[test]
+    fn malformed_channel_points_are_rejected() {
+        assert_eq!(split_channel_point(&format!("{}:1", "ab".repeat(32))).unwrap(), ("ab".repeat(32), 1));
+        for channel_point in ["abab", "abab:", "abab:x"] {
+            let err = split_channel_point(channel_point).unwrap_err();
+            assert!(err.to_string().contains(channel_point), "{}", err);
+        }
+    }
+
+    #[test]
+    fn push_is_rejected_unless_in_whole_satoshis() {
+        assert_eq!(push_sat(25_000).unwrap(), 25);
+        assert!(push_sat(1_500).unwrap_err().to_string().contains("1500"));
+    }
+
+    #[test]
+    fn private_channels_are_not_public() {
+        let graph: LndGraph = serde_json::from_str(
+            r#"{ "edges": [
+                { "channel_id": "1", "node1_pub": "02aa", "node2_pub": "03bb", "capacity": "1000",
+                  "node1_policy": { "disabled": false }, "node2_policy": null },
+                { "channel_id": "2", "node1_pub": "02aa", "node2_pub": "03cc", "capacity": "1000",
+                  "node1_policy": { "disabled": false }, "node2_policy": null }
+            ] }"#,
+        )
+        .unwrap();
+        let channels = gossip_channels(graph, &HashSet::from([2]));
+        let public: Vec<bool> = channels.iter().map(|c| c.public).collect();
+        assert_eq!(public, [true, false]);
+    }
+
+    type Requests = std::sync::Arc<std::sync::Mutex<Vec<String>>>;
+
+    /// Faux lnd : `respond` reçoit la ligne de requête et renvoie le corps de
+    /// la réponse, ou `None` pour ne jamais répondre.
+    async fn fake_lnd(respond: fn(&str) -> Option<&'static str>) -> (LndNode, Requests) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let rest_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Requests::default();
+        let received = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buffer = vec![0; 4096];
+                let read = socket.read(&mut buffer).await.unwrap();
+                let request = String::from_utf8_lossy(&buffer[..read]).lines().next().unwrap_or_default().to_string();
+                received.lock().unwrap().push(request.clone());
+                let Some(body) = respond(&request) else {
+                    // La connexion reste ouverte sans réponse
+                    tokio::spawn(async move {
+                        std::future::pending::<()>().await;
+                        drop(socket)
+                    });
+                    continue;
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        let macaroon = std::env::temp_dir().join(format!("lightning_viz_{}_admin.macaroon", std::process::id()));
+        std::fs::write(&macaroon, b"macaroon").unwrap();
+        let config = LndConfig { rest_url, macaroon_path: macaroon.display().to_string(), tls_cert_path: None };
+        (LndNode::new(config, "node3".to_string()).unwrap(), requests)
+    }
+
+    #[tokio::test]
+    async fn refused_cooperative_close_is_not_forced() {
+        let (node, requests) = fake_lnd(|_| Some("{\"error\":{\"message\":\"peer is offline\"}}\n")).await;
+        let channel = ChannelEntry { connected: false, ..channel(BOB, None, 1_000_000_000) };
+
+        let err = node.close_channel(&channel, Some(Duration::from_secs(5))).await.unwrap_err();
+        assert!(err.to_string().contains("peer is offline"), "{}", err);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1, "{:?}", requests);
+        assert!(requests[0].contains("force=false"), "{:?}", requests);
+    }
+
+    #[tokio::test]
+    async fn refused_force_close_names_the_pending_cooperative_close() {
+        let (node, requests) = fake_lnd(|request| {
+            (!request.contains("force=false")).then_some("{\"error\":{\"message\":\"channel is being closed\"}}\n")
+        })
+        .await;
+        let channel = channel(BOB, Some("103x1x0"), 1_000_000_000);
+
+        let err = node.close_channel(&channel, Some(Duration::from_millis(50))).await.unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(message.contains("fermeture coopérative") && message.contains("n'est pas annulée"), "{}", message);
+        assert!(message.contains("channel is being closed"), "{}", message);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2, "{:?}", requests);
+        assert!(requests[1].contains("force=true"), "{:?}", requests);
+    }
+}
@@ -0,0 +1,774 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use super::backend::LightningBackend;
+use super::types::{
+    ChannelClosing, ChannelEntry, ChannelLiquidity, ChannelOpening, ChannelState, ClosedChannel, CloseType, DecodedInvoice, Funds, GossipChannel, GossipNode, Invoice, InvoiceEntry,
+    InvoiceStatus, NodeInfo, OutputEntry, OutputStatus, Payment, PaymentStatus, RouteHop,
+};
+use crate::bitcoin::MockBitcoin;
+
+/// Confirmations nécessaires avant qu'un canal passe en `CHANNELD_NORMAL`,
+/// comme le `funding-confirms` par défaut de lightningd.
+pub const MOCK_MIN_DEPTH: u64 = 3;
+
+/// Confirmations de la transaction de fermeture forcée avant que le nœud
+/// récupère sa sortie (`to_self_delay`, raccourci pour les tests).
+pub const MOCK_TO_SELF_DELAY: u64 = 5;
+
+/// Confirmations après lesquelles un canal fermé quitte `list_funds` pour
+/// `list_closed_channels`, comme les 100 blocs de lightningd.
+pub const MOCK_FORGET_DEPTH: u64 = 100;
+
+/// Réserve de chaque extrémité d'un canal, en pour mille de sa capacité
+/// (1 %, la valeur par défaut de lightningd).
+const MOCK_RESERVE_PERMILLE: u64 = 10;
+
+/// Politique de relais prêtée au pair intermédiaire d'une route simulée,
+/// identique à celle de `gossip_channel`.
+const MOCK_BASE_FEE_MSAT: u64 = 1000;
+const MOCK_FEE_PPM: u64 = 10;
+const MOCK_CLTV_DELTA: u32 = 6;
+const MOCK_FINAL_CLTV: u32 = 18;
+
+/// Ouverture de canal publiée par le nœud simulé.
+#[derive(Debug, Clone)]
+struct Opening {
+    peer_id: String,
+    txid: String,
+    amount_sat: u64,
+    change_sat: u64,
+    /// Part cédée au pair à l'ouverture ou dépensée en paiements.
+    spent_msat: u64,
+}
+
+/// Fermeture publiée par le nœud simulé.
+#[derive(Debug, Clone)]
+struct Closing {
+    channel: ChannelEntry,
+    txid: String,
+    unilateral: bool,
+    /// Transaction qui récupère la sortie d'une fermeture forcée.
+    sweep_txid: Option<String>,
+}
+
+#[derive(Debug)]
+struct MockState {
+    info: NodeInfo,
+    funds: Funds,
+    gossip_nodes: Vec<GossipNode>,
+    gossip_channels: Vec<GossipChannel>,
+    peers: Vec<String>,
+    addresses: Vec<String>,
+    /// Sorties on-chain consommées par une ouverture, `(txid, vout)`.
+    spent: Vec<(String, u32)>,
+    openings: Vec<Opening>,
+    closings: Vec<Closing>,
+    invoices: Vec<InvoiceEntry>,
+    payments: Vec<Payment>,
+    next_id: u64,
+    failures: HashMap<&'static str, String>,
+    stalled: HashSet<&'static str>,
+}
+
+/// Nœud Lightning en mémoire, déterministe, pour les tests sans lightningd.
+///
+/// Topologie, soldes et erreurs se configurent avec les méthodes `with_*` ;
+/// relié à un `MockBitcoin`, le nœud suit aussi son portefeuille on-chain et
+/// l'avancement de ses ouvertures de canaux au fil des blocs minés.
+#[derive(Debug)]
+pub struct MockLightning {
+    id: String,
+    chain: Option<MockBitcoin>,
+    state: Mutex<MockState>,
+}
+
+impl MockLightning {
+    pub fn new(id: &str, pubkey: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            chain: None,
+            state: Mutex::new(MockState {
+                info: NodeInfo {
+                    id: pubkey.to_string(),
+                    alias: Some(id.to_string()),
+                    color: "3399ff".to_string(),
+                    network: "regtest".to_string(),
+                    version: "mock".to_string(),
+                    blockheight: 0,
+                    num_peers: 0,
+                    num_active_channels: 0,
+                    num_pending_channels: 0,
+                    num_inactive_channels: 0,
+                    fees_collected_msat: 0,
+                    addresses: Vec::new(),
+                },
+                funds: Funds::default(),
+                gossip_nodes: Vec::new(),
+                gossip_channels: Vec::new(),
+                peers: Vec::new(),
+                addresses: Vec::new(),
+                spent: Vec::new(),
+                openings: Vec::new(),
+                closings: Vec::new(),
+                invoices: Vec::new(),
+                payments: Vec::new(),
+                next_id: 0,
+                failures: HashMap::new(),
+                stalled: HashSet::new(),
+            }),
+        }
+    }
+
+    pub fn with_alias(self, alias: &str) -> Self {
+        self.lock().info.alias = Some(alias.to_string());
+        self
+    }
+
+    /// Relie le nœud à une chaîne simulée partagée.
+    pub fn with_chain(mut self, chain: &MockBitcoin) -> Self {
+        self.chain = Some(chain.clone());
+        self
+    }
+
+    /// Ajoute un canal déjà ouvert, tel que renvoyé par `list_funds`.
+    pub fn with_channel(self, channel: ChannelEntry) -> Self {
+        self.lock().funds.channels.push(channel);
+        self
+    }
+
+    /// Ajoute une sortie on-chain confirmée de `amount_sat`.
+    pub fn with_onchain_balance(self, amount_sat: u64) -> Self {
+        {
+            let mut state = self.lock();
+            let id = state.next_id();
+            state.funds.outputs.push(OutputEntry {
+                txid: format!("{:064x}", 0xfeed_0000 + id),
+                output: 0,
+                amount_msat: amount_sat * 1000,
+                status: OutputStatus::Confirmed,
+                address: None,
+                blockheight: Some(1),
+                reserved: false,
+            });
+        }
+        self
+    }
+
+    /// Vue gossip renvoyée par `list_nodes` et `list_channels`.
+    pub fn with_gossip(self, nodes: Vec<GossipNode>, channels: Vec<GossipChannel>) -> Self {
+        {
+            let mut state = self.lock();
+            state.gossip_nodes = nodes;
+            state.gossip_channels = channels;
+        }
+        self
+    }
+
+    /// Fait échouer chaque appel à `method` avec `message`.
+    pub fn failing(self, method: &'static str, message: &str) -> Self {
+        self.fail_on(method, message);
+        self
+    }
+
+    pub fn fail_on(&self, method: &'static str, message: &str) {
+        self.lock().failures.insert(method, message.to_string());
+    }
+
+    /// Laisse chaque appel à `method` sans réponse, comme un nœud bloqué.
+    pub fn stalling(self, method: &'static str) -> Self {
+        self.lock().stalled.insert(method);
+        self
+    }
+
+    /// Marque la facture `payment_hash` comme payée, les nœuds simulés ne se
+    /// transmettant pas les paiements entre eux.
+    pub fn settle_invoice(&self, payment_hash: &str) {
+        let mut state = self.lock();
+        if let Some(invoice) = state.invoices.iter_mut().find(|i| i.payment_hash == payment_hash) {
+            invoice.status = InvoiceStatus::Paid;
+            invoice.amount_received_msat = invoice.amount_msat;
+            invoice.paid_at = Some(1_700_000_000);
+        }
+    }
+
+    /// Pairs auxquels le nœud s'est connecté, par clé publique.
+    pub fn peers(&self) -> Vec<String> {
+        self.lock().peers.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    async fn check(&self, method: &'static str) -> Result<MutexGuard<'_, MockState>> {
+        let stalled = self.lock().stalled.contains(method);
+        if stalled {
+            std::future::pending::<()>().await;
+        }
+        let state = self.lock();
+        match state.failures.get(method) {
+            Some(message) => Err(anyhow::anyhow!("{}", message)),
+            None => Ok(state),
+        }
+    }
+
+    fn confirmations(&self, txid: &str) -> Option<u64> {
+        self.chain.as_ref().and_then(|chain| chain.confirmations(txid))
+    }
+
+    /// Rang de `txid` parmi les transactions du bloc `height`, à partir de 1,
+    /// pour que deux ouvertures d'un même bloc aient des short channel ids distincts.
+    fn block_position(&self, height: u64, txid: &str) -> usize {
+        let Some(chain) = &self.chain else { return 1 };
+        chain
+            .transactions()
+            .iter()
+            .filter(|tx| tx.block_height == Some(height))
+            .position(|tx| tx.txid == txid)
+            .map_or(1, |i| i + 1)
+    }
+
+    /// Fonds configurés complétés par l'état de la chaîne simulée.
+    fn current_funds(&self, state: &MockState) -> Funds {
+        let mut funds = state.funds.clone();
+
+        if let Some(chain) = &self.chain {
+            for address in &state.addresses {
+                for tx in chain.received(address) {
+                    funds.outputs.push(OutputEntry {
+                        txid: tx.txid,
+                        output: 0,
+                        amount_msat: tx.amount_sat * 1000,
+                        status: if tx.block_height.is_some() {
+                            OutputStatus::Confirmed
+                        } else {
+                            OutputStatus::Unconfirmed
+                        },
+                        address: Some(address.clone()),
+                        blockheight: tx.block_height.map(|h| h as u32),
+                        reserved: false,
+                    });
+                }
+            }
+        }
+
+        for opening in &state.openings {
+            let confirmations = self.confirmations(&opening.txid).unwrap_or(0);
+            let funding = self.chain.as_ref().and_then(|chain| chain.transaction(&opening.txid));
+            if opening.change_sat > 0 {
+                funds.outputs.push(OutputEntry {
+                    txid: opening.txid.clone(),
+                    output: 1,
+                    amount_msat: opening.change_sat * 1000,
+                    status: if confirmations > 0 {
+                        OutputStatus::Confirmed
+                    } else {
+                        OutputStatus::Unconfirmed
+                    },
+                    address: None,
+                    blockheight: funding.as_ref().and_then(|tx| tx.block_height).map(|h| h as u32),
+                    reserved: false,
+                });
+            }
+            let normal = confirmations >= MOCK_MIN_DEPTH;
+            let amount_msat = opening.amount_sat * 1000;
+            let our_amount_msat = amount_msat - opening.spent_msat;
+            let reserve_msat = amount_msat * MOCK_RESERVE_PERMILLE / 1000;
+            funds.channels.push(ChannelEntry {
+                peer_id: opening.peer_id.clone(),
+                short_channel_id: funding
+                    .and_then(|tx| tx.block_height)
+                    .filter(|_| normal)
+                    .map(|height| format!("{}x{}x0", height, self.block_position(height, &opening.txid))),
+                channel_id: Some(opening.txid.clone()),
+                funding_txid: opening.txid.clone(),
+                funding_output: 0,
+                state: if normal { ChannelState::ChanneldNormal } else { ChannelState::ChanneldAwaitingLockin },
+                connected: state.peers.contains(&opening.peer_id),
+                amount_msat,
+                our_amount_msat,
+                liquidity: normal.then(|| ChannelLiquidity {
+                    our_reserve_msat: reserve_msat,
+                    their_reserve_msat: reserve_msat,
+                    spendable_msat: our_amount_msat.saturating_sub(reserve_msat),
+                    receivable_msat: (amount_msat - our_amount_msat).saturating_sub(reserve_msat),
+                }),
+            });
+        }
+
+        // Un canal fermé reste listé jusqu'à `MOCK_FORGET_DEPTH` confirmations
+        funds.channels.retain_mut(|channel| {
+            let Some(closing) = state.closings.iter().find(|c| c.channel.funding_txid == channel.funding_txid) else {
+                return true;
+            };
+            let confirmations = self.confirmations(&closing.txid).unwrap_or(0);
+            channel.state = match (confirmations, closing.unilateral) {
+                (0, false) => ChannelState::ClosingdComplete,
+                (0, true) => ChannelState::AwaitingUnilateral,
+                _ => ChannelState::Onchain,
+            };
+            channel.short_channel_id = closing.channel.short_channel_id.clone();
+            channel.our_amount_msat = closing.channel.our_amount_msat;
+            channel.liquidity = None;
+            confirmations < MOCK_FORGET_DEPTH
+        });
+
+        funds
+            .outputs
+            .retain(|o| !state.spent.iter().any(|(txid, vout)| *txid == o.txid && *vout == o.output));
+        funds
+    }
+}
+
+impl MockState {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Nouvelle adresse du portefeuille on-chain du nœud.
+    fn new_address(&mut self, node_id: &str) -> String {
+        let id = self.next_id();
+        let address = format!("bcrt1q{}{}", node_id, id);
+        self.addresses.push(address.clone());
+        address
+    }
+}
+
+impl MockLightning {
+    /// Publie la récupération des fermetures forcées dont le délai
+    /// `MOCK_TO_SELF_DELAY` est écoulé, vers le portefeuille du nœud.
+    fn sweep_closings(&self, state: &mut MockState) {
+        let Some(chain) = &self.chain else { return };
+        for i in 0..state.closings.len() {
+            let closing = &state.closings[i];
+            if !closing.unilateral
+                || closing.sweep_txid.is_some()
+                || self.confirmations(&closing.txid).unwrap_or(0) < MOCK_TO_SELF_DELAY
+            {
+                continue;
+            }
+            let (txid, amount_sat) = (closing.txid.clone(), closing.channel.our_amount_msat / 1000);
+            let address = state.new_address(&self.id);
+            chain.spend(&txid, 0);
+            state.closings[i].sweep_txid = Some(chain.broadcast(&address, amount_sat));
+        }
+    }
+
+    /// Envoie `amount_msat` à `payee` par le canal direct s'il existe, sinon
+    /// via le pair d'un canal suffisant, qui prélève ses frais de relais.
+    fn send(&self, state: &mut MockState, payee: &str, payment_hash: String, amount_msat: u64) -> Payment {
+        let funds = self.current_funds(state);
+        let usable = |c: &&ChannelEntry, needed: u64| {
+            c.state.is_normal() && c.connected && c.our_amount_msat >= needed
+        };
+        let fee_msat = MOCK_BASE_FEE_MSAT + amount_msat * MOCK_FEE_PPM / 1_000_000;
+        let direct = funds.channels.iter().find(|c| c.peer_id == payee && usable(c, amount_msat));
+        let (channel, route) = match direct {
+            Some(channel) => (channel, vec![RouteHop {
+                node_id: payee.to_string(),
+                short_channel_id: channel.short_channel_id.clone(),
+                amount_msat,
+                delay: Some(MOCK_FINAL_CLTV),
+            }]),
+            None => match funds.channels.iter().find(|c| usable(c, amount_msat + fee_msat)) {
+                Some(channel) => (channel, vec![
+                    RouteHop {
+                        node_id: channel.peer_id.clone(),
+                        short_channel_id: channel.short_channel_id.clone(),
+                        amount_msat: amount_msat + fee_msat,
+                        delay: Some(MOCK_FINAL_CLTV + MOCK_CLTV_DELTA),
+                    },
+                    RouteHop {
+                        node_id: payee.to_string(),
+                        short_channel_id: None,
+                        amount_msat,
+                        delay: Some(MOCK_FINAL_CLTV),
+                    },
+                ]),
+                None => {
+                    let payment = Payment::failed(
+                        &payment_hash,
+                        Some(payee.to_string()),
+                        amount_msat,
+                        "Ran out of routes to try".to_string(),
+                    );
+                    state.payments.push(payment.clone());
+                    return payment;
+                }
+            },
+        };
+
+        let amount_sent_msat = route[0].amount_msat;
+        let funding_txid = channel.funding_txid.clone();
+        if let Some(opening) = state.openings.iter_mut().find(|o| o.txid == funding_txid) {
+            opening.spent_msat += amount_sent_msat;
+        } else if let Some(channel) = state.funds.channels.iter_mut().find(|c| c.funding_txid == funding_txid) {
+            channel.our_amount_msat -= amount_sent_msat;
+        }
+        let payment = Payment {
+            payment_preimage: Some(payment_hash.chars().rev().collect()),
+            payment_hash,
+            destination: Some(payee.to_string()),
+            amount_msat,
+            amount_sent_msat,
+            status: PaymentStatus::Complete,
+            route: Some(route),
+            failure_reason: None,
+            created_at: Some(1_700_000_000),
+        };
+        state.payments.push(payment.clone());
+        payment
+    }
+}
+
+/// Facture simulée, décodable par `MockLightning::pay`.
+fn mock_bolt11(amount_msat: u64, payment_hash: &str, payee: &str) -> String {
+    format!("lnbcrtmock{}x{}x{}", amount_msat, payment_hash, payee)
+}
+
+/// Montant, hash et destinataire d'une facture simulée.
+fn parse_mock_bolt11(bolt11: &str) -> Result<(u64, String, String)> {
+    let mut parts = bolt11
+        .strip_prefix("lnbcrtmock")
+        .ok_or_else(|| anyhow::anyhow!("Invalid bolt11: {}", bolt11))?
+        .split('x');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(amount), Some(payment_hash), Some(payee)) => {
+            Ok((amount.parse()?, payment_hash.to_string(), payee.to_string()))
+        }
+        _ => Err(anyhow::anyhow!("Invalid bolt11: {}", bolt11)),
+    }
+}
+
+/// Une direction d'un canal annoncé, avec une politique par défaut.
+pub fn gossip_channel(source: &str, destination: &str, short_channel_id: &str, amount_sat: u64) -> GossipChannel {
+    GossipChannel {
+        source: source.to_string(),
+        destination: destination.to_string(),
+        short_channel_id: short_channel_id.to_string(),
+        amount_msat: amount_sat * 1000,
+        active: true,
+        public: true,
+        base_fee_msat: 1000,
+        fee_ppm: 10,
+        cltv_delta: 6,
+        htlc_minimum_msat: 0,
+        htlc_maximum_msat: Some(amount_sat * 990),
+        last_update: 1_700_000_000,
+    }
+}
+
+pub fn gossip_node(id: &str, alias: &str) -> GossipNode {
+    GossipNode {
+        id: id.to_string(),
+        alias: Some(alias.to_string()),
+        color: None,
+        addresses: Vec::new(),
+        last_timestamp: Some(1_700_000_000),
+    }
+}
+
+#[async_trait]
+impl LightningBackend for MockLightning {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn get_node_info(&self) -> Result<NodeInfo> {
+        let state = self.check("get_node_info").await?;
+        let funds = self.current_funds(&state);
+        let mut info = state.info.clone();
+        info.blockheight = self.chain.as_ref().map_or(0, |chain| chain.height() as u32);
+        info.num_peers = state.peers.len() as u32;
+        info.num_active_channels = funds.channels.iter().filter(|c| c.state.is_normal()).count() as u32;
+        info.num_pending_channels = funds.channels.iter().filter(|c| c.state.is_opening()).count() as u32;
+        Ok(info)
+    }
+
+    async fn create_invoice(&self, amount_msat: u64, label: &str, description: &str) -> Result<Invoice> {
+        let mut state = self.check("create_invoice").await?;
+        if state.invoices.iter().any(|existing| existing.label.as_deref() == Some(label)) {
+            return Err(anyhow::anyhow!("Duplicate label '{}'", label));
+        }
+        let id = state.next_id();
+        let payment_hash = format!("{:064x}", 0x1_0000_0000 + id);
+        let invoice = Invoice {
+            bolt11: mock_bolt11(amount_msat, &payment_hash, &state.info.id),
+            payment_hash,
+            expires_at: None,
+        };
+        state.invoices.push(InvoiceEntry {
+            label: Some(label.to_string()),
+            bolt11: Some(invoice.bolt11.clone()),
+            payment_hash: invoice.payment_hash.clone(),
+            amount_msat: Some(amount_msat),
+            amount_received_msat: None,
+            status: InvoiceStatus::Unpaid,
+            description: Some(description.to_string()),
+            paid_at: None,
+            expires_at: None,
+        });
+        Ok(invoice)
+    }
+
+    async fn open_channel(&self, peer_id: &str, amount_sat: u64, push_msat: u64) -> Result<ChannelOpening> {
+        let mut state = self.check("open_channel").await?;
+        if push_msat > amount_sat * 1000 {
+            return Err(anyhow::anyhow!("push_msat {} exceeds channel capacity", push_msat));
+        }
+        if !state.peers.iter().any(|peer| peer == peer_id) {
+            return Err(anyhow::anyhow!("Unknown peer {}", peer_id));
+        }
+        let chain = self
+            .chain
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Mock node {} has no chain to publish on", self.id))?;
+
+        let mut selected = Vec::new();
+        let mut total_msat = 0;
+        for output in self.current_funds(&state).outputs {
+            if total_msat >= amount_sat * 1000 {
+                break;
+            }
+            if output.status == OutputStatus::Confirmed && !output.reserved {
+                total_msat += output.amount_msat;
+                selected.push((output.txid, output.output));
+            }
+        }
+        if total_msat < amount_sat * 1000 {
+            return Err(anyhow::anyhow!(
+                "Could not afford {}sat using all {} available UTXOs",
+                amount_sat,
+                selected.len()
+            ));
+        }
+
+        let txid = chain.broadcast(&format!("funding:{}", peer_id), amount_sat);
+        state.spent.extend(selected);
+        state.openings.push(Opening {
+            peer_id: peer_id.to_string(),
+            txid: txid.clone(),
+            amount_sat,
+            change_sat: total_msat / 1000 - amount_sat,
+            spent_msat: push_msat,
+        });
+        Ok(ChannelOpening { txid: txid.clone(), outnum: 0, channel_id: Some(txid) })
+    }
+
+    async fn close_channel(&self, channel: &ChannelEntry, unilateral_timeout: Option<Duration>) -> Result<ChannelClosing> {
+        let mut state = self.check("close_channel").await?;
+        let chain = self
+            .chain
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Mock node {} has no chain to publish on", self.id))?;
+        let Some(channel) = self
+            .current_funds(&state)
+            .channels
+            .into_iter()
+            .find(|c| c.funding_txid == channel.funding_txid)
+        else {
+            return Err(anyhow::anyhow!("Unknown channel {}", channel.funding_txid));
+        };
+        if !(channel.state.is_normal() || channel.state.is_opening()) {
+            return Err(anyhow::anyhow!("Channel {} is already closing ({})", channel.funding_txid, channel.state));
+        }
+
+        // La fermeture coopérative suppose le pair connecté ; sinon lightningd
+        // attend la fin du délai avant de publier son engagement
+        let unilateral = match unilateral_timeout {
+            Some(timeout) if timeout.is_zero() => true,
+            _ if channel.connected => false,
+            Some(_) => true,
+            None => return Err(anyhow::anyhow!("Peer {} is not connected", channel.peer_id)),
+        };
+        let amount_sat = channel.our_amount_msat / 1000;
+        let txid = if unilateral {
+            chain.broadcast(&format!("commitment:{}", channel.peer_id), amount_sat)
+        } else {
+            let address = state.new_address(&self.id);
+            chain.broadcast(&address, amount_sat)
+        };
+        state.closings.push(Closing { channel, txid: txid.clone(), unilateral, sweep_txid: None });
+        Ok(ChannelClosing {
+            close_type: if unilateral { CloseType::Unilateral } else { CloseType::Mutual },
+            txid: Some(txid),
+        })
+    }
+
+    async fn list_closed_channels(&self) -> Result<Vec<ClosedChannel>> {
+        let mut state = self.check("list_closed_channels").await?;
+        self.sweep_closings(&mut state);
+        Ok(state
+            .closings
+            .iter()
+            .filter(|closing| self.confirmations(&closing.txid).unwrap_or(0) >= MOCK_FORGET_DEPTH)
+            .map(|closing| ClosedChannel {
+                peer_id: Some(closing.channel.peer_id.clone()),
+                short_channel_id: closing.channel.short_channel_id.clone(),
+                funding_txid: closing.channel.funding_txid.clone(),
+                funding_output: closing.channel.funding_output,
+                amount_msat: closing.channel.amount_msat,
+                final_to_us_msat: closing.channel.our_amount_msat,
+                close_cause: "user".to_string(),
+                closing_txid: Some(closing.txid.clone()),
+            })
+            .collect())
+    }
+
+    async fn pay(&self, bolt11: &str) -> Result<Payment> {
+        let mut state = self.check("pay").await?;
+        let (amount_msat, payment_hash, payee) = parse_mock_bolt11(bolt11)?;
+        Ok(self.send(&mut state, &payee, payment_hash, amount_msat))
+    }
+
+    async fn keysend(&self, destination: &str, amount_msat: u64) -> Result<Payment> {
+        let mut state = self.check("keysend").await?;
+        let payment_hash = format!("{:064x}", 0x2_0000_0000 + state.next_id());
+        Ok(self.send(&mut state, destination, payment_hash, amount_msat))
+    }
+
+    async fn decode_invoice(&self, bolt11: &str) -> Result<DecodedInvoice> {
+        drop(self.check("decode_invoice").await?);
+        let (amount_msat, payment_hash, payee) = parse_mock_bolt11(bolt11)?;
+        Ok(DecodedInvoice {
+            payee,
+            payment_hash,
+            amount_msat: Some(amount_msat),
+            description: None,
+            created_at: 1_700_000_000,
+            expiry: 3600,
+            min_final_cltv_expiry: MOCK_FINAL_CLTV,
+        })
+    }
+
+    async fn list_payments(&self) -> Result<Vec<Payment>> {
+        Ok(self.check("list_payments").await?.payments.clone())
+    }
+
+    async fn list_invoices(&self) -> Result<Vec<InvoiceEntry>> {
+        Ok(self.check("list_invoices").await?.invoices.clone())
+    }
+
+    async fn wait_invoice(&self, payment_hash: &str) -> Result<InvoiceEntry> {
+        let state = self.check("wait_invoice").await?;
+        let invoice = state
+            .invoices
+            .iter()
+            .find(|i| i.payment_hash == payment_hash)
+            .ok_or_else(|| anyhow::anyhow!("Unknown invoice {}", payment_hash))?;
+        // Un vrai nœud bloquerait : le test doit d'abord appeler `settle_invoice`
+        if invoice.status == InvoiceStatus::Unpaid {
+            return Err(anyhow::anyhow!("Invoice {} is still unpaid", payment_hash));
+        }
+        Ok(invoice.clone())
+    }
+
+    async fn connect_peer(&self, node_id: &str, _host: &str, _port: u16) -> Result<()> {
+        let mut state = self.check("connect_peer").await?;
+        if !state.peers.iter().any(|peer| peer == node_id) {
+            state.peers.push(node_id.to_string());
+        }
+        Ok(())
+    }
+
+    async fn get_new_address(&self) -> Result<String> {
+        Ok(self.check("get_new_address").await?.new_address(&self.id))
+    }
+
+    async fn list_funds(&self) -> Result<Funds> {
+        let mut state = self.check("list_funds").await?;
+        self.sweep_closings(&mut state);
+        Ok(self.current_funds(&state))
+    }
+
+    async fn list_channels(&self) -> Result<Vec<GossipChannel>> {
+        Ok(self.check("list_channels").await?.gossip_channels.clone())
+    }
+
+    async fn list_nodes(&self) -> Result<Vec<GossipNode>> {
+        Ok(self.check("list_nodes").await?.gossip_nodes.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin::BitcoinBackend;
+    use crate::visualization::fixtures::{channel, ALICE, BOB, CAROL};
+
+    #[tokio::test]
+    async fn channel_becomes_normal_after_min_depth() {
+        let chain = MockBitcoin::new();
+        let alice = MockLightning::new("alice", ALICE).with_chain(&chain).with_onchain_balance(200_000);
+        alice.connect_peer(BOB, "127.0.0.1", 9736).await.unwrap();
+
+        let opening = alice.open_channel(BOB, 150_000, 0).await.unwrap();
+        let funds = alice.list_funds().await.unwrap();
+        assert_eq!(funds.channels[0].state, ChannelState::ChanneldAwaitingLockin);
+        assert_eq!(funds.outputs.len(), 1, "only the change output remains");
+        assert_eq!(funds.outputs[0].amount_msat, 50_000_000);
+
+        chain.generate_blocks(MOCK_MIN_DEPTH).await.unwrap();
+        let channel = &alice.list_funds().await.unwrap().channels[0];
+        assert_eq!(channel.state, ChannelState::ChanneldNormal);
+        assert_eq!(channel.funding_txid, opening.txid);
+        assert_eq!(channel.short_channel_id.as_deref(), Some("1x1x0"));
+    }
+
+    #[tokio::test]
+    async fn opening_requires_a_peer_and_funds() {
+        let chain = MockBitcoin::new();
+        let alice = MockLightning::new("alice", ALICE).with_chain(&chain);
+        assert!(alice.open_channel(BOB, 10_000, 0).await.is_err());
+
+        alice.connect_peer(BOB, "127.0.0.1", 9736).await.unwrap();
+        let err = alice.open_channel(BOB, 10_000, 0).await.unwrap_err();
+        assert!(err.to_string().contains("Could not afford"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn payment_uses_local_balance() {
+        let alice = MockLightning::new("alice", ALICE).with_channel(ChannelEntry {
+            amount_msat: 1_000_000,
+            ..channel(BOB, Some("103x1x0"), 600_000)
+        });
+        let bob = MockLightning::new("bob", BOB);
+        let invoice = bob.create_invoice(500_000, "coffee", "").await.unwrap();
+
+        let payment = alice.pay(&invoice.bolt11).await.unwrap();
+        assert_eq!(payment.payment_hash, invoice.payment_hash);
+        assert_eq!(payment.route_summary(), "1 saut(s)");
+        assert_eq!(payment.fee_msat(), 0);
+        assert_eq!(alice.list_funds().await.unwrap().channel_balance_msat(), 100_000);
+
+        let retry = alice.pay(&invoice.bolt11).await.unwrap();
+        assert_eq!(retry.status, PaymentStatus::Failed, "balance is exhausted");
+        assert_eq!(retry.failure_reason.as_deref(), Some("Ran out of routes to try"));
+        assert_eq!(alice.list_payments().await.unwrap().len(), 2);
+
+        assert!(bob.wait_invoice(&invoice.payment_hash).await.is_err(), "mocks do not settle each other");
+        bob.settle_invoice(&invoice.payment_hash);
+        let settled = bob.wait_invoice(&invoice.payment_hash).await.unwrap();
+        assert_eq!(settled.status, InvoiceStatus::Paid);
+        assert_eq!(settled.label.as_deref(), Some("coffee"));
+    }
+
+    #[tokio::test]
+    async fn keysend_through_a_peer_pays_its_fee() {
+        let alice = MockLightning::new("alice", ALICE).with_channel(ChannelEntry {
+            amount_msat: 10_000_000,
+            ..channel(BOB, Some("103x1x0"), 10_000_000)
+        });
+
+        let payment = alice.keysend(CAROL, 2_000_000).await.unwrap().ensure_complete().unwrap();
+        let hops: Vec<_> = payment.route.iter().flatten().map(|hop| hop.node_id.as_str()).collect();
+        assert_eq!(hops, [BOB, CAROL]);
+        assert_eq!(payment.fee_msat(), 1000 + 20);
+        assert_eq!(alice.list_funds().await.unwrap().channel_balance_msat(), 8_000_000 - 1020);
+    }
+}
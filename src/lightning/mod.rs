@@ -1,3 +1,17 @@
-mod node;
-
-pub use node::{LightningConfig, LightningNode}; 
\ No newline at end of file
+mod backend;
+mod lnd;
+#[cfg(test)]
+mod mock;
+mod node;
+mod rpc;
+mod types;
+
+pub use backend::{BackendKind, LightningBackend};
+pub use lnd::{LndConfig, LndNode};
+pub use node::{LightningConfig, LightningNode};
+pub use types::{
+    ChannelClosing, ChannelEntry, ChannelState, CloseType, ClosedChannel, Funds, GossipChannel, GossipNode, NodeInfo, OutputStatus,
+    Payment, PaymentStatus,
+};
+#[cfg(test)]
+pub use mock::{gossip_channel, gossip_node, MockLightning, MOCK_MIN_DEPTH, MOCK_TO_SELF_DELAY};
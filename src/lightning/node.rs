@@ -1,137 +1,450 @@
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
-use cln_rpc::{
-    ClnRpc,
-    Response,
-    Request,
-    model::requests::{InvoiceRequest, GetinfoRequest, ConnectRequest, FundchannelRequest, NewaddrRequest, ListfundsRequest},
-    primitives::{Amount, AmountOrAny, AmountOrAll, PublicKey},
-};
-use std::cell::UnsafeCell;
-use std::str::FromStr;
-use serde_json::Value;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LightningConfig {
-    pub network: String,
-    pub lightning_dir: String,
-    pub bitcoin_rpc_host: String,
-    pub bitcoin_rpc_port: u16,
-    pub bitcoin_rpc_user: String,
-    pub bitcoin_rpc_password: String,
-}
-
-pub struct LightningNode {
-    pub id: String,
-    rpc_client: Option<UnsafeCell<ClnRpc>>,
-    config: LightningConfig,
-}
-
-impl LightningNode {
-    pub fn new(config: LightningConfig, id: String) -> Self {
-        Self {
-            id,
-            rpc_client: None,
-            config,
-        }
-    }
-
-    pub async fn connect_rpc(&mut self) -> Result<()> {
-        let socket_path = format!("{}/regtest/lightning-rpc", self.config.lightning_dir);
-        self.rpc_client = Some(UnsafeCell::new(ClnRpc::new(socket_path).await?));
-        Ok(())
-    }
-
-    pub async fn get_node_info(&self) -> Result<Value> {
-        let client = self.rpc_client.as_ref().unwrap();
-        unsafe {
-            let response = (*client.get()).call(Request::Getinfo(GetinfoRequest {})).await?;
-            Ok(serde_json::to_value(&response)?)
-        }
-    }
-
-    pub async fn create_invoice(&self, amount_msat: u64, label: &str, description: &str) -> Result<Response> {
-        let client = self.rpc_client.as_ref().unwrap();
-        unsafe {
-            Ok((*client.get()).call(Request::Invoice(InvoiceRequest {
-                amount_msat: AmountOrAny::Amount(Amount::from_msat(amount_msat)),
-                label: label.to_string(),
-                description: description.to_string(),
-                expiry: None,
-                fallbacks: None,
-                preimage: None,
-                cltv: None,
-                deschashonly: None,
-                exposeprivatechannels: None,
-            })).await?)
-        }
-    }
-
-    pub async fn open_channel(&self, peer_id: &str, amount_sat: u64) -> Result<Response> {
-        let client = self.rpc_client.as_ref().unwrap();
-        let pubkey = PublicKey::from_str(peer_id)?;
-        
-        unsafe {
-            Ok((*client.get()).call(Request::FundChannel(FundchannelRequest {
-                id: pubkey,
-                amount: AmountOrAll::Amount(Amount::from_sat(amount_sat)),
-                push_msat: None,
-                feerate: None,
-                announce: None,
-                minconf: None,
-                close_to: None,
-                request_amt: None,
-                compact_lease: None,
-                utxos: None,
-                mindepth: None,
-                reserve: None,
-                channel_type: None,
-            })).await?)
-        }
-    }
-
-    pub async fn connect_peer(&self, node_id: &str, host: &str, port: u16) -> Result<Response> {
-        let client = self.rpc_client.as_ref().unwrap();
-        let pubkey = PublicKey::from_str(node_id)?;
-        unsafe {
-            Ok((*client.get()).call(Request::Connect(ConnectRequest {
-                id: pubkey.to_string(),
-                host: Some(host.to_string()),
-                port: Some(port),
-            })).await?)
-        }
-    }
-
-    pub async fn get_new_address(&self) -> Result<String> {
-        let client = self.rpc_client.as_ref().unwrap();
-        unsafe {
-            let response = (*client.get()).call(Request::NewAddr(NewaddrRequest {
-                addresstype: None
-            })).await?;
-            
-            println!("NewAddr response: {:?}", response);
-            
-            match response {
-                Response::NewAddr(addr_response) => {
-                    addr_response.bech32
-                        .ok_or_else(|| anyhow::anyhow!("No bech32 address in response"))
-                },
-                _ => Err(anyhow::anyhow!("Unexpected response type"))
-            }
-        }
-    }
-
-    pub async fn list_funds(&self) -> Result<Value> {
-        let client = self.rpc_client.as_ref().unwrap();
-        unsafe {
-            let response = (*client.get()).call(Request::ListFunds(ListfundsRequest {
-                spent: None
-            })).await?;
-            Ok(serde_json::to_value(&response)?)
-        }
-    }
-}
-
-// Implémentation de Send et Sync pour LightningNode
-unsafe impl Send for LightningNode {}
-unsafe impl Sync for LightningNode {} 
\ No newline at end of file
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use cln_rpc::{
+    Response,
+    Request,
+    RpcError,
+    model::requests::{
+        InvoiceRequest, GetinfoRequest, ConnectRequest, FundchannelRequest, NewaddrRequest, ListfundsRequest,
+        ListchannelsRequest, ListnodesRequest, StopRequest, DecodepayRequest, PayRequest, KeysendRequest,
+        ListpaysRequest, ListinvoicesRequest, WaitinvoiceRequest, CloseRequest, ListclosedchannelsRequest,
+        ListpeerchannelsRequest,
+    },
+    model::responses::{
+        GetinfoResponse, ListfundsResponse, NewaddrResponse, ListchannelsResponse, ListnodesResponse,
+        InvoiceResponse, FundchannelResponse, ConnectResponse, DecodepayResponse, PayResponse, KeysendResponse,
+        ListpaysResponse, ListinvoicesResponse, WaitinvoiceResponse, CloseResponse, CloseType as ClnCloseType,
+        ListclosedchannelsResponse, ListpeerchannelsResponse,
+    },
+    primitives::{Amount, AmountOrAny, AmountOrAll, PublicKey},
+};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::backend::LightningBackend;
+use crate::bitcoin::Network;
+use super::rpc::RpcPool;
+use super::types::{
+    ChannelClosing, ChannelEntry, ChannelOpening, ClosedChannel, CloseType, DecodedInvoice, Funds, GossipChannel,
+    GossipNode, Invoice, InvoiceEntry, NodeInfo, Payment, PaymentStatus,
+};
+
+/// Nombre maximal de connexions RPC ouvertes en parallèle vers un même nœud.
+const MAX_RPC_CONNECTIONS: usize = 4;
+
+/// Codes `PAY_*` de lightningd : le paiement a échoué, pas le nœud. `PAY_IN_PROGRESS`
+/// (200) en est exclu : des HTLC peuvent encore aboutir.
+const PAY_ERROR_CODES: std::ops::RangeInclusive<i32> = 201..=219;
+
+/// Durée pendant laquelle `pay` essaie d'autres routes avant d'abandonner, en secondes.
+const PAY_RETRY_FOR_SECS: u16 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LightningConfig {
+    pub network: Network,
+    pub lightning_dir: String,
+    pub bitcoin_rpc_host: String,
+    pub bitcoin_rpc_port: u16,
+    pub bitcoin_rpc_user: String,
+    pub bitcoin_rpc_password: String,
+}
+
+/// Nœud Core Lightning piloté via son socket RPC.
+///
+/// Le clone est peu coûteux et partage le même pool de connexions, ce qui
+/// permet d'appeler le nœud depuis plusieurs tâches tokio.
+#[derive(Clone)]
+pub struct LightningNode {
+    pub id: String,
+    rpc_client: Option<Arc<RpcPool>>,
+    config: LightningConfig,
+}
+
+impl LightningNode {
+    pub fn new(config: LightningConfig, id: String) -> Self {
+        Self {
+            id,
+            rpc_client: None,
+            config,
+        }
+    }
+
+    pub async fn connect_rpc(&mut self) -> Result<()> {
+        let socket_path = PathBuf::from(&self.config.lightning_dir)
+            .join(self.config.network.lightning_dir_name())
+            .join("lightning-rpc");
+        self.rpc_client = Some(Arc::new(RpcPool::connect(socket_path, MAX_RPC_CONNECTIONS).await?));
+        Ok(())
+    }
+
+    async fn call(&self, request: Request) -> Result<Response> {
+        let client = self.rpc_client.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Lightning node {} is not connected, call connect_rpc() first", self.id)
+        })?;
+        client.call(request).await
+    }
+
+    /// Demande à lightningd de s'arrêter (`stop`).
+    pub async fn stop(&self) -> Result<()> {
+        self.call(Request::Stop(StopRequest {})).await.map(|_| ())
+    }
+
+    /// Appelle le nœud et convertit la réponse dans le type attendu.
+    async fn call_typed<R: TryFrom<Response>>(&self, request: Request) -> Result<R> {
+        let response = self.call(request).await?;
+        R::try_from(response).map_err(|_| anyhow::anyhow!("Unexpected response type"))
+    }
+
+    async fn decodepay(&self, bolt11: &str) -> Result<DecodepayResponse> {
+        self.call_typed(Request::DecodePay(DecodepayRequest {
+            bolt11: bolt11.to_string(),
+            description: None,
+        })).await
+    }
+}
+
+/// Raison de l'échec si `error` est une erreur `PAY_*` de lightningd, avec le
+/// code d'échec et le canal fautif quand ils sont connus.
+fn payment_failure(error: &anyhow::Error) -> Option<String> {
+    let rpc = error.downcast_ref::<RpcError>()?;
+    if !rpc.code.is_some_and(|code| PAY_ERROR_CODES.contains(&code)) {
+        return None;
+    }
+    let data = rpc.data.as_ref();
+    let failcode = data.and_then(|d| d.get("failcodename")).and_then(|v| v.as_str());
+    let channel = data.and_then(|d| d.get("erring_channel")).and_then(|v| v.as_str());
+    Some(match (failcode, channel) {
+        (Some(failcode), Some(channel)) => format!("{} ({} sur {})", rpc.message, failcode, channel),
+        (Some(failcode), None) => format!("{} ({})", rpc.message, failcode),
+        _ => rpc.message.clone(),
+    })
+}
+
+/// `payment_hash` rapporté par lightningd dans les données d'une erreur `PAY_*`.
+fn failed_payment_hash(error: &anyhow::Error) -> Option<String> {
+    let data = error.downcast_ref::<RpcError>()?.data.as_ref()?;
+    data.get("payment_hash").and_then(|v| v.as_str()).map(str::to_string)
+}
+
+#[async_trait]
+impl LightningBackend for LightningNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn get_node_info(&self) -> Result<NodeInfo> {
+        let info: GetinfoResponse = self.call_typed(Request::Getinfo(GetinfoRequest {})).await?;
+        Ok(info.into())
+    }
+
+    async fn create_invoice(&self, amount_msat: u64, label: &str, description: &str) -> Result<Invoice> {
+        let invoice: InvoiceResponse = self.call_typed(Request::Invoice(InvoiceRequest {
+            amount_msat: AmountOrAny::Amount(Amount::from_msat(amount_msat)),
+            label: label.to_string(),
+            description: description.to_string(),
+            expiry: None,
+            fallbacks: None,
+            preimage: None,
+            cltv: None,
+            deschashonly: None,
+            exposeprivatechannels: None,
+        })).await?;
+
+        Ok(Invoice {
+            bolt11: invoice.bolt11,
+            payment_hash: invoice.payment_hash.to_string(),
+            expires_at: Some(invoice.expires_at),
+        })
+    }
+
+    async fn open_channel(&self, peer_id: &str, amount_sat: u64, push_msat: u64) -> Result<ChannelOpening> {
+        let pubkey = PublicKey::from_str(peer_id)?;
+
+        let opening: FundchannelResponse = self.call_typed(Request::FundChannel(FundchannelRequest {
+            id: pubkey,
+            amount: AmountOrAll::Amount(Amount::from_sat(amount_sat)),
+            push_msat: (push_msat > 0).then(|| Amount::from_msat(push_msat)),
+            feerate: None,
+            announce: None,
+            minconf: None,
+            close_to: None,
+            request_amt: None,
+            compact_lease: None,
+            utxos: None,
+            mindepth: None,
+            reserve: None,
+            channel_type: None,
+        })).await?;
+
+        Ok(ChannelOpening {
+            txid: opening.txid,
+            outnum: opening.outnum,
+            channel_id: Some(opening.channel_id.to_string()),
+        })
+    }
+
+    async fn close_channel(&self, channel: &ChannelEntry, unilateral_timeout: Option<Duration>) -> Result<ChannelClosing> {
+        let id = channel
+            .channel_id
+            .clone()
+            .or_else(|| channel.short_channel_id.clone())
+            .unwrap_or_else(|| channel.peer_id.clone());
+        // Pour lightningd, 0 signifie « attendre le pair indéfiniment »
+        let unilateraltimeout = match unilateral_timeout {
+            None => 0,
+            Some(timeout) => timeout.as_secs().clamp(1, u32::MAX as u64) as u32,
+        };
+
+        let closing: CloseResponse = self.call_typed(Request::Close(CloseRequest {
+            id,
+            unilateraltimeout: Some(unilateraltimeout),
+            destination: None,
+            fee_negotiation_step: None,
+            force_lease_closed: None,
+            wrong_funding: None,
+            feerange: None,
+        })).await?;
+
+        Ok(ChannelClosing {
+            close_type: match closing.item_type {
+                ClnCloseType::MUTUAL => CloseType::Mutual,
+                ClnCloseType::UNILATERAL => CloseType::Unilateral,
+                ClnCloseType::UNOPENED => CloseType::Unopened,
+            },
+            txid: closing.txid,
+        })
+    }
+
+    async fn list_closed_channels(&self) -> Result<Vec<ClosedChannel>> {
+        let response: ListclosedchannelsResponse = self.call_typed(Request::ListClosedChannels(ListclosedchannelsRequest {
+            id: None,
+        })).await?;
+        Ok(response.closedchannels.into_iter().map(ClosedChannel::from).collect())
+    }
+
+    async fn pay(&self, bolt11: &str) -> Result<Payment> {
+        let invoice = self.decodepay(bolt11).await?;
+        let amount_msat = invoice
+            .amount_msat
+            .ok_or_else(|| anyhow::anyhow!("La facture ne précise pas de montant"))?
+            .msat();
+
+        // Le plugin pay essaie d'autres routes, découpe en MPP et écarte les canaux défaillants
+        let result: Result<PayResponse> = self.call_typed(Request::Pay(PayRequest {
+            bolt11: bolt11.to_string(),
+            amount_msat: None,
+            description: None,
+            exemptfee: None,
+            label: None,
+            localinvreqid: None,
+            maxdelay: None,
+            maxfee: None,
+            maxfeepercent: None,
+            partial_msat: None,
+            retry_for: Some(PAY_RETRY_FOR_SECS),
+            riskfactor: None,
+            exclude: None,
+        })).await;
+
+        match result {
+            Ok(sent) => Ok(Payment {
+                payment_hash: sent.payment_hash.to_string(),
+                payment_preimage: Some(hex::encode(sent.payment_preimage.to_vec())),
+                destination: Some(sent.destination.unwrap_or(invoice.payee).to_string()),
+                amount_msat: sent.amount_msat.msat(),
+                amount_sent_msat: sent.amount_sent_msat.msat(),
+                status: PaymentStatus::Complete,
+                // Ni pay ni listsendpays ne donnent les nœuds intermédiaires
+                route: None,
+                failure_reason: None,
+                created_at: Some(sent.created_at as u64),
+            }),
+            Err(e) => match payment_failure(&e) {
+                Some(reason) => Ok(Payment::failed(
+                    &invoice.payment_hash.to_string(),
+                    Some(invoice.payee.to_string()),
+                    amount_msat,
+                    reason,
+                )),
+                None => Err(e),
+            },
+        }
+    }
+
+    async fn keysend(&self, destination: &str, amount_msat: u64) -> Result<Payment> {
+        let result: Result<KeysendResponse> = self.call_typed(Request::KeySend(KeysendRequest {
+            destination: PublicKey::from_str(destination)?,
+            amount_msat: Amount::from_msat(amount_msat),
+            exemptfee: None,
+            extratlvs: None,
+            label: None,
+            maxdelay: None,
+            maxfeepercent: None,
+            retry_for: None,
+            routehints: None,
+        })).await;
+
+        match result {
+            Ok(sent) => Ok(Payment {
+                payment_hash: sent.payment_hash.to_string(),
+                payment_preimage: Some(hex::encode(sent.payment_preimage.to_vec())),
+                destination: Some(destination.to_string()),
+                amount_msat: sent.amount_msat.msat(),
+                amount_sent_msat: sent.amount_sent_msat.msat(),
+                status: PaymentStatus::Complete,
+                // keysend passe par le plugin pay, qui ne renvoie pas la route
+                route: None,
+                failure_reason: None,
+                created_at: Some(sent.created_at as u64),
+            }),
+            // Sans le hash de la tentative, l'échec ne peut pas être rattaché à un paiement
+            Err(e) => match (payment_failure(&e), failed_payment_hash(&e)) {
+                (Some(reason), Some(payment_hash)) => {
+                    Ok(Payment::failed(&payment_hash, Some(destination.to_string()), amount_msat, reason))
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    async fn decode_invoice(&self, bolt11: &str) -> Result<DecodedInvoice> {
+        Ok(self.decodepay(bolt11).await?.into())
+    }
+
+    async fn list_payments(&self) -> Result<Vec<Payment>> {
+        let response: ListpaysResponse = self.call_typed(Request::ListPays(ListpaysRequest {
+            bolt11: None,
+            payment_hash: None,
+            status: None,
+        })).await?;
+        Ok(response.pays.into_iter().map(Payment::from).collect())
+    }
+
+    async fn list_invoices(&self) -> Result<Vec<InvoiceEntry>> {
+        let response: ListinvoicesResponse = self.call_typed(Request::ListInvoices(ListinvoicesRequest {
+            index: None,
+            invstring: None,
+            label: None,
+            limit: None,
+            offer_id: None,
+            payment_hash: None,
+            start: None,
+        })).await?;
+        Ok(response.invoices.into_iter().map(InvoiceEntry::from).collect())
+    }
+
+    async fn wait_invoice(&self, payment_hash: &str) -> Result<InvoiceEntry> {
+        // waitinvoice attend un label : on le retrouve à partir du hash
+        let response: ListinvoicesResponse = self.call_typed(Request::ListInvoices(ListinvoicesRequest {
+            payment_hash: Some(payment_hash.to_string()),
+            index: None,
+            invstring: None,
+            label: None,
+            limit: None,
+            offer_id: None,
+            start: None,
+        })).await?;
+        let invoice = response
+            .invoices
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Aucune facture de hash {} sur {}", payment_hash, self.id))?;
+
+        let waited: WaitinvoiceResponse = self.call_typed(Request::WaitInvoice(WaitinvoiceRequest {
+            label: invoice.label,
+        })).await?;
+        Ok(waited.into())
+    }
+
+    async fn connect_peer(&self, node_id: &str, host: &str, port: u16) -> Result<()> {
+        let pubkey = PublicKey::from_str(node_id)?;
+        let _: ConnectResponse = self.call_typed(Request::Connect(ConnectRequest {
+            id: pubkey.to_string(),
+            host: Some(host.to_string()),
+            port: Some(port),
+        })).await?;
+        Ok(())
+    }
+
+    async fn get_new_address(&self) -> Result<String> {
+        let response: NewaddrResponse = self.call_typed(Request::NewAddr(NewaddrRequest {
+            addresstype: None
+        })).await?;
+
+        response.bech32
+            .ok_or_else(|| anyhow::anyhow!("No bech32 address in response"))
+    }
+
+    async fn list_funds(&self) -> Result<Funds> {
+        let (funds, peer_channels) = tokio::try_join!(
+            self.call_typed::<ListfundsResponse>(Request::ListFunds(ListfundsRequest { spent: None })),
+            self.call_typed::<ListpeerchannelsResponse>(Request::ListPeerChannels(ListpeerchannelsRequest { id: None })),
+        )?;
+        let mut funds = Funds::from(funds);
+        funds.merge_peer_channels(&peer_channels.channels);
+        Ok(funds)
+    }
+
+    async fn list_channels(&self) -> Result<Vec<GossipChannel>> {
+        let response: ListchannelsResponse = self.call_typed(Request::ListChannels(ListchannelsRequest {
+            short_channel_id: None,
+            source: None,
+            destination: None,
+        })).await?;
+        Ok(response.channels.into_iter().map(GossipChannel::from).collect())
+    }
+
+    async fn list_nodes(&self) -> Result<Vec<GossipNode>> {
+        let response: ListnodesResponse = self.call_typed(Request::ListNodes(ListnodesRequest {
+            id: None,
+        })).await?;
+        Ok(response.nodes.into_iter().map(GossipNode::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc_error(code: i32, data: serde_json::Value) -> anyhow::Error {
+        RpcError { code: Some(code), message: "Ran out of routes to try".to_string(), data: Some(data) }.into()
+    }
+
+    #[tokio::test]
+    async fn unconnected_node_returns_an_error() {
+        let config = LightningConfig {
+            network: Network::Regtest,
+            lightning_dir: "/nonexistent".to_string(),
+            bitcoin_rpc_host: "127.0.0.1".to_string(),
+            bitcoin_rpc_port: 18443,
+            bitcoin_rpc_user: "user".to_string(),
+            bitcoin_rpc_password: "password".to_string(),
+        };
+        let node = LightningNode::new(config, "node1".to_string());
+        let message = node.get_node_info().await.unwrap_err().to_string();
+        assert!(message.contains("not connected"), "{}", message);
+    }
+
+    #[test]
+    fn pay_errors_carry_their_reason_and_hash() {
+        let error = rpc_error(
+            210,
+            serde_json::json!({ "payment_hash": "ab".repeat(32), "failcodename": "WIRE_TEMPORARY_CHANNEL_FAILURE" }),
+        );
+        assert_eq!(
+            payment_failure(&error).as_deref(),
+            Some("Ran out of routes to try (WIRE_TEMPORARY_CHANNEL_FAILURE)")
+        );
+        assert_eq!(failed_payment_hash(&error), Some("ab".repeat(32)));
+    }
+
+    #[test]
+    fn payment_in_progress_is_not_a_failure() {
+        assert_eq!(payment_failure(&rpc_error(200, serde_json::json!({}))), None);
+        assert_eq!(payment_failure(&anyhow::anyhow!("connection reset")), None);
+        assert_eq!(failed_payment_hash(&rpc_error(205, serde_json::json!({}))), None);
+    }
+}
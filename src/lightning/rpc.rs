@@ -0,0 +1,158 @@
+use anyhow::{Context, Result};
+use cln_rpc::{ClnRpc, Request, Response};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::sync::Semaphore;
+
+/// Petit pool de connexions au socket RPC de lightningd.
+///
+/// Chaque appel emprunte une connexion exclusive, ce qui permet à plusieurs
+/// tâches tokio d'interroger le même nœud en parallèle sans partager de
+/// `ClnRpc` mutable. Le nombre de connexions simultanées est borné par un sémaphore.
+pub struct RpcPool {
+    socket_path: PathBuf,
+    idle: Mutex<Vec<ClnRpc>>,
+    permits: Semaphore,
+}
+
+impl RpcPool {
+    /// Ouvre une première connexion pour vérifier que le socket répond.
+    pub async fn connect(socket_path: PathBuf, max_connections: usize) -> Result<Self> {
+        let client = ClnRpc::new(&socket_path)
+            .await
+            .with_context(|| format!("Impossible d'ouvrir le socket RPC {:?}", socket_path))?;
+
+        Ok(Self {
+            socket_path,
+            idle: Mutex::new(vec![client]),
+            permits: Semaphore::new(max_connections.max(1)),
+        })
+    }
+
+    pub async fn call(&self, request: Request) -> Result<Response> {
+        let _permit = self.permits.acquire().await?;
+
+        let idle = self.idle.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        let mut client = match idle {
+            Some(client) => client,
+            None => ClnRpc::new(&self.socket_path)
+                .await
+                .with_context(|| format!("Impossible d'ouvrir le socket RPC {:?}", self.socket_path))?,
+        };
+
+        let result = client.call(request).await;
+
+        // Une erreur sans code vient du transport : la connexion n'est pas réutilisée.
+        if result.as_ref().map_or_else(|e| e.code.is_some(), |_| true) {
+            self.idle.lock().unwrap_or_else(|e| e.into_inner()).push(client);
+        }
+
+        Ok(result?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cln_rpc::model::requests::{ListnodesRequest, ListpaysRequest, StopRequest};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{UnixListener, UnixStream};
+
+    /// Compteurs du faux lightningd : connexions acceptées, appels en cours et maximum observé.
+    #[derive(Default)]
+    struct Counters {
+        connections: AtomicUsize,
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+    }
+
+    /// Faux socket lightningd : `stop` répond après 50 ms, `listpays` renvoie
+    /// une erreur RPC et `listnodes` coupe la connexion sans répondre.
+    fn fake_lightningd(name: &str) -> (PathBuf, Arc<Counters>) {
+        let path = std::env::temp_dir().join(format!("lightning_viz_{}_{}.sock", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let counters = Arc::new(Counters::default());
+        let server_counters = counters.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                server_counters.connections.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(serve(stream, server_counters.clone()));
+            }
+        });
+        (path, counters)
+    }
+
+    async fn serve(mut stream: UnixStream, counters: Arc<Counters>) {
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") else {
+                match stream.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                }
+                continue;
+            };
+            let request: serde_json::Value = serde_json::from_slice(&buffer[..end]).unwrap();
+            buffer.drain(..end + 2);
+
+            let running = counters.running.fetch_add(1, Ordering::SeqCst) + 1;
+            counters.max_running.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            counters.running.fetch_sub(1, Ordering::SeqCst);
+
+            let response = match request["method"].as_str() {
+                Some("listpays") => serde_json::json!({
+                    "jsonrpc": "2.0", "id": request["id"], "error": { "code": -1, "message": "boom" }
+                }),
+                Some("listnodes") => return,
+                _ => serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": {} }),
+            };
+            stream.write_all(format!("{}\n\n", response).as_bytes()).await.unwrap();
+        }
+    }
+
+    fn stop() -> Request {
+        Request::Stop(StopRequest {})
+    }
+
+    #[tokio::test]
+    async fn concurrent_calls_are_bounded_by_the_pool_size() {
+        let (path, counters) = fake_lightningd("bounded");
+        let pool = Arc::new(RpcPool::connect(path.clone(), 2).await.unwrap());
+
+        let calls: Vec<_> = (0..6)
+            .map(|_| {
+                let pool = pool.clone();
+                tokio::spawn(async move { pool.call(stop()).await })
+            })
+            .collect();
+        for call in calls {
+            call.await.unwrap().unwrap();
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(counters.max_running.load(Ordering::SeqCst), 2);
+        assert_eq!(counters.connections.load(Ordering::SeqCst), 2, "idle connections are reused");
+    }
+
+    #[tokio::test]
+    async fn connections_are_returned_after_rpc_errors_and_replaced_after_transport_errors() {
+        let (path, counters) = fake_lightningd("errors");
+        let pool = RpcPool::connect(path.clone(), 1).await.unwrap();
+
+        let listpays = ListpaysRequest { bolt11: None, payment_hash: None, status: None };
+        assert!(pool.call(Request::ListPays(listpays)).await.is_err());
+        pool.call(stop()).await.unwrap();
+        assert_eq!(counters.connections.load(Ordering::SeqCst), 1, "an RPC error keeps the connection");
+
+        assert!(pool.call(Request::ListNodes(ListnodesRequest { id: None })).await.is_err());
+        pool.call(stop()).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(counters.connections.load(Ordering::SeqCst), 2, "a broken connection is replaced");
+    }
+}
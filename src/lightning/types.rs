@@ -0,0 +1,880 @@
+use cln_rpc::model::responses::{
+    DecodepayResponse, GetinfoResponse, ListchannelsChannels, ListclosedchannelsClosedchannels, ListfundsChannels, ListfundsOutputs,
+    ListfundsOutputsStatus, ListfundsResponse, ListinvoicesInvoices, ListinvoicesInvoicesStatus, ListnodesNodes,
+    ListpaysPays, ListpeerchannelsChannels, ListpaysPaysStatus, WaitinvoiceResponse, WaitinvoiceStatus,
+};
+use anyhow::Result;
+use cln_rpc::model::responses::ListclosedchannelsClosedchannelsClose_cause as ClnCloseCause;
+use cln_rpc::primitives::ChannelState as ClnChannelState;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Informations générales d'un nœud (`getinfo`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeInfo {
+    pub id: String,
+    pub alias: Option<String>,
+    pub color: String,
+    pub network: String,
+    pub version: String,
+    pub blockheight: u32,
+    pub num_peers: u32,
+    pub num_active_channels: u32,
+    pub num_pending_channels: u32,
+    pub num_inactive_channels: u32,
+    pub fees_collected_msat: u64,
+    /// Adresses annoncées, au format `hôte:port`.
+    pub addresses: Vec<String>,
+}
+
+impl NodeInfo {
+    /// Alias du nœud, ou début de sa clé publique s'il n'en a pas.
+    pub fn display_name(&self) -> String {
+        match &self.alias {
+            Some(alias) if !alias.is_empty() => alias.clone(),
+            _ => self.id.chars().take(8).collect(),
+        }
+    }
+}
+
+impl From<GetinfoResponse> for NodeInfo {
+    fn from(info: GetinfoResponse) -> Self {
+        Self {
+            id: info.id.to_string(),
+            alias: info.alias,
+            color: info.color,
+            network: info.network,
+            version: info.version,
+            blockheight: info.blockheight,
+            num_peers: info.num_peers,
+            num_active_channels: info.num_active_channels,
+            num_pending_channels: info.num_pending_channels,
+            num_inactive_channels: info.num_inactive_channels,
+            fees_collected_msat: info.fees_collected_msat.msat(),
+            addresses: info
+                .address
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|a| a.address.map(|host| format!("{}:{}", host, a.port)))
+                .collect(),
+        }
+    }
+}
+
+/// Fonds on-chain et en canaux d'un nœud (`listfunds`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Funds {
+    pub outputs: Vec<OutputEntry>,
+    pub channels: Vec<ChannelEntry>,
+}
+
+impl Funds {
+    /// Somme des sorties on-chain confirmées.
+    pub fn confirmed_onchain_msat(&self) -> u64 {
+        self.outputs
+            .iter()
+            .filter(|o| o.status == OutputStatus::Confirmed)
+            .map(|o| o.amount_msat)
+            .sum()
+    }
+
+    /// Somme de notre part dans tous les canaux.
+    pub fn channel_balance_msat(&self) -> u64 {
+        self.channels.iter().map(|c| c.our_amount_msat).sum()
+    }
+
+    /// Complète les canaux de `listfunds` par les soldes et réserves de
+    /// `listpeerchannels`, plus à jour, en les associant par transaction de financement.
+    pub fn merge_peer_channels(&mut self, peer_channels: &[ListpeerchannelsChannels]) {
+        for channel in &mut self.channels {
+            let Some(peer_channel) = peer_channels.iter().find(|c| {
+                c.funding_txid.as_deref() == Some(channel.funding_txid.as_str())
+                    && c.funding_outnum == Some(channel.funding_output)
+            }) else {
+                continue;
+            };
+            if let Some(to_us) = peer_channel.to_us_msat {
+                channel.our_amount_msat = to_us.msat();
+            }
+            channel.liquidity = ChannelLiquidity::from_peer_channel(peer_channel);
+        }
+    }
+}
+
+impl From<ListfundsResponse> for Funds {
+    fn from(funds: ListfundsResponse) -> Self {
+        Self {
+            outputs: funds.outputs.into_iter().map(OutputEntry::from).collect(),
+            channels: funds.channels.into_iter().map(ChannelEntry::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStatus {
+    Unconfirmed,
+    Confirmed,
+    Spent,
+    Immature,
+}
+
+impl From<ListfundsOutputsStatus> for OutputStatus {
+    fn from(status: ListfundsOutputsStatus) -> Self {
+        match status {
+            ListfundsOutputsStatus::UNCONFIRMED => OutputStatus::Unconfirmed,
+            ListfundsOutputsStatus::CONFIRMED => OutputStatus::Confirmed,
+            ListfundsOutputsStatus::SPENT => OutputStatus::Spent,
+            ListfundsOutputsStatus::IMMATURE => OutputStatus::Immature,
+        }
+    }
+}
+
+/// Sortie on-chain détenue par le portefeuille du nœud.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputEntry {
+    pub txid: String,
+    pub output: u32,
+    pub amount_msat: u64,
+    pub status: OutputStatus,
+    pub address: Option<String>,
+    pub blockheight: Option<u32>,
+    pub reserved: bool,
+}
+
+impl From<ListfundsOutputs> for OutputEntry {
+    fn from(output: ListfundsOutputs) -> Self {
+        Self {
+            txid: output.txid,
+            output: output.output,
+            amount_msat: output.amount_msat.msat(),
+            status: output.status.into(),
+            address: output.address,
+            blockheight: output.blockheight,
+            reserved: output.reserved,
+        }
+    }
+}
+
+/// Étape du cycle de vie d'un canal, selon les états de lightningd. Les
+/// autres backends s'y ramènent, et `Closed` désigne les canaux de
+/// `listclosedchannels` que le nœud ne suit plus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChannelState {
+    Openingd,
+    DualopendOpenInit,
+    DualopendAwaitingLockin,
+    ChanneldAwaitingLockin,
+    ChanneldNormal,
+    ChanneldAwaitingSplice,
+    ChanneldShuttingDown,
+    ClosingdSigexchange,
+    ClosingdComplete,
+    AwaitingUnilateral,
+    FundingSpendSeen,
+    Onchain,
+    Closed,
+}
+
+impl ChannelState {
+    /// Tous les états, dans l'ordre du cycle de vie d'un canal.
+    #[cfg(test)]
+    pub const ALL: [ChannelState; 13] = [
+        ChannelState::Openingd,
+        ChannelState::DualopendOpenInit,
+        ChannelState::DualopendAwaitingLockin,
+        ChannelState::ChanneldAwaitingLockin,
+        ChannelState::ChanneldNormal,
+        ChannelState::ChanneldAwaitingSplice,
+        ChannelState::ChanneldShuttingDown,
+        ChannelState::ClosingdSigexchange,
+        ChannelState::ClosingdComplete,
+        ChannelState::AwaitingUnilateral,
+        ChannelState::FundingSpendSeen,
+        ChannelState::Onchain,
+        ChannelState::Closed,
+    ];
+
+    /// Nom de l'état dans lightningd, ex. `CHANNELD_NORMAL`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChannelState::Openingd => "OPENINGD",
+            ChannelState::DualopendOpenInit => "DUALOPEND_OPEN_INIT",
+            ChannelState::DualopendAwaitingLockin => "DUALOPEND_AWAITING_LOCKIN",
+            ChannelState::ChanneldAwaitingLockin => "CHANNELD_AWAITING_LOCKIN",
+            ChannelState::ChanneldNormal => "CHANNELD_NORMAL",
+            ChannelState::ChanneldAwaitingSplice => "CHANNELD_AWAITING_SPLICE",
+            ChannelState::ChanneldShuttingDown => "CHANNELD_SHUTTING_DOWN",
+            ChannelState::ClosingdSigexchange => "CLOSINGD_SIGEXCHANGE",
+            ChannelState::ClosingdComplete => "CLOSINGD_COMPLETE",
+            ChannelState::AwaitingUnilateral => "AWAITING_UNILATERAL",
+            ChannelState::FundingSpendSeen => "FUNDING_SPEND_SEEN",
+            ChannelState::Onchain => "ONCHAIN",
+            ChannelState::Closed => "CLOSED",
+        }
+    }
+
+    /// Libellé court affiché dans les graphes.
+    pub fn label(self) -> &'static str {
+        match self {
+            ChannelState::Openingd | ChannelState::DualopendOpenInit => "ouverture",
+            ChannelState::DualopendAwaitingLockin | ChannelState::ChanneldAwaitingLockin => "attente de confirmation",
+            ChannelState::ChanneldNormal => "ouvert",
+            ChannelState::ChanneldAwaitingSplice => "splice en cours",
+            ChannelState::ChanneldShuttingDown => "fermeture demandée",
+            ChannelState::ClosingdSigexchange => "fermeture négociée",
+            ChannelState::ClosingdComplete => "fermeture publiée",
+            ChannelState::AwaitingUnilateral => "fermeture forcée",
+            ChannelState::FundingSpendSeen => "fermeture vue on-chain",
+            ChannelState::Onchain => "fermeture confirmée",
+            ChannelState::Closed => "fermé",
+        }
+    }
+
+    /// Canal pas encore utilisable, en attente de son financement.
+    pub fn is_opening(self) -> bool {
+        matches!(
+            self,
+            ChannelState::Openingd
+                | ChannelState::DualopendOpenInit
+                | ChannelState::DualopendAwaitingLockin
+                | ChannelState::ChanneldAwaitingLockin
+        )
+    }
+
+    /// Canal capable de relayer des paiements (un splice ne l'interrompt pas).
+    pub fn is_normal(self) -> bool {
+        matches!(self, ChannelState::ChanneldNormal | ChannelState::ChanneldAwaitingSplice)
+    }
+
+    /// Fermeture engagée, le canal ne redeviendra jamais utilisable.
+    pub fn is_closing(self) -> bool {
+        matches!(
+            self,
+            ChannelState::ChanneldShuttingDown
+                | ChannelState::ClosingdSigexchange
+                | ChannelState::ClosingdComplete
+                | ChannelState::AwaitingUnilateral
+                | ChannelState::FundingSpendSeen
+                | ChannelState::Onchain
+        )
+    }
+}
+
+impl fmt::Display for ChannelState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<ClnChannelState> for ChannelState {
+    fn from(state: ClnChannelState) -> Self {
+        match state {
+            ClnChannelState::OPENINGD => ChannelState::Openingd,
+            ClnChannelState::CHANNELD_AWAITING_LOCKIN => ChannelState::ChanneldAwaitingLockin,
+            ClnChannelState::CHANNELD_NORMAL => ChannelState::ChanneldNormal,
+            ClnChannelState::CHANNELD_SHUTTING_DOWN => ChannelState::ChanneldShuttingDown,
+            ClnChannelState::CLOSINGD_SIGEXCHANGE => ChannelState::ClosingdSigexchange,
+            ClnChannelState::CLOSINGD_COMPLETE => ChannelState::ClosingdComplete,
+            ClnChannelState::AWAITING_UNILATERAL => ChannelState::AwaitingUnilateral,
+            ClnChannelState::FUNDING_SPEND_SEEN => ChannelState::FundingSpendSeen,
+            ClnChannelState::ONCHAIN => ChannelState::Onchain,
+            ClnChannelState::DUALOPEND_OPEN_INIT => ChannelState::DualopendOpenInit,
+            ClnChannelState::DUALOPEND_AWAITING_LOCKIN => ChannelState::DualopendAwaitingLockin,
+            ClnChannelState::CHANNELD_AWAITING_SPLICE => ChannelState::ChanneldAwaitingSplice,
+        }
+    }
+}
+
+/// Canal vu depuis le nœud local.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelEntry {
+    pub peer_id: String,
+    pub short_channel_id: Option<String>,
+    pub channel_id: Option<String>,
+    pub funding_txid: String,
+    pub funding_output: u32,
+    pub state: ChannelState,
+    pub connected: bool,
+    /// Capacité totale du canal.
+    pub amount_msat: u64,
+    /// Part du nœud local dans le canal.
+    pub our_amount_msat: u64,
+    /// Réserves et montants transférables, quand le backend les fournit.
+    #[serde(default)]
+    pub liquidity: Option<ChannelLiquidity>,
+}
+
+impl ChannelEntry {
+    /// Part du pair, frais d'engagement et HTLC en cours compris.
+    pub fn their_amount_msat(&self) -> u64 {
+        self.amount_msat.saturating_sub(self.our_amount_msat)
+    }
+}
+
+impl From<ListfundsChannels> for ChannelEntry {
+    fn from(channel: ListfundsChannels) -> Self {
+        Self {
+            peer_id: channel.peer_id.to_string(),
+            short_channel_id: channel.short_channel_id.map(|scid| scid.to_string()),
+            channel_id: channel.channel_id.map(|id| id.to_string()),
+            funding_txid: channel.funding_txid,
+            funding_output: channel.funding_output,
+            state: channel.state.into(),
+            connected: channel.connected,
+            amount_msat: channel.amount_msat.msat(),
+            our_amount_msat: channel.our_amount_msat.msat(),
+            liquidity: None,
+        }
+    }
+}
+
+/// Liquidité d'un canal du point de vue du nœud local.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelLiquidity {
+    /// Solde que le nœud local doit conserver dans le canal.
+    pub our_reserve_msat: u64,
+    /// Solde que le pair doit conserver dans le canal.
+    pub their_reserve_msat: u64,
+    /// Montant que le nœud local peut envoyer par ce canal.
+    pub spendable_msat: u64,
+    /// Montant que le nœud local peut recevoir par ce canal.
+    pub receivable_msat: u64,
+}
+
+impl ChannelLiquidity {
+    /// Liquidité d'un canal de `listpeerchannels`, absente tant que lightningd
+    /// ne la calcule pas (canal en ouverture ou fermé).
+    fn from_peer_channel(channel: &ListpeerchannelsChannels) -> Option<Self> {
+        Some(Self {
+            our_reserve_msat: channel.our_reserve_msat?.msat(),
+            their_reserve_msat: channel.their_reserve_msat?.msat(),
+            spendable_msat: channel.spendable_msat?.msat(),
+            receivable_msat: channel.receivable_msat?.msat(),
+        })
+    }
+}
+
+/// Nœud tel qu'annoncé dans le gossip (`listnodes`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GossipNode {
+    pub id: String,
+    pub alias: Option<String>,
+    pub color: Option<String>,
+    /// Adresses annoncées, au format `hôte:port`.
+    pub addresses: Vec<String>,
+    pub last_timestamp: Option<u32>,
+}
+
+impl From<ListnodesNodes> for GossipNode {
+    fn from(node: ListnodesNodes) -> Self {
+        Self {
+            id: node.nodeid.to_string(),
+            alias: node.alias,
+            color: node.color,
+            addresses: node
+                .addresses
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|a| a.address.map(|host| format!("{}:{}", host, a.port)))
+                .collect(),
+            last_timestamp: node.last_timestamp,
+        }
+    }
+}
+
+/// Une direction d'un canal annoncé (`listchannels`) : la politique de
+/// `source` pour relayer des paiements vers `destination`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GossipChannel {
+    pub source: String,
+    pub destination: String,
+    pub short_channel_id: String,
+    pub amount_msat: u64,
+    pub active: bool,
+    pub public: bool,
+    pub base_fee_msat: u32,
+    pub fee_ppm: u32,
+    pub cltv_delta: u32,
+    pub htlc_minimum_msat: u64,
+    pub htlc_maximum_msat: Option<u64>,
+    pub last_update: u32,
+}
+
+impl From<ListchannelsChannels> for GossipChannel {
+    fn from(channel: ListchannelsChannels) -> Self {
+        Self {
+            source: channel.source.to_string(),
+            destination: channel.destination.to_string(),
+            short_channel_id: channel.short_channel_id.to_string(),
+            amount_msat: channel.amount_msat.msat(),
+            active: channel.active,
+            public: channel.public,
+            base_fee_msat: channel.base_fee_millisatoshi,
+            fee_ppm: channel.fee_per_millionth,
+            cltv_delta: channel.delay,
+            htlc_minimum_msat: channel.htlc_minimum_msat.msat(),
+            htlc_maximum_msat: channel.htlc_maximum_msat.map(|a| a.msat()),
+            last_update: channel.last_update,
+        }
+    }
+}
+
+/// Facture BOLT11 créée par un nœud.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Invoice {
+    pub bolt11: String,
+    pub payment_hash: String,
+    /// Expiration en secondes depuis l'époque Unix, si le backend la fournit.
+    pub expires_at: Option<u64>,
+}
+
+/// Transaction de financement publiée à l'ouverture d'un canal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelOpening {
+    pub txid: String,
+    pub outnum: u32,
+    pub channel_id: Option<String>,
+}
+
+/// Manière dont un canal a été fermé (`close`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseType {
+    /// Accord des deux pairs sur une transaction de fermeture.
+    Mutual,
+    /// Publication de notre transaction d'engagement, sans le pair.
+    Unilateral,
+    /// Canal jamais confirmé : rien n'est publié.
+    Unopened,
+}
+
+impl fmt::Display for CloseType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CloseType::Mutual => "mutual",
+            CloseType::Unilateral => "unilateral",
+            CloseType::Unopened => "unopened",
+        })
+    }
+}
+
+/// Résultat d'une demande de fermeture.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelClosing {
+    pub close_type: CloseType,
+    /// Transaction de fermeture publiée, absente pour un canal jamais ouvert.
+    pub txid: Option<String>,
+}
+
+/// Canal fermé dont le nœud a fini de traiter les sorties on-chain
+/// (`listclosedchannels`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClosedChannel {
+    pub peer_id: Option<String>,
+    pub short_channel_id: Option<String>,
+    pub funding_txid: String,
+    pub funding_output: u32,
+    pub amount_msat: u64,
+    /// Part qui nous est revenue à la fermeture.
+    pub final_to_us_msat: u64,
+    /// Origine de la fermeture, ex. `user` ou `remote` (CLN), `cooperative_close` (LND).
+    pub close_cause: String,
+    /// Transaction de fermeture, si le backend la communique.
+    pub closing_txid: Option<String>,
+}
+
+/// Nom de l'origine d'une fermeture dans lightningd, stable d'une version de cln-rpc à l'autre.
+fn close_cause_name(cause: ClnCloseCause) -> &'static str {
+    match cause {
+        ClnCloseCause::UNKNOWN => "unknown",
+        ClnCloseCause::LOCAL => "local",
+        ClnCloseCause::USER => "user",
+        ClnCloseCause::REMOTE => "remote",
+        ClnCloseCause::PROTOCOL => "protocol",
+        ClnCloseCause::ONCHAIN => "onchain",
+    }
+}
+
+impl From<ListclosedchannelsClosedchannels> for ClosedChannel {
+    fn from(channel: ListclosedchannelsClosedchannels) -> Self {
+        Self {
+            peer_id: channel.peer_id.map(|id| id.to_string()),
+            short_channel_id: channel.short_channel_id.map(|scid| scid.to_string()),
+            funding_txid: channel.funding_txid,
+            funding_output: channel.funding_outnum,
+            amount_msat: channel.total_msat.msat(),
+            final_to_us_msat: channel.final_to_us_msat.msat(),
+            close_cause: close_cause_name(channel.close_cause).to_string(),
+            closing_txid: None,
+        }
+    }
+}
+
+/// Issue d'un paiement émis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentStatus {
+    Pending,
+    Complete,
+    Failed,
+}
+
+impl fmt::Display for PaymentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PaymentStatus::Pending => "pending",
+            PaymentStatus::Complete => "complete",
+            PaymentStatus::Failed => "failed",
+        })
+    }
+}
+
+/// Saut d'une route de paiement, du premier pair jusqu'au destinataire.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteHop {
+    /// Nœud atteint par ce saut.
+    pub node_id: String,
+    pub short_channel_id: Option<String>,
+    /// Montant transmis à `node_id`, frais des sauts suivants compris.
+    pub amount_msat: u64,
+    /// Délai CLTV restant à l'arrivée sur `node_id`.
+    pub delay: Option<u32>,
+}
+
+/// Résultat d'un paiement.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Payment {
+    pub payment_hash: String,
+    pub payment_preimage: Option<String>,
+    pub destination: Option<String>,
+    /// Montant reçu par le destinataire.
+    pub amount_msat: u64,
+    /// Montant envoyé, frais de routage compris.
+    pub amount_sent_msat: u64,
+    pub status: PaymentStatus,
+    /// Sauts empruntés ; `None` si le backend ne les communique pas, ce qui
+    /// ne dit rien du nombre de sauts.
+    pub route: Option<Vec<RouteHop>>,
+    /// Raison de l'échec quand `status` vaut `Failed` et que le backend la connaît.
+    pub failure_reason: Option<String>,
+    /// Date de création, en secondes depuis l'époque Unix.
+    pub created_at: Option<u64>,
+}
+
+impl Payment {
+    /// Paiement échoué, sans route connue (aucune route, solde insuffisant...).
+    pub fn failed(payment_hash: &str, destination: Option<String>, amount_msat: u64, reason: String) -> Self {
+        Self {
+            payment_hash: payment_hash.to_string(),
+            payment_preimage: None,
+            destination,
+            amount_msat,
+            amount_sent_msat: 0,
+            status: PaymentStatus::Failed,
+            route: None,
+            failure_reason: Some(reason),
+            created_at: None,
+        }
+    }
+
+    /// Frais de routage payés.
+    pub fn fee_msat(&self) -> u64 {
+        self.amount_sent_msat.saturating_sub(self.amount_msat)
+    }
+
+    /// Nombre de sauts, ou « route inconnue » si le backend ne la communique pas.
+    pub fn route_summary(&self) -> String {
+        match &self.route {
+            Some(route) => format!("{} saut(s)", route.len()),
+            None => "route inconnue".to_string(),
+        }
+    }
+
+    /// Convertit un paiement non abouti en erreur portant sa raison d'échec.
+    pub fn ensure_complete(self) -> Result<Self> {
+        if self.status == PaymentStatus::Complete {
+            return Ok(self);
+        }
+        Err(anyhow::anyhow!(
+            "Paiement {} {} : {}",
+            self.payment_hash,
+            self.status,
+            self.failure_reason.as_deref().unwrap_or("raison inconnue")
+        ))
+    }
+}
+
+impl From<ListpaysPays> for Payment {
+    fn from(pay: ListpaysPays) -> Self {
+        let status = match pay.status {
+            ListpaysPaysStatus::PENDING => PaymentStatus::Pending,
+            ListpaysPaysStatus::FAILED => PaymentStatus::Failed,
+            ListpaysPaysStatus::COMPLETE => PaymentStatus::Complete,
+        };
+        Self {
+            payment_hash: pay.payment_hash.to_string(),
+            payment_preimage: pay.preimage.map(|p| hex::encode(p.to_vec())),
+            destination: pay.destination.map(|d| d.to_string()),
+            amount_msat: pay.amount_msat.map_or(0, |a| a.msat()),
+            amount_sent_msat: pay.amount_sent_msat.map_or(0, |a| a.msat()),
+            status,
+            // listpays ne détaille ni la route ni la cause d'un échec
+            route: None,
+            failure_reason: None,
+            created_at: Some(pay.created_at),
+        }
+    }
+}
+
+/// Contenu d'une facture BOLT11 décodée.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecodedInvoice {
+    pub payee: String,
+    pub payment_hash: String,
+    /// Absent pour une facture « any amount ».
+    pub amount_msat: Option<u64>,
+    pub description: Option<String>,
+    /// Date de création, en secondes depuis l'époque Unix.
+    pub created_at: u64,
+    /// Durée de validité en secondes à partir de `created_at`.
+    pub expiry: u64,
+    pub min_final_cltv_expiry: u32,
+}
+
+impl From<DecodepayResponse> for DecodedInvoice {
+    fn from(invoice: DecodepayResponse) -> Self {
+        Self {
+            payee: invoice.payee.to_string(),
+            payment_hash: invoice.payment_hash.to_string(),
+            amount_msat: invoice.amount_msat.map(|a| a.msat()),
+            description: invoice.description,
+            created_at: invoice.created_at,
+            expiry: invoice.expiry,
+            min_final_cltv_expiry: invoice.min_final_cltv_expiry,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvoiceStatus {
+    Unpaid,
+    Paid,
+    /// Expirée, ou annulée côté LND.
+    Expired,
+}
+
+impl fmt::Display for InvoiceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InvoiceStatus::Unpaid => "unpaid",
+            InvoiceStatus::Paid => "paid",
+            InvoiceStatus::Expired => "expired",
+        })
+    }
+}
+
+/// Facture émise par le nœud, avec son état de paiement (`listinvoices`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvoiceEntry {
+    /// Label CLN ; LND n'en a pas.
+    pub label: Option<String>,
+    pub bolt11: Option<String>,
+    pub payment_hash: String,
+    pub amount_msat: Option<u64>,
+    pub amount_received_msat: Option<u64>,
+    pub status: InvoiceStatus,
+    pub description: Option<String>,
+    /// Date de paiement, en secondes depuis l'époque Unix.
+    pub paid_at: Option<u64>,
+    pub expires_at: Option<u64>,
+}
+
+impl From<ListinvoicesInvoices> for InvoiceEntry {
+    fn from(invoice: ListinvoicesInvoices) -> Self {
+        Self {
+            label: Some(invoice.label),
+            bolt11: invoice.bolt11,
+            payment_hash: invoice.payment_hash.to_string(),
+            amount_msat: invoice.amount_msat.map(|a| a.msat()),
+            amount_received_msat: invoice.amount_received_msat.map(|a| a.msat()),
+            status: match invoice.status {
+                ListinvoicesInvoicesStatus::UNPAID => InvoiceStatus::Unpaid,
+                ListinvoicesInvoicesStatus::PAID => InvoiceStatus::Paid,
+                ListinvoicesInvoicesStatus::EXPIRED => InvoiceStatus::Expired,
+            },
+            description: invoice.description,
+            paid_at: invoice.paid_at,
+            expires_at: Some(invoice.expires_at),
+        }
+    }
+}
+
+impl From<WaitinvoiceResponse> for InvoiceEntry {
+    fn from(invoice: WaitinvoiceResponse) -> Self {
+        Self {
+            label: Some(invoice.label),
+            bolt11: invoice.bolt11,
+            payment_hash: invoice.payment_hash.to_string(),
+            amount_msat: invoice.amount_msat.map(|a| a.msat()),
+            amount_received_msat: invoice.amount_received_msat.map(|a| a.msat()),
+            status: match invoice.status {
+                WaitinvoiceStatus::PAID => InvoiceStatus::Paid,
+                WaitinvoiceStatus::EXPIRED => InvoiceStatus::Expired,
+            },
+            description: Some(invoice.description),
+            paid_at: invoice.paid_at,
+            expires_at: Some(invoice.expires_at),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Point générateur de secp256k1, une clé publique valide pour cln-rpc.
+    const PUBKEY: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+    #[test]
+    fn getinfo_becomes_node_info() {
+        let response: GetinfoResponse = serde_json::from_value(serde_json::json!({
+            "id": PUBKEY,
+            "alias": "alice",
+            "color": "3399ff",
+            "num_peers": 1,
+            "num_pending_channels": 0,
+            "num_active_channels": 2,
+            "num_inactive_channels": 1,
+            "address": [{ "type": "ipv4", "address": "10.0.0.1", "port": 9735 }],
+            "version": "v24.02",
+            "blockheight": 110,
+            "network": "regtest",
+            "fees_collected_msat": 1500,
+            "lightning-dir": "/tmp/node1/regtest",
+        }))
+        .unwrap();
+
+        let info = NodeInfo::from(response);
+        assert_eq!(info.id, PUBKEY);
+        assert_eq!(info.display_name(), "alice");
+        assert_eq!((info.num_active_channels, info.num_inactive_channels, info.blockheight), (2, 1, 110));
+        assert_eq!(info.fees_collected_msat, 1500);
+        assert_eq!(info.addresses, ["10.0.0.1:9735"]);
+    }
+
+    #[test]
+    fn listfunds_becomes_funds_in_msat() {
+        let response: ListfundsResponse = serde_json::from_value(serde_json::json!({
+            "outputs": [{
+                "txid": "ab".repeat(32),
+                "output": 1,
+                "amount_msat": 200_000_000,
+                "scriptpubkey": "0014",
+                "status": "confirmed",
+                "blockheight": 105,
+                "reserved": false,
+            }],
+            "channels": [{
+                "peer_id": PUBKEY,
+                "connected": true,
+                "state": "CHANNELD_NORMAL",
+                "short_channel_id": "103x1x0",
+                "our_amount_msat": 700_000_000,
+                "amount_msat": 1_000_000_000,
+                "funding_txid": "cd".repeat(32),
+                "funding_output": 0,
+            }],
+        }))
+        .unwrap();
+
+        let funds = Funds::from(response);
+        assert_eq!(funds.confirmed_onchain_msat(), 200_000_000);
+        assert_eq!(funds.outputs[0].status, OutputStatus::Confirmed);
+        let channel = &funds.channels[0];
+        assert_eq!(channel.peer_id, PUBKEY);
+        assert_eq!(channel.short_channel_id.as_deref(), Some("103x1x0"));
+        assert_eq!(channel.state, ChannelState::ChanneldNormal);
+        assert_eq!(channel.amount_msat, 1_000_000_000);
+        assert_eq!((channel.our_amount_msat, channel.their_amount_msat()), (700_000_000, 300_000_000));
+    }
+
+    #[test]
+    fn unknown_route_is_not_a_direct_payment() {
+        let mut payment = Payment::failed(&"ab".repeat(32), None, 1000, "Ran out of routes to try".to_string());
+        assert_eq!(payment.route_summary(), "route inconnue");
+        payment.route = Some(Vec::new());
+        assert_eq!(payment.route_summary(), "0 saut(s)");
+    }
+
+    #[test]
+    fn every_cln_state_keeps_its_lightningd_name() {
+        let cln_states = [
+            ClnChannelState::OPENINGD,
+            ClnChannelState::CHANNELD_AWAITING_LOCKIN,
+            ClnChannelState::CHANNELD_NORMAL,
+            ClnChannelState::CHANNELD_SHUTTING_DOWN,
+            ClnChannelState::CLOSINGD_SIGEXCHANGE,
+            ClnChannelState::CLOSINGD_COMPLETE,
+            ClnChannelState::AWAITING_UNILATERAL,
+            ClnChannelState::FUNDING_SPEND_SEEN,
+            ClnChannelState::ONCHAIN,
+            ClnChannelState::DUALOPEND_OPEN_INIT,
+            ClnChannelState::DUALOPEND_AWAITING_LOCKIN,
+            ClnChannelState::CHANNELD_AWAITING_SPLICE,
+        ];
+        for cln in cln_states {
+            let state = ChannelState::from(cln);
+            assert_eq!(serde_json::to_value(cln).unwrap(), state.as_str(), "{:?}", cln);
+            assert_eq!(serde_json::to_value(state).unwrap(), state.as_str(), "{:?}", cln);
+        }
+    }
+
+    #[test]
+    fn close_causes_use_lightningd_names() {
+        for cause in [
+            ClnCloseCause::UNKNOWN,
+            ClnCloseCause::LOCAL,
+            ClnCloseCause::USER,
+            ClnCloseCause::REMOTE,
+            ClnCloseCause::PROTOCOL,
+            ClnCloseCause::ONCHAIN,
+        ] {
+            assert_eq!(serde_json::to_value(cause).unwrap(), close_cause_name(cause));
+        }
+    }
+
+    #[test]
+    fn every_live_state_is_in_exactly_one_phase() {
+        use ChannelState::*;
+        let table = [
+            // état, ouverture, normal, fermeture, libellé
+            (Openingd, true, false, false, "ouverture"),
+            (DualopendOpenInit, true, false, false, "ouverture"),
+            (DualopendAwaitingLockin, true, false, false, "attente de confirmation"),
+            (ChanneldAwaitingLockin, true, false, false, "attente de confirmation"),
+            (ChanneldNormal, false, true, false, "ouvert"),
+            (ChanneldAwaitingSplice, false, true, false, "splice en cours"),
+            (ChanneldShuttingDown, false, false, true, "fermeture demandée"),
+            (ClosingdSigexchange, false, false, true, "fermeture négociée"),
+            (ClosingdComplete, false, false, true, "fermeture publiée"),
+            (AwaitingUnilateral, false, false, true, "fermeture forcée"),
+            (FundingSpendSeen, false, false, true, "fermeture vue on-chain"),
+            (Onchain, false, false, true, "fermeture confirmée"),
+            (Closed, false, false, false, "fermé"),
+        ];
+        assert_eq!(table.map(|row| row.0), ChannelState::ALL);
+        for (state, opening, normal, closing, label) in table {
+            assert_eq!(
+                (state.is_opening(), state.is_normal(), state.is_closing(), state.label()),
+                (opening, normal, closing, label),
+                "{}",
+                state
+            );
+            let phases = [opening, normal, closing].into_iter().filter(|in_phase| *in_phase).count();
+            assert_eq!(phases, usize::from(state != Closed), "{}: a closed channel is in no phase", state);
+        }
+    }
+}
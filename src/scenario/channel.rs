@@ -0,0 +1,231 @@
+use anyhow::{Context, Result};
+use std::ops::ControlFlow;
+use std::time::Duration;
+
+use super::wait::wait_for;
+use crate::bitcoin::{BitcoinBackend, TransactionStatus};
+use crate::lightning::{ChannelClosing, ChannelEntry, LightningBackend};
+
+/// Ouverture et fermeture d'un canal en regtest : chaque étape mine ses blocs vers
+/// `mining_address` puis attend que le nœud ait constaté le changement d'état,
+/// au lieu de laisser passer un délai arbitraire.
+pub struct ChannelWorkflow<'a> {
+    bitcoin: &'a dyn BitcoinBackend,
+    mining_address: String,
+    /// Blocs minés après la transaction d'ouverture ou de fermeture.
+    pub confirmations: u64,
+    /// Délai maximal d'attente de chaque étape.
+    pub timeout: Duration,
+}
+
+impl<'a> ChannelWorkflow<'a> {
+    pub async fn new(bitcoin: &'a dyn BitcoinBackend, confirmations: u64, timeout: Duration) -> Result<Self> {
+        bitcoin.network().require_mining("L'ouverture d'un canal avec confirmations")?;
+        bitcoin.ensure_wallet().await?;
+        let mining_address = bitcoin.get_new_address().await?;
+        Ok(Self { bitcoin, mining_address, confirmations, timeout })
+    }
+
+    /// Envoie `amount_btc` au portefeuille on-chain de `node`, mine un bloc et
+    /// attend que le nœud voie ces fonds confirmés.
+    pub async fn fund(&self, node: &dyn LightningBackend, amount_btc: f64) -> Result<String> {
+        let deposit = Deposit::send(self.bitcoin, node, amount_btc).await?;
+        self.bitcoin.generate_to_address(1, &self.mining_address).await?;
+        deposit.wait_confirmed(self.timeout).await?;
+        Ok(deposit.txid)
+    }
+
+    /// Ouvre un canal de `node` vers `peer_id` (déjà connecté), mine
+    /// `confirmations` blocs et attend que le canal passe en `CHANNELD_NORMAL`.
+    pub async fn open(
+        &self,
+        node: &dyn LightningBackend,
+        peer_id: &str,
+        amount_sat: u64,
+        push_msat: u64,
+    ) -> Result<ChannelEntry> {
+        let opening = node
+            .open_channel(peer_id, amount_sat, push_msat)
+            .await
+            .with_context(|| format!("Ouverture du canal de {} vers {} impossible", node.id(), peer_id))?;
+        self.bitcoin.generate_to_address(self.confirmations, &self.mining_address).await?;
+        wait_for_channel_normal(node, &opening.txid, self.timeout).await
+    }
+
+    /// Ferme `channel` (de force après `unilateral_timeout`, voir
+    /// `LightningBackend::close_channel`), mine `confirmations` blocs et attend
+    /// que bitcoind voie la transaction de fermeture confirmée.
+    pub async fn close(
+        &self,
+        node: &dyn LightningBackend,
+        channel: &ChannelEntry,
+        unilateral_timeout: Option<Duration>,
+    ) -> Result<(ChannelClosing, TransactionStatus)> {
+        let closing = node
+            .close_channel(channel, unilateral_timeout)
+            .await
+            .with_context(|| format!("Fermeture du canal {} de {} impossible", channel.funding_txid, node.id()))?;
+        let txid = closing.txid.clone().ok_or_else(|| {
+            anyhow::anyhow!("Le canal {} n'a été fermé par aucune transaction ({})", channel.funding_txid, closing.close_type)
+        })?;
+        self.bitcoin.generate_to_address(self.confirmations, &self.mining_address).await?;
+
+        let what = format!("la transaction de fermeture {}", txid);
+        let status = wait_for(&what, self.timeout, || async {
+            let status = self.bitcoin.get_transaction_status(&txid).await?;
+            Ok(if status.confirmations >= self.confirmations {
+                ControlFlow::Break(status)
+            } else {
+                ControlFlow::Continue(format!("{} confirmations", status.confirmations))
+            })
+        })
+        .await?;
+        Ok((closing, status))
+    }
+}
+
+/// Envoi on-chain vers un nœud, en attente de confirmation.
+pub struct Deposit<'n> {
+    node: &'n dyn LightningBackend,
+    pub txid: String,
+    /// Solde confirmé du nœud une fois l'envoi confirmé.
+    expected_msat: u64,
+}
+
+impl<'n> Deposit<'n> {
+    /// Envoie `amount_btc` à une nouvelle adresse de `node`. Le solde confirmé
+    /// est lu avant l'envoi, pour que des fonds déjà présents ne passent pas
+    /// pour ce dépôt.
+    pub async fn send(bitcoin: &dyn BitcoinBackend, node: &'n dyn LightningBackend, amount_btc: f64) -> Result<Self> {
+        let before = node.list_funds().await?.confirmed_onchain_msat();
+        let address = node.get_new_address().await?;
+        let txid = bitcoin.send_to_address(&address, amount_btc).await?;
+        let expected_msat = before + (amount_btc * 100_000_000_000.0).round() as u64;
+        Ok(Self { node, txid, expected_msat })
+    }
+
+    /// Attend que le nœud voie le dépôt confirmé.
+    pub async fn wait_confirmed(&self, timeout: Duration) -> Result<()> {
+        wait_for_confirmed_funds(self.node, self.expected_msat, timeout).await
+    }
+}
+
+/// Attend que `node` dispose d'au moins `expected_msat` confirmés on-chain.
+async fn wait_for_confirmed_funds(node: &dyn LightningBackend, expected_msat: u64, timeout: Duration) -> Result<()> {
+    wait_for(&format!("les fonds confirmés de {}", node.id()), timeout, || async move {
+        let confirmed = node.list_funds().await?.confirmed_onchain_msat();
+        Ok(if confirmed >= expected_msat {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(format!("{} msat confirmés sur {}", confirmed, expected_msat))
+        })
+    })
+    .await
+}
+
+/// Attend que le canal financé par `funding_txid` soit utilisable. Échoue sans
+/// attendre la fin du délai si le canal est déjà en cours de fermeture.
+pub async fn wait_for_channel_normal(
+    node: &dyn LightningBackend,
+    funding_txid: &str,
+    timeout: Duration,
+) -> Result<ChannelEntry> {
+    let what = format!("le canal {} de {} en CHANNELD_NORMAL", funding_txid, node.id());
+    wait_for(&what, timeout, || async move {
+        let funds = node.list_funds().await?;
+        let Some(channel) = funds.channels.into_iter().find(|c| c.funding_txid == funding_txid) else {
+            return Ok(ControlFlow::Continue("inconnu".to_string()));
+        };
+        if channel.state.is_normal() {
+            Ok(ControlFlow::Break(channel))
+        } else if !channel.state.is_opening() {
+            Err(anyhow::anyhow!(
+                "Le canal {} de {} a été fermé avant d'être utilisable (état {})",
+                funding_txid,
+                node.id(),
+                channel.state
+            ))
+        } else {
+            Ok(ControlFlow::Continue(channel.state.to_string()))
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin::MockBitcoin;
+    use crate::lightning::{ChannelState, CloseType, MockLightning, MOCK_TO_SELF_DELAY};
+    use crate::visualization::fixtures::{channel, ALICE, BOB};
+
+    #[tokio::test(start_paused = true)]
+    async fn channel_is_normal_after_its_confirmations() {
+        let chain = MockBitcoin::new();
+        let alice = MockLightning::new("alice", ALICE).with_chain(&chain);
+        alice.connect_peer(BOB, "127.0.0.1", 9736).await.unwrap();
+        let workflow = ChannelWorkflow::new(&chain, 3, Duration::from_secs(10)).await.unwrap();
+
+        workflow.fund(&alice, 0.01).await.unwrap();
+        let channel = workflow.open(&alice, BOB, 200_000, 0).await.unwrap();
+
+        assert_eq!(channel.state, ChannelState::ChanneldNormal);
+        assert_eq!(channel.peer_id, BOB);
+        assert_eq!(chain.height(), 1 + 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn forced_close_is_swept_after_the_delay() {
+        let chain = MockBitcoin::new();
+        let alice = MockLightning::new("alice", ALICE).with_chain(&chain);
+        alice.connect_peer(BOB, "127.0.0.1", 9736).await.unwrap();
+        let workflow = ChannelWorkflow::new(&chain, 3, Duration::from_secs(10)).await.unwrap();
+        workflow.fund(&alice, 0.01).await.unwrap();
+        let channel = workflow.open(&alice, BOB, 200_000, 0).await.unwrap();
+        let onchain = alice.list_funds().await.unwrap().confirmed_onchain_msat();
+
+        let (closing, status) = workflow.close(&alice, &channel, Some(Duration::ZERO)).await.unwrap();
+        assert_eq!(closing.close_type, CloseType::Unilateral);
+        assert_eq!(status.confirmations, 3);
+        assert!(!status.outputs[0].spent);
+        assert_eq!(alice.list_funds().await.unwrap().channels[0].state, ChannelState::Onchain);
+
+        chain.generate_blocks(MOCK_TO_SELF_DELAY).await.unwrap();
+        let funds = alice.list_funds().await.unwrap();
+        let status = chain.get_transaction_status(&closing.txid.unwrap()).await.unwrap();
+        assert!(status.outputs[0].spent, "the delayed output has been swept");
+        assert_eq!(funds.confirmed_onchain_msat(), onchain);
+        chain.generate_blocks(1).await.unwrap();
+        assert_eq!(alice.list_funds().await.unwrap().confirmed_onchain_msat(), onchain + 200_000_000);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn existing_funds_do_not_pass_for_an_unconfirmed_deposit() {
+        let chain = MockBitcoin::new();
+        let alice = MockLightning::new("alice", ALICE).with_chain(&chain).with_onchain_balance(5_000_000);
+
+        let deposit = Deposit::send(&chain, &alice, 0.01).await.unwrap();
+        let err = deposit.wait_confirmed(Duration::from_secs(5)).await.unwrap_err().to_string();
+        assert!(err.contains("5000000000 msat confirmés sur 6000000000"), "{}", err);
+
+        chain.generate_blocks(1).await.unwrap();
+        deposit.wait_confirmed(Duration::from_secs(5)).await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn closed_channel_fails_without_waiting() {
+        let alice = MockLightning::new("alice", ALICE).with_channel(ChannelEntry {
+            state: ChannelState::Onchain,
+            connected: false,
+            ..channel(BOB, None, 1_000_000_000)
+        });
+
+        let start = tokio::time::Instant::now();
+        let err = wait_for_channel_normal(&alice, &"ab".repeat(32), Duration::from_secs(60))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("fermé") && err.contains("ONCHAIN"), "{}", err);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::{Context, Result};
+use config::{Config, File, FileFormat};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::lightning::BackendKind;
+use crate::settings::{LightningNodeSettings, Settings};
+
+mod channel;
+mod orchestrator;
+mod wait;
+
+pub use channel::ChannelWorkflow;
+pub use orchestrator::{Orchestrator, ScenarioNodeHandle};
+
+/// Topologie regtest décrite dans un fichier TOML : nœuds et leur financement,
+/// canaux à ouvrir puis paiements à effectuer, dans cet ordre.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    /// Blocs minés après l'envoi des fonds on-chain.
+    #[serde(default = "default_funding_confirmations")]
+    pub funding_confirmations: u64,
+    /// Blocs minés après l'ouverture des canaux ; 6 suffisent à les annoncer.
+    #[serde(default = "default_channel_confirmations")]
+    pub channel_confirmations: u64,
+    /// Délai maximal d'attente de chaque étape, en secondes.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default, rename = "node")]
+    pub nodes: Vec<ScenarioNode>,
+    #[serde(default, rename = "channel")]
+    pub channels: Vec<ScenarioChannel>,
+    #[serde(default, rename = "payment")]
+    pub payments: Vec<ScenarioPayment>,
+}
+
+fn default_funding_confirmations() -> u64 {
+    1
+}
+
+fn default_channel_confirmations() -> u64 {
+    6
+}
+
+fn default_timeout_secs() -> u64 {
+    120
+}
+
+/// Nœud du scénario. S'il n'existe pas dans la configuration, il y est ajouté
+/// à partir de `lightning_dir` (`<data_dir>/<id>` par défaut), `host` et `port`
+/// (attribué par le superviseur s'il est absent).
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScenarioNode {
+    pub id: String,
+    /// Montant envoyé on-chain au nœud avant l'ouverture des canaux, en BTC.
+    #[serde(default)]
+    pub fund_btc: f64,
+    pub lightning_dir: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScenarioChannel {
+    pub from: String,
+    pub to: String,
+    pub capacity_sat: u64,
+    /// Montant cédé à `to` à l'ouverture.
+    #[serde(default)]
+    pub push_msat: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScenarioPayment {
+    pub from: String,
+    pub to: String,
+    pub amount_msat: u64,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self> {
+        Config::builder()
+            .add_source(File::from(path).format(FileFormat::Toml).required(true))
+            .build()
+            .with_context(|| format!("Impossible de lire le scénario {:?}", path))?
+            .try_deserialize()
+            .with_context(|| format!("Scénario invalide dans {:?}", path))
+    }
+
+    /// Ajoute à la configuration les nœuds que seul le scénario déclare.
+    pub fn register_nodes(&self, settings: &mut Settings) -> Result<()> {
+        for node in &self.nodes {
+            if settings.lightning_node(&node.id).is_ok() {
+                continue;
+            }
+            settings.add_lightning_node(LightningNodeSettings {
+                id: node.id.clone(),
+                backend: BackendKind::Cln,
+                lightning_dir: node.lightning_dir.clone().unwrap_or_default(),
+                rest_url: None,
+                macaroon_path: None,
+                tls_cert_path: None,
+                host: node.host.clone().unwrap_or_else(|| "127.0.0.1".to_string()),
+                port: node.port,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Vérifie que chaque canal et paiement relie deux nœuds déclarés et
+    /// renvoie toutes les erreurs d'un coup.
+    pub fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+        let mut ids = HashSet::new();
+        for node in &self.nodes {
+            if !ids.insert(node.id.as_str()) {
+                errors.push(format!("le nœud {:?} est déclaré plusieurs fois", node.id));
+            }
+            if !(node.fund_btc >= 0.0 && node.fund_btc.is_finite()) {
+                errors.push(format!("nœud {} : fund_btc doit être positif", node.id));
+            }
+        }
+
+        let mut check_endpoints = |what: String, from: &str, to: &str| {
+            for id in [from, to] {
+                if !ids.contains(id) {
+                    errors.push(format!("{} : le nœud {:?} n'est pas déclaré dans [[node]]", what, id));
+                }
+            }
+            if from == to {
+                errors.push(format!("{} : from et to doivent être différents", what));
+            }
+        };
+        for (i, channel) in self.channels.iter().enumerate() {
+            check_endpoints(format!("channel[{}]", i), &channel.from, &channel.to);
+        }
+        for (i, payment) in self.payments.iter().enumerate() {
+            check_endpoints(format!("payment[{}]", i), &payment.from, &payment.to);
+        }
+
+        for (i, channel) in self.channels.iter().enumerate() {
+            if channel.capacity_sat == 0 {
+                errors.push(format!("channel[{}] : capacity_sat doit être différent de 0", i));
+            }
+            if channel.push_msat > channel.capacity_sat * 1000 {
+                errors.push(format!("channel[{}] : push_msat dépasse la capacité du canal", i));
+            }
+        }
+        for (i, payment) in self.payments.iter().enumerate() {
+            if payment.amount_msat == 0 {
+                errors.push(format!("payment[{}] : amount_msat doit être différent de 0", i));
+            }
+        }
+        if self.timeout_secs == 0 {
+            errors.push("timeout_secs doit être différent de 0".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Scénario invalide :\n  - {}", errors.join("\n  - ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn example(name: &str) -> Scenario {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("scenarios").join(name);
+        Scenario::load(&path).unwrap()
+    }
+
+    #[test]
+    fn ten_node_example_is_valid() {
+        let scenario = example("ten_nodes.toml");
+        scenario.validate().unwrap();
+        assert_eq!(scenario.nodes.len(), 10);
+
+        let mut settings = Settings::from_toml(
+            r#"
+            [bitcoin]
+            rpc_host = "127.0.0.1"
+            rpc_port = 18443
+            rpc_user = "user"
+            rpc_password = "password"
+
+            [[lightning]]
+            id = "node1"
+            lightning_dir = "/tmp/node1"
+            "#,
+        )
+        .unwrap();
+        scenario.register_nodes(&mut settings).unwrap();
+        assert_eq!(settings.lightning.len(), 10);
+        assert_eq!(settings.lightning_node("node1").unwrap().lightning_dir, "/tmp/node1");
+    }
+
+    #[test]
+    fn unknown_endpoints_are_all_reported() {
+        let mut scenario = example("ten_nodes.toml");
+        scenario.channels[0].to = "ghost".to_string();
+        scenario.payments[0].from = "phantom".to_string();
+        let err = scenario.validate().unwrap_err().to_string();
+        assert!(err.contains("\"ghost\"") && err.contains("\"phantom\""), "{}", err);
+    }
+}
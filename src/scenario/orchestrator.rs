@@ -0,0 +1,282 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::channel::{wait_for_channel_normal, Deposit};
+use super::wait::wait_for;
+use super::{Scenario, ScenarioChannel, ScenarioPayment};
+use crate::bitcoin::BitcoinBackend;
+use crate::lightning::{LightningBackend, OutputStatus, PaymentStatus};
+
+/// Nœud participant à un scénario, avec l'adresse où ses pairs le joignent.
+pub struct ScenarioNodeHandle {
+    pub backend: Arc<dyn LightningBackend>,
+    pub host: String,
+    pub port: u16,
+}
+
+/// Exécute un `Scenario` : finance les nœuds, ouvre les canaux, mine les
+/// confirmations nécessaires et attend chaque changement d'état avant de
+/// passer à l'étape suivante.
+pub struct Orchestrator<'a> {
+    bitcoin: &'a dyn BitcoinBackend,
+    nodes: HashMap<String, ScenarioNodeHandle>,
+}
+
+impl<'a> Orchestrator<'a> {
+    pub fn new(bitcoin: &'a dyn BitcoinBackend, nodes: HashMap<String, ScenarioNodeHandle>) -> Self {
+        Self { bitcoin, nodes }
+    }
+
+    pub async fn run(&self, scenario: &Scenario) -> Result<()> {
+        scenario.validate()?;
+        self.bitcoin.network().require_mining("L'exécution d'un scénario")?;
+        let timeout = Duration::from_secs(scenario.timeout_secs);
+
+        let mut pubkeys = HashMap::new();
+        for node in &scenario.nodes {
+            let info = self.node(&node.id)?.backend.get_node_info().await?;
+            pubkeys.insert(node.id.clone(), info.id);
+        }
+
+        self.fund_nodes(scenario, timeout).await?;
+
+        let mut fundings = Vec::new();
+        for channel in &scenario.channels {
+            fundings.push(self.open_channel(channel, &pubkeys[&channel.to], timeout).await?);
+        }
+        if !fundings.is_empty() {
+            self.bitcoin.generate_blocks(scenario.channel_confirmations).await?;
+        }
+        for (channel, txid) in scenario.channels.iter().zip(&fundings) {
+            self.wait_for_channel_normal(channel, txid, timeout).await?;
+        }
+
+        for (i, payment) in scenario.payments.iter().enumerate() {
+            self.pay(i, payment, timeout).await?;
+        }
+        println!(
+            "Scénario terminé : {} nœuds, {} canaux, {} paiements",
+            scenario.nodes.len(),
+            scenario.channels.len(),
+            scenario.payments.len()
+        );
+        Ok(())
+    }
+
+    fn node(&self, id: &str) -> Result<&ScenarioNodeHandle> {
+        self.nodes
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("Le nœud {:?} du scénario n'est pas connecté", id))
+    }
+
+    async fn fund_nodes(&self, scenario: &Scenario, timeout: Duration) -> Result<()> {
+        let funded: Vec<_> = scenario.nodes.iter().filter(|node| node.fund_btc > 0.0).collect();
+        if funded.is_empty() {
+            return Ok(());
+        }
+
+        self.bitcoin.ensure_wallet().await?;
+        let mut deposits = Vec::new();
+        for node in &funded {
+            let deposit = Deposit::send(self.bitcoin, self.node(&node.id)?.backend.as_ref(), node.fund_btc).await?;
+            println!("{} : {} BTC envoyés ({})", node.id, node.fund_btc, deposit.txid);
+            deposits.push(deposit);
+        }
+        self.bitcoin.generate_blocks(scenario.funding_confirmations.max(1)).await?;
+
+        for deposit in &deposits {
+            deposit.wait_confirmed(timeout).await?;
+        }
+        Ok(())
+    }
+
+    /// Connecte les deux nœuds, ouvre le canal puis mine un bloc pour que la
+    /// monnaie rendue soit utilisable par l'ouverture suivante.
+    async fn open_channel(&self, channel: &ScenarioChannel, peer_id: &str, timeout: Duration) -> Result<String> {
+        let from = &self.node(&channel.from)?.backend;
+        let to = self.node(&channel.to)?;
+        from.connect_peer(peer_id, &to.host, to.port)
+            .await
+            .with_context(|| format!("Connexion de {} à {} impossible", channel.from, channel.to))?;
+
+        let opening = from
+            .open_channel(peer_id, channel.capacity_sat, channel.push_msat)
+            .await
+            .with_context(|| format!("Ouverture du canal {} → {} impossible", channel.from, channel.to))?;
+        println!(
+            "Canal {} → {} : {} sats, transaction {}",
+            channel.from, channel.to, channel.capacity_sat, opening.txid
+        );
+
+        self.bitcoin.generate_blocks(1).await?;
+        wait_for(&format!("la monnaie de {}", channel.from), timeout, || async move {
+            let funds = from.list_funds().await?;
+            let pending = funds.outputs.iter().filter(|o| o.status == OutputStatus::Unconfirmed).count();
+            Ok(if pending == 0 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(format!("{} sorties non confirmées", pending))
+            })
+        })
+        .await?;
+        Ok(opening.txid)
+    }
+
+    async fn wait_for_channel_normal(&self, channel: &ScenarioChannel, txid: &str, timeout: Duration) -> Result<()> {
+        let from = self.node(&channel.from)?.backend.as_ref();
+        wait_for_channel_normal(from, txid, timeout)
+            .await
+            .with_context(|| format!("Le canal {} → {} n'est pas utilisable", channel.from, channel.to))?;
+        Ok(())
+    }
+
+    /// Paie une facture du destinataire. Le nœud essaie lui-même d'autres
+    /// routes ; la facture n'est repayée qu'après un échec définitif, le temps
+    /// que le gossip fasse connaître une route au payeur. Une erreur du nœud,
+    /// après laquelle l'issue du paiement est inconnue, interrompt le scénario.
+    async fn pay(&self, index: usize, payment: &ScenarioPayment, timeout: Duration) -> Result<()> {
+        let from = &self.node(&payment.from)?.backend;
+        let to = &self.node(&payment.to)?.backend;
+        let label = format!("scenario-{}-{}", index, chrono::Utc::now().timestamp_millis());
+        let invoice = to.create_invoice(payment.amount_msat, &label, "lightning_viz scenario").await?;
+
+        let what = format!("le paiement {} → {}", payment.from, payment.to);
+        let bolt11 = invoice.bolt11.as_str();
+        let result = wait_for(&what, timeout, || async move {
+            let result = from.pay(bolt11).await?;
+            Ok(match result.status {
+                PaymentStatus::Complete => ControlFlow::Break(result),
+                PaymentStatus::Failed => {
+                    ControlFlow::Continue(result.failure_reason.unwrap_or_else(|| result.status.to_string()))
+                }
+                PaymentStatus::Pending => {
+                    return Err(anyhow::anyhow!("Paiement {} toujours en cours", result.payment_hash))
+                }
+            })
+        })
+        .await?;
+        println!(
+            "Paiement {} → {} : {} msat, {}, {} msat de frais",
+            payment.from,
+            payment.to,
+            result.amount_sent_msat,
+            result.route_summary(),
+            result.fee_msat()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin::MockBitcoin;
+    use crate::lightning::{ChannelState, MockLightning};
+    use crate::visualization::fixtures::{ALICE, BOB, CAROL};
+
+    const PUBKEYS: [&str; 3] = [ALICE, BOB, CAROL];
+
+    fn scenario(source: &str) -> Scenario {
+        config::Config::builder()
+            .add_source(config::File::from_str(source, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    fn mock_nodes(chain: &MockBitcoin) -> (Vec<Arc<MockLightning>>, HashMap<String, ScenarioNodeHandle>) {
+        let mocks: Vec<_> = ["alice", "bob", "carol"]
+            .iter()
+            .zip(PUBKEYS)
+            .map(|(id, pubkey)| Arc::new(MockLightning::new(id, pubkey).with_chain(chain)))
+            .collect();
+        let handles = mocks
+            .iter()
+            .enumerate()
+            .map(|(i, mock)| {
+                let backend: Arc<dyn LightningBackend> = mock.clone();
+                let handle = ScenarioNodeHandle { backend, host: "127.0.0.1".to_string(), port: 9735 + i as u16 };
+                (mock.id().to_string(), handle)
+            })
+            .collect();
+        (mocks, handles)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn topology_is_built_in_order() {
+        let chain = MockBitcoin::new();
+        let (mocks, handles) = mock_nodes(&chain);
+        let scenario = scenario(
+            r#"
+            [[node]]
+            id = "alice"
+            fund_btc = 0.02
+            [[node]]
+            id = "bob"
+            fund_btc = 0.01
+            [[node]]
+            id = "carol"
+
+            [[channel]]
+            from = "alice"
+            to = "bob"
+            capacity_sat = 1000000
+            push_msat = 100000000
+            [[channel]]
+            from = "alice"
+            to = "carol"
+            capacity_sat = 500000
+            [[channel]]
+            from = "bob"
+            to = "carol"
+            capacity_sat = 800000
+
+            [[payment]]
+            from = "alice"
+            to = "carol"
+            amount_msat = 5000000
+            "#,
+        );
+
+        Orchestrator::new(&chain, handles).run(&scenario).await.unwrap();
+
+        let alice = mocks[0].list_funds().await.unwrap();
+        assert_eq!(alice.channels.len(), 2);
+        assert!(alice.channels.iter().all(|c| c.state == ChannelState::ChanneldNormal));
+        // 1 000 000 sats moins le push, plus 500 000 sats moins le paiement
+        assert_eq!(alice.channel_balance_msat(), 900_000_000 + 500_000_000 - 5_000_000);
+        assert_eq!(alice.confirmed_onchain_msat(), 500_000_000);
+        assert_eq!(mocks[1].list_funds().await.unwrap().channels[0].peer_id, PUBKEYS[2]);
+        assert_eq!(chain.height(), 1 + 3 + 6);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stuck_channel_times_out_with_its_state() {
+        let chain = MockBitcoin::new();
+        let (_, handles) = mock_nodes(&chain);
+        let scenario = scenario(
+            r#"
+            channel_confirmations = 1
+            timeout_secs = 5
+            [[node]]
+            id = "alice"
+            fund_btc = 0.1
+            [[node]]
+            id = "bob"
+
+            [[channel]]
+            from = "alice"
+            to = "bob"
+            capacity_sat = 100000
+            "#,
+        );
+
+        let err = format!("{:#}", Orchestrator::new(&chain, handles).run(&scenario).await.unwrap_err());
+        assert!(err.contains("alice → bob"), "{}", err);
+        assert!(err.contains("CHANNELD_AWAITING_LOCKIN"), "{}", err);
+    }
+}
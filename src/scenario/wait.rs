@@ -0,0 +1,52 @@
+use anyhow::Result;
+use std::future::Future;
+use std::ops::ControlFlow;
+use std::time::Duration;
+
+/// Intervalle entre deux sondes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Appelle `check` jusqu'à ce qu'il renvoie `ControlFlow::Break`, ou échoue
+/// après `timeout` en rappelant le dernier état observé (`ControlFlow::Continue`).
+/// Les erreurs de `check` interrompent l'attente immédiatement.
+pub async fn wait_for<T, F, Fut>(what: &str, timeout: Duration, mut check: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<ControlFlow<T, String>>>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let last_state = match check().await? {
+            ControlFlow::Break(value) => return Ok(value),
+            ControlFlow::Continue(state) => state,
+        };
+        if tokio::time::Instant::now() >= deadline {
+            return Err(anyhow::anyhow!(
+                "Délai de {:?} dépassé en attendant {} (dernier état : {})",
+                timeout,
+                what,
+                last_state
+            ));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn timeout_reports_the_last_state() {
+        let mut calls = 0;
+        let err = wait_for::<(), _, _>("le canal", Duration::from_secs(2), || {
+            calls += 1;
+            let state = format!("CHANNELD_AWAITING_LOCKIN ({} sondes)", calls);
+            async move { Ok(ControlFlow::Continue(state)) }
+        })
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("le canal"), "{}", err);
+        assert!(err.to_string().contains("CHANNELD_AWAITING_LOCKIN (5 sondes)"), "{}", err);
+    }
+}
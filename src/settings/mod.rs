@@ -0,0 +1,422 @@
+use anyhow::{Context, Result};
+use config::{Config, Environment, File, FileFormat};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::bitcoin::BitcoinConfig;
+use crate::lightning::{BackendKind, LightningConfig, LndConfig};
+
+/// Fichier de configuration chargé quand aucun chemin n'est fourni.
+pub const DEFAULT_CONFIG_PATH: &str = "config/default.toml";
+
+/// Préfixe des variables d'environnement, ex. `LIGHTNING_VIZ_BITCOIN__RPC_PASSWORD`.
+const ENV_PREFIX: &str = "LIGHTNING_VIZ";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Settings {
+    pub bitcoin: BitcoinConfig,
+    #[serde(default)]
+    pub lightning: Vec<LightningNodeSettings>,
+    #[serde(default)]
+    pub supervisor: SupervisorSettings,
+}
+
+/// Démarrage des démons par `--start-daemons` (section `[supervisor]`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SupervisorSettings {
+    /// Racine des données générées : `<data_dir>/bitcoind`, et `<data_dir>/<id>`
+    /// pour chaque nœud `cln` sans `lightning_dir`.
+    pub data_dir: String,
+    /// Exécutable lightningd, cherché dans le `PATH` s'il n'est pas absolu.
+    pub lightningd_path: String,
+    /// Port P2P de bitcoind.
+    pub bitcoind_p2p_port: u16,
+    /// Délai accordé à chaque démon pour répondre en RPC.
+    pub startup_timeout_secs: u64,
+    /// Délai accordé à chaque démon pour s'arrêter après `stop`, avant d'être tué.
+    pub shutdown_timeout_secs: u64,
+}
+
+impl Default for SupervisorSettings {
+    fn default() -> Self {
+        Self {
+            data_dir: "~/.lightning_viz".to_string(),
+            lightningd_path: "lightningd".to_string(),
+            bitcoind_p2p_port: 18444,
+            startup_timeout_secs: 60,
+            shutdown_timeout_secs: 30,
+        }
+    }
+}
+
+/// Un nœud Lightning déclaré dans une table `[[lightning]]`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LightningNodeSettings {
+    pub id: String,
+    #[serde(default)]
+    pub backend: BackendKind,
+    /// Répertoire de lightningd (backend `cln`), `<data_dir>/<id>` par défaut.
+    #[serde(default)]
+    pub lightning_dir: String,
+    /// URL de l'API REST (backend `lnd`).
+    pub rest_url: Option<String>,
+    /// Macaroon admin (backend `lnd`).
+    pub macaroon_path: Option<String>,
+    /// Certificat TLS (backend `lnd`).
+    pub tls_cert_path: Option<String>,
+    /// Adresse sur laquelle les pairs peuvent joindre ce nœud.
+    #[serde(default = "default_host")]
+    pub host: String,
+    /// Port d'écoute ; s'il est absent, le superviseur en choisit un libre au
+    /// démarrage et l'inscrit dans le fichier `config` de lightningd.
+    pub port: Option<u16>,
+}
+
+fn default_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+impl Settings {
+    /// Charge la configuration par couches : le fichier `path`, puis le fichier
+    /// utilisateur optionnel, puis les variables d'environnement `LIGHTNING_VIZ_*`.
+    /// Le résultat est validé avant d'être renvoyé.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut builder = Config::builder()
+            .add_source(File::from(path).format(FileFormat::Toml).required(true));
+
+        if let Some(user_path) = user_config_path() {
+            builder = builder.add_source(
+                File::from(user_path).format(FileFormat::Toml).required(false),
+            );
+        }
+
+        let settings: Settings = builder
+            .add_source(
+                Environment::with_prefix(ENV_PREFIX)
+                    .prefix_separator("_")
+                    .separator("__"),
+            )
+            .build()
+            .with_context(|| format!("Impossible de lire la configuration {:?}", path))?
+            .try_deserialize()
+            .with_context(|| format!("Configuration invalide dans {:?}", path))?;
+
+        let settings = settings.expand_paths();
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Charge une configuration complète depuis une chaîne TOML, sans fichier
+    /// utilisateur ni variables d'environnement.
+    #[cfg(test)]
+    pub fn from_toml(source: &str) -> Result<Self> {
+        let settings: Settings = Config::builder()
+            .add_source(File::from_str(source, FileFormat::Toml))
+            .build()?
+            .try_deserialize()?;
+        let settings = settings.expand_paths();
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Vérifie l'ensemble des valeurs et renvoie toutes les erreurs d'un coup.
+    pub fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+
+        let bitcoin = &self.bitcoin;
+        if bitcoin.rpc_host.trim().is_empty() {
+            errors.push("bitcoin.rpc_host ne peut pas être vide".to_string());
+        }
+        if bitcoin.rpc_port == 0 {
+            errors.push("bitcoin.rpc_port doit être différent de 0".to_string());
+        }
+        if bitcoin.rpc_user.is_empty() {
+            errors.push("bitcoin.rpc_user ne peut pas être vide".to_string());
+        }
+        if bitcoin.rpc_password.is_empty() {
+            errors.push("bitcoin.rpc_password ne peut pas être vide".to_string());
+        }
+        if bitcoin.rpc_timeout_secs == 0 {
+            errors.push("bitcoin.rpc_timeout_secs doit être différent de 0".to_string());
+        }
+        if matches!(&bitcoin.bitcoin_path, Some(path) if path.trim().is_empty()) {
+            errors.push("bitcoin.bitcoin_path ne peut pas être vide s'il est défini".to_string());
+        }
+
+        if self.lightning.is_empty() {
+            errors.push("au moins un nœud [[lightning]] doit être déclaré".to_string());
+        }
+        let mut seen = HashSet::new();
+        let mut addresses = HashMap::new();
+        for (i, node) in self.lightning.iter().enumerate() {
+            let name = if node.id.is_empty() { format!("#{}", i) } else { node.id.clone() };
+            if node.id.trim().is_empty() {
+                errors.push(format!("lightning[{}].id ne peut pas être vide", i));
+            } else if !seen.insert(node.id.as_str()) {
+                errors.push(format!("lightning[{}].id {:?} est déclaré plusieurs fois", i, node.id));
+            }
+            match node.backend {
+                BackendKind::Cln => {
+                    if node.lightning_dir.trim().is_empty() {
+                        errors.push(format!("lightning {} : lightning_dir ne peut pas être vide", name));
+                    }
+                }
+                BackendKind::Lnd => {
+                    if node.rest_url.as_deref().is_none_or(|url| url.trim().is_empty()) {
+                        errors.push(format!("lightning {} : rest_url est requis pour le backend lnd", name));
+                    }
+                    if node.macaroon_path.as_deref().is_none_or(|path| path.trim().is_empty()) {
+                        errors.push(format!("lightning {} : macaroon_path est requis pour le backend lnd", name));
+                    }
+                }
+            }
+            if node.host.trim().is_empty() {
+                errors.push(format!("lightning {} : host ne peut pas être vide", name));
+            }
+            match node.port {
+                Some(0) => errors.push(format!("lightning {} : port doit être différent de 0", name)),
+                Some(port) => {
+                    if let Some(other) = addresses.insert((node.host.as_str(), port), name.clone()) {
+                        let address = format!("{}:{}", node.host, port);
+                        errors.push(format!("lightning {} : {} est déjà utilisé par {}", name, address, other));
+                    }
+                }
+                None => {}
+            }
+        }
+
+        let supervisor = &self.supervisor;
+        if supervisor.data_dir.trim().is_empty() {
+            errors.push("supervisor.data_dir ne peut pas être vide".to_string());
+        }
+        if supervisor.bitcoind_p2p_port == 0 || supervisor.bitcoind_p2p_port == bitcoin.rpc_port {
+            errors.push("supervisor.bitcoind_p2p_port doit être non nul et différent de bitcoin.rpc_port".to_string());
+        }
+        if supervisor.startup_timeout_secs == 0 {
+            errors.push("supervisor.startup_timeout_secs doit être différent de 0".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Configuration invalide :\n  - {}",
+                errors.join("\n  - ")
+            ))
+        }
+    }
+
+    /// Ajoute un nœud déclaré ailleurs que dans la configuration (ex. un scénario)
+    /// puis revalide l'ensemble.
+    pub fn add_lightning_node(&mut self, mut node: LightningNodeSettings) -> Result<()> {
+        node.lightning_dir = expand_home(&node.lightning_dir);
+        self.default_lightning_dir(&mut node);
+        self.lightning.push(node);
+        self.validate()
+    }
+
+    /// Recherche un nœud Lightning par son identifiant.
+    pub fn lightning_node(&self, id: &str) -> Result<&LightningNodeSettings> {
+        self.lightning
+            .iter()
+            .find(|node| node.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Aucun nœud Lightning nommé {:?} dans la configuration", id))
+    }
+
+    /// Construit la `LightningConfig` d'un nœud à partir des paramètres Bitcoin communs.
+    pub fn lightning_config(&self, node: &LightningNodeSettings) -> LightningConfig {
+        LightningConfig {
+            network: self.bitcoin.network,
+            lightning_dir: node.lightning_dir.clone(),
+            bitcoin_rpc_host: self.bitcoin.rpc_host.clone(),
+            bitcoin_rpc_port: self.bitcoin.rpc_port,
+            bitcoin_rpc_user: self.bitcoin.rpc_user.clone(),
+            bitcoin_rpc_password: self.bitcoin.rpc_password.clone(),
+        }
+    }
+
+    /// Construit la `LndConfig` d'un nœud dont le backend est `lnd`.
+    pub fn lnd_config(&self, node: &LightningNodeSettings) -> Result<LndConfig> {
+        Ok(LndConfig {
+            rest_url: node.rest_url.clone().ok_or_else(|| {
+                anyhow::anyhow!("lightning {} : rest_url est requis pour le backend lnd", node.id)
+            })?,
+            macaroon_path: node.macaroon_path.clone().ok_or_else(|| {
+                anyhow::anyhow!("lightning {} : macaroon_path est requis pour le backend lnd", node.id)
+            })?,
+            tls_cert_path: node.tls_cert_path.clone(),
+        })
+    }
+
+    fn expand_paths(mut self) -> Self {
+        self.bitcoin.bitcoin_path = self.bitcoin.bitcoin_path.map(|p| expand_home(&p));
+        self.supervisor.data_dir = expand_home(&self.supervisor.data_dir);
+        self.supervisor.lightningd_path = expand_home(&self.supervisor.lightningd_path);
+        let mut nodes = std::mem::take(&mut self.lightning);
+        for node in &mut nodes {
+            node.lightning_dir = expand_home(&node.lightning_dir);
+            node.macaroon_path = node.macaroon_path.as_deref().map(expand_home);
+            node.tls_cert_path = node.tls_cert_path.as_deref().map(expand_home);
+            self.default_lightning_dir(node);
+        }
+        self.lightning = nodes;
+        self
+    }
+
+    /// Un nœud `cln` sans `lightning_dir` vit dans `<data_dir>/<id>`, comme
+    /// bitcoind dans `<data_dir>/bitcoind` : le superviseur n'écrit jamais
+    /// dans le répertoire d'un nœud installé par ailleurs.
+    fn default_lightning_dir(&self, node: &mut LightningNodeSettings) {
+        if node.backend == BackendKind::Cln && node.lightning_dir.trim().is_empty() && !node.id.trim().is_empty() {
+            node.lightning_dir = Path::new(&self.supervisor.data_dir).join(&node.id).display().to_string();
+        }
+    }
+}
+
+/// Fichier de surcharge propre à l'utilisateur :
+/// `$XDG_CONFIG_HOME/lightning_viz/config.toml` ou `~/.config/lightning_viz/config.toml`.
+fn user_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".config")))?;
+    Some(base.join("lightning_viz").join("config.toml"))
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// Remplace un `~` initial par le répertoire personnel de l'utilisateur.
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{}", home.display(), rest)
+        }
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const BASE: &str = r#"
+        [bitcoin]
+        rpc_host = "127.0.0.1"
+        rpc_port = 18443
+        rpc_user = "base"
+        rpc_password = "base"
+
+        [supervisor]
+        data_dir = "/srv/viz"
+
+        [[lightning]]
+        id = "node1"
+
+        [[lightning]]
+        id = "node2"
+        backend = "lnd"
+        rest_url = "https://127.0.0.1:8080"
+        macaroon_path = "~/lnd/admin.macaroon"
+    "#;
+
+    #[test]
+    fn user_file_then_environment_override_the_config_file() {
+        let dir = std::env::temp_dir().join(format!("lightning_viz_{}_settings", std::process::id()));
+        fs::create_dir_all(dir.join("lightning_viz")).unwrap();
+        let path = dir.join("default.toml");
+        fs::write(&path, BASE).unwrap();
+        fs::write(
+            dir.join("lightning_viz").join("config.toml"),
+            "[bitcoin]\nrpc_user = \"user\"\nrpc_password = \"user\"\n",
+        )
+        .unwrap();
+
+        // Seul ce test modifie ces variables, lues uniquement par `load`
+        std::env::set_var("XDG_CONFIG_HOME", &dir);
+        std::env::set_var("LIGHTNING_VIZ_BITCOIN__RPC_PASSWORD", "env");
+        let settings = Settings::load(&path);
+        std::env::remove_var("XDG_CONFIG_HOME");
+        std::env::remove_var("LIGHTNING_VIZ_BITCOIN__RPC_PASSWORD");
+        fs::remove_dir_all(&dir).unwrap();
+
+        let settings = settings.unwrap();
+        assert_eq!(settings.bitcoin.rpc_host, "127.0.0.1", "kept from the config file");
+        assert_eq!(settings.bitcoin.rpc_user, "user", "user file overrides the config file");
+        assert_eq!(settings.bitcoin.rpc_password, "env", "environment overrides the user file");
+    }
+
+    #[test]
+    fn every_error_is_reported_at_once() {
+        let mut settings = Settings::from_toml(BASE).unwrap();
+        settings.bitcoin.rpc_user.clear();
+        settings.bitcoin.rpc_port = 0;
+        settings.lightning[1].id = "node1".to_string();
+        settings.lightning[1].rest_url = None;
+        settings.supervisor.startup_timeout_secs = 0;
+
+        let message = settings.validate().unwrap_err().to_string();
+        for expected in [
+            "bitcoin.rpc_port doit être différent de 0",
+            "bitcoin.rpc_user ne peut pas être vide",
+            "lightning[1].id \"node1\" est déclaré plusieurs fois",
+            "lightning node1 : rest_url est requis pour le backend lnd",
+            "supervisor.startup_timeout_secs doit être différent de 0",
+        ] {
+            assert!(message.contains(expected), "{:?} missing from {}", expected, message);
+        }
+        assert_eq!(message.matches("\n  - ").count(), 5, "{}", message);
+    }
+
+    #[test]
+    fn two_nodes_cannot_listen_on_the_same_address() {
+        let mut settings = Settings::from_toml(BASE).unwrap();
+        settings.lightning[0].port = Some(9736);
+        settings.lightning[1].port = Some(9736);
+        let message = settings.validate().unwrap_err().to_string();
+        assert!(message.contains("lightning node2 : 127.0.0.1:9736 est déjà utilisé par node1"), "{}", message);
+
+        settings.lightning[1].host = "10.0.0.2".to_string();
+        settings.validate().unwrap();
+        settings.lightning[1].port = None;
+        settings.validate().unwrap();
+    }
+
+    #[test]
+    fn node_without_lightning_dir_is_invalid_only_without_id() {
+        let message = Settings::from_toml(&BASE.replace("id = \"node1\"", "id = \"\"")).unwrap_err().to_string();
+        assert!(message.contains("lightning[0].id ne peut pas être vide"), "{}", message);
+        assert!(message.contains("lightning #0 : lightning_dir ne peut pas être vide"), "{}", message);
+    }
+
+    #[test]
+    fn home_is_expanded_only_at_the_start_of_a_path() {
+        let Some(home) = home_dir() else { return };
+        assert_eq!(expand_home("~"), home.display().to_string());
+        assert_eq!(expand_home("~/.lightning"), format!("{}/.lightning", home.display()));
+        assert_eq!(expand_home("~alice/.lightning"), "~alice/.lightning");
+        assert_eq!(expand_home("/data/~/x"), "/data/~/x");
+    }
+
+    #[test]
+    fn paths_are_expanded_and_cln_dirs_default_under_data_dir() {
+        let mut settings = Settings::from_toml(BASE).unwrap();
+        assert_eq!(settings.lightning_node("node1").unwrap().lightning_dir, "/srv/viz/node1");
+        assert_eq!(settings.lightning_node("node2").unwrap().lightning_dir, "", "lnd nodes have no lightning_dir");
+        if let Some(home) = home_dir() {
+            let macaroon = settings.lightning_node("node2").unwrap().macaroon_path.clone();
+            assert_eq!(macaroon, Some(format!("{}/lnd/admin.macaroon", home.display())));
+        }
+
+        let mut node = settings.lightning_node("node1").unwrap().clone();
+        node.id = "node3".to_string();
+        node.lightning_dir.clear();
+        settings.add_lightning_node(node).unwrap();
+        assert_eq!(settings.lightning_node("node3").unwrap().lightning_dir, "/srv/viz/node3");
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt::Write;
+
+use crate::bitcoin::{BitcoinConfig, Network};
+use crate::settings::{LightningNodeSettings, SupervisorSettings};
+
+/// Première ligne des fichiers générés : un fichier qui ne la porte pas n'a
+/// pas été écrit par le superviseur et n'est jamais écrasé.
+pub(super) const GENERATED_HEADER: &str = "# Généré par lightning_viz, écrasé à chaque démarrage.\n";
+
+/// Section de `bitcoin.conf` propre à chaque réseau.
+fn bitcoind_section(network: Network) -> &'static str {
+    match network {
+        Network::Mainnet => "main",
+        Network::Testnet => "test",
+        Network::Signet => "signet",
+        Network::Regtest => "regtest",
+    }
+}
+
+/// Contenu de `bitcoin.conf` : authentification et ports issus de la configuration.
+pub(super) fn bitcoin_conf(bitcoin: &BitcoinConfig, supervisor: &SupervisorSettings) -> String {
+    let mut conf = String::from(GENERATED_HEADER);
+    if let Some(flag) = bitcoin.network.bitcoind_flag() {
+        let _ = writeln!(conf, "{}=1", flag.trim_start_matches('-'));
+    }
+    conf.push_str("server=1\n");
+    conf.push_str("txindex=1\n");
+    let _ = writeln!(conf, "rpcuser={}", bitcoin.rpc_user);
+    let _ = writeln!(conf, "rpcpassword={}", bitcoin.rpc_password);
+    if bitcoin.network.allows_mining() {
+        // Sans estimation de frais possible en regtest, les envois échoueraient.
+        conf.push_str("fallbackfee=0.00001\n");
+    }
+
+    let _ = writeln!(conf, "\n[{}]", bitcoind_section(bitcoin.network));
+    let _ = writeln!(conf, "rpcport={}", bitcoin.rpc_port);
+    let _ = writeln!(conf, "rpcbind={}", bitcoin.rpc_host);
+    let _ = writeln!(conf, "rpcallowip={}", bitcoin.rpc_host);
+    let _ = writeln!(conf, "port={}", supervisor.bitcoind_p2p_port);
+    conf
+}
+
+/// Contenu du fichier `config` de lightningd pour un nœud de la configuration,
+/// à l'écoute sur `port`.
+pub(super) fn lightningd_conf(bitcoin: &BitcoinConfig, node: &LightningNodeSettings, port: u16) -> String {
+    let mut conf = String::from(GENERATED_HEADER);
+    let _ = writeln!(conf, "network={}", bitcoin.network.lightning_dir_name());
+    let _ = writeln!(conf, "alias={}", node.id);
+    let _ = writeln!(conf, "addr={}:{}", node.host, port);
+    let _ = writeln!(conf, "bitcoin-rpcconnect={}", bitcoin.rpc_host);
+    let _ = writeln!(conf, "bitcoin-rpcport={}", bitcoin.rpc_port);
+    let _ = writeln!(conf, "bitcoin-rpcuser={}", bitcoin.rpc_user);
+    let _ = writeln!(conf, "bitcoin-rpcpassword={}", bitcoin.rpc_password);
+    conf.push_str("log-level=info\n");
+    conf
+}
+
+/// Port de la ligne `addr=` d'un fichier `config` de lightningd.
+pub(super) fn lightningd_port(conf: &str) -> Option<u16> {
+    conf.lines()
+        .filter_map(|line| line.strip_prefix("addr="))
+        .find_map(|addr| addr.trim().rsplit_once(':')?.1.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+
+    fn settings(network: &str) -> Settings {
+        Settings::from_toml(&format!(
+            r#"
+            [bitcoin]
+            rpc_host = "127.0.0.1"
+            rpc_port = 38332
+            rpc_user = "user"
+            rpc_password = "password"
+            network = "{}"
+
+            [[lightning]]
+            id = "node1"
+            lightning_dir = "/tmp/node1"
+            port = 9736
+            "#,
+            network
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn bitcoin_conf_puts_ports_in_the_network_section() {
+        let settings = settings("signet");
+        let conf = bitcoin_conf(&settings.bitcoin, &settings.supervisor);
+        assert!(conf.contains("signet=1\n"), "{}", conf);
+        assert!(conf.contains("\n[signet]\nrpcport=38332\n"), "{}", conf);
+        assert!(conf.contains("port=18444\n"), "{}", conf);
+        assert!(!conf.contains("fallbackfee"), "{}", conf);
+    }
+
+    #[test]
+    fn lightningd_conf_uses_lightningd_network_names() {
+        let settings = settings("mainnet");
+        let conf = lightningd_conf(&settings.bitcoin, &settings.lightning[0], 9736);
+        assert!(conf.contains("network=bitcoin\n"), "{}", conf);
+        assert!(conf.contains("addr=127.0.0.1:9736\n"), "{}", conf);
+        assert!(conf.contains("bitcoin-rpcport=38332\n"), "{}", conf);
+        assert_eq!(lightningd_port(&conf), Some(9736));
+    }
+}
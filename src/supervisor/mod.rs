@@ -0,0 +1,363 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+use crate::bitcoin::{BitcoinBackend, BitcoinNode};
+use crate::lightning::{BackendKind, LightningBackend, LightningConfig, LightningNode};
+use crate::settings::{LightningNodeSettings, Settings};
+
+mod conf;
+mod process;
+
+use process::ManagedProcess;
+
+/// Intervalle entre deux sondes RPC pendant le démarrage.
+const READINESS_POLL: Duration = Duration::from_millis(500);
+
+/// Nombre de ports proposés par le système avant d'abandonner la recherche
+/// d'un port libre.
+const FREE_PORT_ATTEMPTS: usize = 16;
+
+/// Démarre bitcoind et les nœuds lightningd de la configuration, puis les
+/// arrête proprement (`stop` RPC, puis arrêt forcé après délai).
+///
+/// Les démons qui répondent déjà au démarrage sont réutilisés et ne sont pas
+/// arrêtés par `shutdown`.
+pub struct Supervisor {
+    settings: Settings,
+    bitcoind: Option<ManagedProcess>,
+    lightningd: Vec<(String, ManagedProcess)>,
+    /// Ports déjà réservés : ceux de la configuration et ceux attribués.
+    ports: HashSet<u16>,
+}
+
+impl Supervisor {
+    pub async fn start(settings: &Settings) -> Result<Self> {
+        let mut ports: HashSet<u16> = settings.lightning.iter().filter_map(|node| node.port).collect();
+        ports.extend([settings.bitcoin.rpc_port, settings.supervisor.bitcoind_p2p_port]);
+        let mut supervisor = Self {
+            settings: settings.clone(),
+            bitcoind: None,
+            lightningd: Vec::new(),
+            ports,
+        };
+        if let Err(e) = supervisor.start_all().await {
+            // Ne pas laisser derrière nous les démons déjà lancés
+            if let Err(stop_error) = supervisor.shutdown().await {
+                tracing::warn!("Arrêt des démons incomplet : {}", stop_error);
+            }
+            return Err(e);
+        }
+        Ok(supervisor)
+    }
+
+    async fn start_all(&mut self) -> Result<()> {
+        self.start_bitcoind().await?;
+        for node in self.settings.lightning.clone() {
+            match node.backend {
+                BackendKind::Cln => self.start_lightningd(&node).await?,
+                BackendKind::Lnd => println!("{} : backend lnd, démarrage non géré", node.id),
+            }
+        }
+        Ok(())
+    }
+
+    fn startup_timeout(&self) -> Duration {
+        Duration::from_secs(self.settings.supervisor.startup_timeout_secs)
+    }
+
+    fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.settings.supervisor.shutdown_timeout_secs)
+    }
+
+    async fn start_bitcoind(&mut self) -> Result<()> {
+        let bitcoin = &self.settings.bitcoin;
+        let node = BitcoinNode::new(bitcoin.clone())?;
+        if node.is_ready().await {
+            println!("Bitcoin Core est déjà en cours d'exécution");
+            return node.ensure_wallet().await;
+        }
+
+        let datadir = PathBuf::from(&self.settings.supervisor.data_dir).join("bitcoind");
+        write_generated(&datadir, "bitcoin.conf", &conf::bitcoin_conf(bitcoin, &self.settings.supervisor))?;
+
+        let command = bitcoind_command(&self.settings, &datadir);
+        let mut process = ManagedProcess::spawn("bitcoind", command, &datadir.join("bitcoind.log"))?;
+
+        let deadline = tokio::time::Instant::now() + self.startup_timeout();
+        loop {
+            process.check_running()?;
+            if node.is_ready().await {
+                break;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                self.bitcoind = Some(process);
+                return Err(anyhow::anyhow!("bitcoind ne répond pas après {:?}", self.startup_timeout()));
+            }
+            tokio::time::sleep(READINESS_POLL).await;
+        }
+        self.bitcoind = Some(process);
+        println!("Bitcoin Core est prêt");
+
+        node.ensure_wallet().await
+    }
+
+    async fn start_lightningd(&mut self, node_settings: &LightningNodeSettings) -> Result<()> {
+        let config = self.settings.lightning_config(node_settings);
+        if connect(&config, &node_settings.id).await.is_ok() {
+            println!("{} est déjà en cours d'exécution", node_settings.id);
+            return Ok(());
+        }
+
+        let lightning_dir = PathBuf::from(&node_settings.lightning_dir);
+        let port = match node_settings.port {
+            Some(port) => port,
+            None => {
+                let previous = std::fs::read_to_string(lightning_dir.join("config"))
+                    .ok()
+                    .and_then(|conf| conf::lightningd_port(&conf));
+                let port = free_port(&node_settings.host, &self.ports, previous)?;
+                self.ports.insert(port);
+                port
+            }
+        };
+        let conf = conf::lightningd_conf(&self.settings.bitcoin, node_settings, port);
+        write_generated(&lightning_dir, "config", &conf)?;
+
+        let command = lightningd_command(&self.settings, &lightning_dir);
+        let name = format!("lightningd {}", node_settings.id);
+        let mut process = ManagedProcess::spawn(&name, command, &lightning_dir.join("lightningd.log"))?;
+
+        let deadline = tokio::time::Instant::now() + self.startup_timeout();
+        let result = loop {
+            if let Err(e) = process.check_running() {
+                break Err(e);
+            }
+            if connect(&config, &node_settings.id).await.is_ok() {
+                println!("{} est prêt", name);
+                break Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                break Err(anyhow::anyhow!("{} ne répond pas après {:?}", name, self.startup_timeout()));
+            }
+            tokio::time::sleep(READINESS_POLL).await;
+        };
+        self.lightningd.push((node_settings.id.clone(), process));
+        result
+    }
+
+    /// Arrête les démons lancés par le superviseur, nœuds Lightning d'abord.
+    pub async fn shutdown(mut self) -> Result<()> {
+        let timeout = self.shutdown_timeout();
+        while let Some((id, mut process)) = self.lightningd.pop() {
+            let node_settings = self.settings.lightning_node(&id)?;
+            let config = self.settings.lightning_config(node_settings);
+            if let Ok(node) = connect(&config, &id).await {
+                // La connexion est coupée par lightningd pendant sa réponse à stop
+                let _ = node.stop().await;
+            }
+            process.wait_or_kill(timeout).await?;
+        }
+
+        if let Some(mut process) = self.bitcoind.take() {
+            let node = BitcoinNode::new(self.settings.bitcoin.clone())?;
+            if let Err(e) = node.stop().await {
+                tracing::warn!("stop refusé par bitcoind : {}", e);
+            }
+            process.wait_or_kill(timeout).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Port sur lequel les pairs joignent un nœud : celui de la configuration,
+/// sinon celui que le superviseur a inscrit dans le fichier `config` de lightningd.
+pub fn node_port(node: &LightningNodeSettings) -> Result<u16> {
+    if let Some(port) = node.port {
+        return Ok(port);
+    }
+    std::fs::read_to_string(Path::new(&node.lightning_dir).join("config"))
+        .ok()
+        .and_then(|conf| conf::lightningd_port(&conf))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "lightning {} : port inconnu, indiquez-le dans la configuration ou démarrez le nœud avec up \
+                 ou --start-daemons",
+                node.id
+            )
+        })
+}
+
+/// Port libre sur `host` hors de `taken`, `preferred` en priorité : un nœud
+/// redémarré garde ainsi l'adresse que ses pairs connaissent.
+fn free_port(host: &str, taken: &HashSet<u16>, preferred: Option<u16>) -> Result<u16> {
+    if let Some(port) = preferred.filter(|port| !taken.contains(port)) {
+        if TcpListener::bind((host, port)).is_ok() {
+            return Ok(port);
+        }
+    }
+    for _ in 0..FREE_PORT_ATTEMPTS {
+        let listener = TcpListener::bind((host, 0)).with_context(|| format!("Impossible d'écouter sur {}", host))?;
+        let port = listener.local_addr()?.port();
+        if !taken.contains(&port) {
+            return Ok(port);
+        }
+    }
+    Err(anyhow::anyhow!("Aucun port libre trouvé sur {}", host))
+}
+
+/// Se connecte au socket RPC d'un nœud et vérifie qu'il répond à `getinfo`.
+async fn connect(config: &LightningConfig, id: &str) -> Result<LightningNode> {
+    let mut node = LightningNode::new(config.clone(), id.to_string());
+    node.connect_rpc().await?;
+    node.get_node_info().await?;
+    Ok(node)
+}
+
+fn bitcoind_command(settings: &Settings, datadir: &Path) -> Command {
+    let mut command = Command::new(settings.bitcoin.bitcoin_path.as_deref().unwrap_or("bitcoind"));
+    command.arg(format!("-datadir={}", datadir.display()));
+    command
+}
+
+fn lightningd_command(settings: &Settings, lightning_dir: &Path) -> Command {
+    let mut command = Command::new(&settings.supervisor.lightningd_path);
+    command.arg(format!("--lightning-dir={}", lightning_dir.display()));
+    command
+}
+
+/// Écrit un fichier de configuration généré, sauf si `dir` en contient déjà un
+/// que le superviseur n'a pas écrit, comme celui d'un nœud installé à la main.
+fn write_generated(dir: &Path, name: &str, content: &str) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Impossible de créer {:?}", dir))?;
+    let path = dir.join(name);
+    match std::fs::read(&path) {
+        Ok(existing) if !existing.starts_with(conf::GENERATED_HEADER.as_bytes()) => {
+            return Err(anyhow::anyhow!(
+                "{:?} n'a pas été généré par lightning_viz et ne sera pas écrasé : retirez lightning_dir de la \
+                 configuration pour utiliser <data_dir>/<id>, ou démarrez ce démon vous-même",
+                path
+            ))
+        }
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(anyhow::Error::new(e).context(format!("Impossible de lire {:?}", path)))
+        }
+        _ => {}
+    }
+    std::fs::write(&path, content).with_context(|| format!("Impossible d'écrire {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lightning_viz_{}_{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Port local sur lequel rien n'écoute : bitcoind n'y est pas déjà lancé.
+    fn closed_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+    }
+
+    fn settings(data_dir: &Path, bitcoin_path: &str) -> Settings {
+        Settings::from_toml(&format!(
+            r#"
+            [bitcoin]
+            rpc_host = "127.0.0.1"
+            rpc_port = {}
+            rpc_user = "user"
+            rpc_password = "password"
+            bitcoin_path = '{}'
+
+            [supervisor]
+            data_dir = '{}'
+            startup_timeout_secs = 5
+
+            [[lightning]]
+            id = "node1"
+            "#,
+            closed_port(),
+            bitcoin_path,
+            data_dir.display()
+        ))
+        .unwrap()
+    }
+
+    fn args(command: &Command) -> Vec<String> {
+        command.as_std().get_args().map(|arg| arg.to_string_lossy().into_owned()).collect()
+    }
+
+    #[test]
+    fn daemons_run_in_their_own_dirs_under_data_dir() {
+        let data_dir = temp_dir("supervisor_dirs");
+        let settings = settings(&data_dir, "bitcoind");
+        let node_dir = Path::new(&settings.lightning[0].lightning_dir);
+        assert_eq!(node_dir, data_dir.join("node1"));
+
+        assert_eq!(args(&lightningd_command(&settings, node_dir)), [format!("--lightning-dir={}", node_dir.display())]);
+        let bitcoind = bitcoind_command(&settings, &data_dir.join("bitcoind"));
+        assert_eq!(bitcoind.as_std().get_program(), "bitcoind");
+        assert_eq!(args(&bitcoind), [format!("-datadir={}", data_dir.join("bitcoind").display())]);
+    }
+
+    #[test]
+    fn nodes_without_port_get_a_free_one_kept_across_restarts() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let busy = listener.local_addr().unwrap().port();
+        let port = free_port("127.0.0.1", &HashSet::new(), Some(busy)).unwrap();
+        assert_ne!(port, busy, "a port in use is not reused");
+        assert_eq!(free_port("127.0.0.1", &HashSet::new(), Some(port)).unwrap(), port);
+        assert_ne!(free_port("127.0.0.1", &HashSet::from([port]), Some(port)).unwrap(), port);
+
+        let data_dir = temp_dir("supervisor_ports");
+        let settings = settings(&data_dir, "bitcoind");
+        let node = &settings.lightning[0];
+        let err = node_port(node).unwrap_err();
+        assert!(err.to_string().contains("port inconnu"), "{}", err);
+        write_generated(Path::new(&node.lightning_dir), "config", &conf::lightningd_conf(&settings.bitcoin, node, port))
+            .unwrap();
+        assert_eq!(node_port(node).unwrap(), port);
+    }
+
+    #[test]
+    fn foreign_config_is_never_overwritten() {
+        let dir = temp_dir("supervisor_foreign");
+        std::fs::write(dir.join("config"), "network=regtest\nalias=mine\n").unwrap();
+
+        let err = write_generated(&dir, "config", "# généré\n").unwrap_err();
+        assert!(err.to_string().contains("ne sera pas écrasé"), "{}", err);
+        assert_eq!(std::fs::read_to_string(dir.join("config")).unwrap(), "network=regtest\nalias=mine\n");
+
+        let generated = format!("{}network=regtest\n", conf::GENERATED_HEADER);
+        std::fs::write(dir.join("config"), &generated).unwrap();
+        write_generated(&dir, "config", &format!("{}network=signet\n", conf::GENERATED_HEADER)).unwrap();
+        assert!(std::fs::read_to_string(dir.join("config")).unwrap().ends_with("network=signet\n"));
+    }
+
+    /// bitcoind est remplacé par un script `sh` exécutable.
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failed_start_reports_the_daemon_log() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let data_dir = temp_dir("supervisor_start");
+        let stub = data_dir.join("bitcoind-stub");
+        std::fs::write(&stub, "#!/bin/sh\necho \"stub bitcoind $@\"\nexit 3\n").unwrap();
+        std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let err = Supervisor::start(&settings(&data_dir, &stub.display().to_string())).await.err().unwrap();
+
+        let message = format!("{:#}", err);
+        assert!(message.contains("bitcoind s'est arrêté"), "{}", message);
+        assert!(message.contains(&format!("stub bitcoind -datadir={}", data_dir.join("bitcoind").display())), "{}", message);
+        let conf = std::fs::read_to_string(data_dir.join("bitcoind").join("bitcoin.conf")).unwrap();
+        assert!(conf.starts_with(conf::GENERATED_HEADER), "{}", conf);
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::{Child, Command};
+
+/// Démon lancé par le superviseur, dont la sortie est redirigée vers un journal.
+pub(super) struct ManagedProcess {
+    pub name: String,
+    pub log_path: PathBuf,
+    child: Child,
+}
+
+impl ManagedProcess {
+    /// Lance `command` en redirigeant stdout et stderr vers `log_path`.
+    /// Le processus est tué si le `ManagedProcess` est abandonné sans `stop`.
+    pub fn spawn(name: &str, mut command: Command, log_path: &Path) -> Result<Self> {
+        let log = File::create(log_path)
+            .with_context(|| format!("Impossible de créer le journal {:?}", log_path))?;
+        let child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::from(log.try_clone()?))
+            .stderr(Stdio::from(log))
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Impossible de lancer {}", name))?;
+        println!("{} lancé (pid {:?}), journal : {:?}", name, child.id(), log_path);
+
+        Ok(Self {
+            name: name.to_string(),
+            log_path: log_path.to_path_buf(),
+            child,
+        })
+    }
+
+    /// Renvoie une erreur, avec la fin du journal, si le processus s'est déjà terminé.
+    pub fn check_running(&mut self) -> Result<()> {
+        match self.child.try_wait()? {
+            None => Ok(()),
+            Some(status) => Err(anyhow::anyhow!(
+                "{} s'est arrêté ({}) :\n{}",
+                self.name,
+                status,
+                log_tail(&self.log_path, 20)
+            )),
+        }
+    }
+
+    /// Attend la fin du processus après une demande d'arrêt, puis le tue
+    /// s'il est toujours là après `timeout`.
+    pub async fn wait_or_kill(&mut self, timeout: Duration) -> Result<()> {
+        match tokio::time::timeout(timeout, self.child.wait()).await {
+            Ok(status) => {
+                println!("{} arrêté ({})", self.name, status?);
+            }
+            Err(_) => {
+                println!("{} ne répond pas à stop, arrêt forcé", self.name);
+                self.child.kill().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Dernières lignes d'un journal, pour expliquer un échec de démarrage.
+fn log_tail(path: &Path, lines: usize) -> String {
+    let content = std::fs::read_to_string(path).unwrap_or_default();
+    let all: Vec<&str> = content.lines().collect();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
+
+// Les processus de test sont des scripts `sh`.
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn log_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("lightning_viz_{}_{}.log", std::process::id(), name))
+    }
+
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    }
+
+    #[tokio::test]
+    async fn stopped_process_is_waited_for() {
+        let mut process = ManagedProcess::spawn("stub", shell("echo started"), &log_path("stopped")).unwrap();
+        process.wait_or_kill(Duration::from_secs(5)).await.unwrap();
+        assert!(process.check_running().is_err());
+        assert_eq!(log_tail(&process.log_path, 1), "started");
+    }
+
+    #[tokio::test]
+    async fn unresponsive_process_is_killed_after_the_timeout() {
+        let mut process = ManagedProcess::spawn("stub", shell("sleep 30"), &log_path("killed")).unwrap();
+        process.check_running().unwrap();
+
+        let started = std::time::Instant::now();
+        process.wait_or_kill(Duration::from_millis(100)).await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(process.child.try_wait().unwrap().is_some(), "process is gone");
+    }
+}
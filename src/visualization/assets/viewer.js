@@ -0,0 +1,120 @@
+// Navigation dans le SVG exporté : zoom à la molette, déplacement au glisser,
+// infobulles et recherche par alias, clé publique ou short channel id.
+(function () {
+    "use strict";
+
+    const viewport = document.getElementById("viewport");
+    const svg = viewport.querySelector("svg");
+    const tooltip = document.getElementById("tooltip");
+    const search = document.getElementById("search");
+    const matches = document.getElementById("matches");
+
+    const box = svg.viewBox.baseVal;
+    const home = { x: box.x, y: box.y, width: box.width, height: box.height };
+    let view = Object.assign({}, home);
+    svg.removeAttribute("width");
+    svg.removeAttribute("height");
+
+    function apply() {
+        svg.setAttribute("viewBox", view.x + " " + view.y + " " + view.width + " " + view.height);
+    }
+
+    // Position du pointeur dans le repère du dessin
+    function toDrawing(event) {
+        const point = svg.createSVGPoint();
+        point.x = event.clientX;
+        point.y = event.clientY;
+        return point.matrixTransform(svg.getScreenCTM().inverse());
+    }
+
+    svg.addEventListener("wheel", function (event) {
+        event.preventDefault();
+        const anchor = toDrawing(event);
+        const factor = event.deltaY < 0 ? 0.85 : 1 / 0.85;
+        view.x = anchor.x - (anchor.x - view.x) * factor;
+        view.y = anchor.y - (anchor.y - view.y) * factor;
+        view.width *= factor;
+        view.height *= factor;
+        apply();
+    }, { passive: false });
+
+    let grab = null;
+    svg.addEventListener("pointerdown", function (event) {
+        grab = toDrawing(event);
+        viewport.classList.add("dragging");
+        svg.setPointerCapture(event.pointerId);
+    });
+    svg.addEventListener("pointermove", function (event) {
+        if (grab) {
+            // Le point saisi reste sous le pointeur
+            const current = toDrawing(event);
+            view.x += grab.x - current.x;
+            view.y += grab.y - current.y;
+            apply();
+        }
+    });
+    svg.addEventListener("pointerup", function () {
+        grab = null;
+        viewport.classList.remove("dragging");
+    });
+
+    // Les <title> du SVG deviennent des infobulles instantanées
+    svg.querySelectorAll("g.node, g.channel").forEach(function (element) {
+        const title = element.querySelector("title");
+        if (!title) {
+            return;
+        }
+        const text = title.textContent;
+        title.remove();
+        element.addEventListener("mouseenter", function () {
+            tooltip.textContent = text;
+            tooltip.hidden = false;
+        });
+        element.addEventListener("mousemove", function (event) {
+            tooltip.style.left = event.clientX + 14 + "px";
+            tooltip.style.top = event.clientY + 14 + "px";
+        });
+        element.addEventListener("mouseleave", function () {
+            tooltip.hidden = true;
+        });
+    });
+
+    function centerOn(element) {
+        const bounds = element.getBBox();
+        const scale = Math.max((bounds.width * 3) / home.width, (bounds.height * 3) / home.height, 0.2);
+        view.width = home.width * scale;
+        view.height = home.height * scale;
+        view.x = bounds.x + bounds.width / 2 - view.width / 2;
+        view.y = bounds.y + bounds.height / 2 - view.height / 2;
+        apply();
+    }
+
+    function runSearch() {
+        const query = search.value.trim().toLowerCase();
+        let found = [];
+        svg.querySelectorAll("[data-search]").forEach(function (element) {
+            const match = query !== "" && element.dataset.search.toLowerCase().includes(query);
+            element.classList.toggle("match", match);
+            if (match) {
+                found.push(element);
+            }
+        });
+        svg.classList.toggle("searching", query !== "");
+        matches.textContent = query === "" ? "" : found.length + " résultat(s)";
+        return found;
+    }
+
+    search.addEventListener("input", runSearch);
+    search.addEventListener("keydown", function (event) {
+        if (event.key === "Enter") {
+            const found = runSearch();
+            if (found.length > 0) {
+                centerOn(found[0]);
+            }
+        }
+    });
+    document.getElementById("reset").addEventListener("click", function () {
+        view = Object.assign({}, home);
+        apply();
+    });
+})();
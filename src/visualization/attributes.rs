@@ -0,0 +1,112 @@
+use super::style::{first_color, node_fill, node_stroke, ChannelKind};
+use super::{GraphChannel, GraphNode};
+
+/// Type d'un attribut, commun à GraphML et GEXF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+    String,
+    Long,
+    Boolean,
+}
+
+impl AttributeType {
+    /// Nom du type dans `attr.type` (GraphML) et `type` (GEXF).
+    pub fn name(self) -> &'static str {
+        match self {
+            AttributeType::String => "string",
+            AttributeType::Long => "long",
+            AttributeType::Boolean => "boolean",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeValue {
+    String(String),
+    Long(u64),
+    Boolean(bool),
+}
+
+impl std::fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttributeValue::String(value) => f.write_str(value),
+            AttributeValue::Long(value) => write!(f, "{}", value),
+            AttributeValue::Boolean(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Attributs déclarés pour les nœuds, dans l'ordre des exports.
+pub const NODE_ATTRIBUTES: &[(&str, AttributeType)] = &[
+    ("alias", AttributeType::String),
+    ("color", AttributeType::String),
+    ("capacity_sat", AttributeType::Long),
+    ("state", AttributeType::String),
+];
+
+/// Attributs déclarés pour les canaux. Les frais `fee_*` sont ceux de la
+/// direction source → cible de l'arête, `reverse_fee_*` ceux de l'autre.
+pub const CHANNEL_ATTRIBUTES: &[(&str, AttributeType)] = &[
+    ("short_channel_id", AttributeType::String),
+    ("capacity_sat", AttributeType::Long),
+    ("state", AttributeType::String),
+    ("color", AttributeType::String),
+    ("active", AttributeType::Boolean),
+    ("fee_base_msat", AttributeType::Long),
+    ("fee_ppm", AttributeType::Long),
+    ("reverse_fee_base_msat", AttributeType::Long),
+    ("reverse_fee_ppm", AttributeType::Long),
+    ("source_balance_sat", AttributeType::Long),
+    ("target_balance_sat", AttributeType::Long),
+];
+
+/// Valeurs connues des attributs d'un nœud ; un attribut inconnu est omis.
+pub fn node_attributes(node: &GraphNode) -> Vec<(&'static str, AttributeValue)> {
+    let mut values = Vec::new();
+    if let Some(alias) = node.alias.as_ref().filter(|alias| !alias.is_empty()) {
+        values.push(("alias", AttributeValue::String(alias.clone())));
+    }
+    if let Some(color) = node_stroke(node) {
+        values.push(("color", AttributeValue::String(color)));
+    }
+    values.push(("capacity_sat", AttributeValue::Long(node.capacity_msat / 1000)));
+    values.push(("state", AttributeValue::String(node.state.name().to_string())));
+    values
+}
+
+/// Valeurs connues des attributs d'un canal ; un attribut inconnu est omis.
+pub fn channel_attributes(channel: &GraphChannel) -> Vec<(&'static str, AttributeValue)> {
+    let kind = ChannelKind::of(channel);
+    let mut values = Vec::new();
+    if let Some(scid) = &channel.short_channel_id {
+        values.push(("short_channel_id", AttributeValue::String(scid.clone())));
+    }
+    values.push(("capacity_sat", AttributeValue::Long(channel.capacity_msat / 1000)));
+    values.push(("state", AttributeValue::String(kind.name().to_string())));
+    values.push(("color", AttributeValue::String(kind.style().color.to_string())));
+    values.push(("active", AttributeValue::Boolean(channel.is_active())));
+    let directions = [
+        (("fee_base_msat", "fee_ppm"), &channel.source),
+        (("reverse_fee_base_msat", "reverse_fee_ppm"), &channel.target),
+    ];
+    for ((base, ppm), end) in directions {
+        if let Some(policy) = &end.policy {
+            values.push((base, AttributeValue::Long(policy.base_fee_msat as u64)));
+            values.push((ppm, AttributeValue::Long(policy.fee_ppm as u64)));
+        }
+    }
+    if let Some(balance) = channel.source.balance_msat {
+        values.push(("source_balance_sat", AttributeValue::Long(balance / 1000)));
+    }
+    if let Some(balance) = channel.target.balance_msat {
+        values.push(("target_balance_sat", AttributeValue::Long(balance / 1000)));
+    }
+    values
+}
+
+/// Couleur d'affichage d'un nœud : celle qu'il annonce, sinon celle de son état.
+pub fn node_display_color(node: &GraphNode) -> String {
+    node_stroke(node).unwrap_or_else(|| first_color(node_fill(node.state)).to_string())
+}
+
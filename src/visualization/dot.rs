@@ -0,0 +1,200 @@
+use petgraph::visit::EdgeRef;
+use std::fmt::Write;
+
+use super::style::{
+    first_color, liquidity_segments, node_fill, node_stroke, pen_width, ChannelKind, Legend, REMOTE_LIQUIDITY_COLOR,
+};
+use super::{GraphChannel, GraphNode, NetworkGraph};
+
+/// Écrit le graphe au format DOT. Les nœuds sont identifiés par leur clé
+/// publique, de sorte qu'un même réseau produit toujours les mêmes identifiants.
+pub(super) fn write_dot(network: &NetworkGraph) -> String {
+    let mut dot = String::from("digraph lightning {\n");
+    dot.push_str("    rankdir=LR;\n");
+    dot.push_str("    splines=curved;\n");
+    dot.push_str("    bgcolor=\"#ffffff\";\n");
+    dot.push_str("    node [\n");
+    dot.push_str("        style=\"filled,rounded\",\n");
+    dot.push_str("        gradientangle=270,\n");
+    dot.push_str("        shape=\"box\",\n");
+    dot.push_str("        fontname=\"Arial\",\n");
+    dot.push_str("        fontsize=12\n");
+    dot.push_str("    ];\n");
+    dot.push_str("    edge [\n");
+    dot.push_str("        fontname=\"Arial\",\n");
+    dot.push_str("        fontsize=10,\n");
+    dot.push_str("        arrowsize=0.8\n");
+    dot.push_str("    ];\n");
+
+    let graph = &network.graph;
+
+    dot.push('\n');
+    for node in graph.node_weights() {
+        let _ = writeln!(dot, "    {} [{}];", quote(&node.id), attributes(&node_attributes(node)));
+    }
+
+    if graph.edge_count() > 0 {
+        dot.push('\n');
+    }
+    for edge in graph.edge_references() {
+        let _ = writeln!(
+            dot,
+            "    {} -> {} [{}];",
+            quote(&graph[edge.source()].id),
+            quote(&graph[edge.target()].id),
+            attributes(&channel_attributes(edge.weight()))
+        );
+    }
+
+    let legend = network.legend();
+    if !legend.is_empty() {
+        write_legend(&mut dot, &legend);
+    }
+    dot.push_str("}\n");
+
+    dot
+}
+
+/// Légende des seuls états de nœuds et de canaux présents dans le graphe.
+fn write_legend(dot: &mut String, legend: &Legend) {
+    dot.push_str("\n    subgraph cluster_legend {\n");
+    dot.push_str("        label=\"Légende\";\n");
+    dot.push_str("        node [shape=none, style=\"\"];\n");
+    dot.push_str("        legend [label=<\n");
+    dot.push_str("            <table border=\"0\">\n");
+    for state in &legend.nodes {
+        let _ = writeln!(
+            dot,
+            "                <tr><td bgcolor=\"{}\">Nœud {}</td></tr>",
+            first_color(node_fill(*state)),
+            state.label()
+        );
+    }
+    for kind in &legend.channels {
+        let style = kind.style();
+        let _ = writeln!(
+            dot,
+            "                <tr><td><font color=\"{}\">{} {}</font></td></tr>",
+            style.color,
+            style.line.sample(),
+            kind.legend_label()
+        );
+    }
+    if legend.liquidity {
+        let _ = writeln!(
+            dot,
+            "                <tr><td><font color=\"{}\">━━ Solde de la cible</font></td></tr>",
+            REMOTE_LIQUIDITY_COLOR
+        );
+    }
+    dot.push_str("            </table>\n");
+    dot.push_str("        >];\n");
+    dot.push_str("    }\n");
+}
+
+fn node_attributes(node: &GraphNode) -> Vec<(&'static str, String)> {
+    let fill = node_fill(node.state);
+    let mut tooltip = node.id.clone();
+    for address in &node.addresses {
+        tooltip.push('\n');
+        tooltip.push_str(address);
+    }
+    let mut attrs = vec![
+        ("label", node.label()),
+        ("tooltip", tooltip),
+        ("fillcolor", fill.to_string()),
+    ];
+    if let Some(color) = node_stroke(node) {
+        attrs.push(("color", color));
+        attrs.push(("penwidth", "2".to_string()));
+    }
+    attrs
+}
+
+fn channel_attributes(channel: &GraphChannel) -> Vec<(&'static str, String)> {
+    let style = ChannelKind::of(channel).style();
+    let color = match channel.liquidity_ratio() {
+        Some(ratio) => liquidity_color(style.color, ratio),
+        None => style.color.to_string(),
+    };
+    vec![
+        ("label", channel.label()),
+        ("color", color),
+        ("style", style.line.dot_name().to_string()),
+        ("penwidth", format!("{:.1}", pen_width(channel.capacity_msat))),
+    ]
+}
+
+/// Couleur d'arête découpée en segments proportionnels aux soldes, au format
+/// Graphviz `couleur;part:couleur`.
+fn liquidity_color(local_color: &'static str, local_ratio: f64) -> String {
+    match liquidity_segments(local_color, local_ratio).as_slice() {
+        [(local, ratio), (remote, _)] => format!("{};{:.2}:{}", local, ratio, remote),
+        segments => segments[0].0.to_string(),
+    }
+}
+
+fn attributes(attrs: &[(&str, String)]) -> String {
+    attrs
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, quote(value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Entoure une valeur de guillemets en échappant les caractères spéciaux DOT.
+/// Les retours à la ligne deviennent des `\n` interprétés par Graphviz.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => {}
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::fixtures::{assert_golden, two_node_network, ALICE};
+
+    #[test]
+    fn two_nodes_match_golden_file() {
+        assert_golden("two_nodes.dot", &two_node_network().to_dot());
+    }
+
+    #[test]
+    fn empty_graph_matches_golden_file() {
+        assert_golden("empty.dot", &NetworkGraph::new().to_dot());
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        let mut network = NetworkGraph::new();
+        network.add_node(ALICE, Some("al\"ice\\ \nnode"));
+        let dot = network.to_dot();
+        assert!(dot.contains(r#"label="al\"ice\\ \nnode\n(02aaaaaa)"#), "{}", dot);
+    }
+
+    #[test]
+    fn invalid_node_color_is_ignored() {
+        let mut network = NetworkGraph::new();
+        let idx = network.add_node(ALICE, Some("alice"));
+        network.graph[idx].color = Some("red\"; evil".to_string());
+        assert!(!network.to_dot().contains("evil"));
+    }
+
+    #[test]
+    fn liquidity_splits_the_edge_color() {
+        assert_eq!(liquidity_color("#a3be8c", 0.7), "#a3be8c;0.70:#81a1c1");
+        assert_eq!(liquidity_color("#a3be8c", 0.0), REMOTE_LIQUIDITY_COLOR);
+        assert_eq!(liquidity_color("#a3be8c", 1.0), "#a3be8c");
+    }
+}
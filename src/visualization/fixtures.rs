@@ -0,0 +1,111 @@
+use std::path::PathBuf;
+
+use super::{ChannelEnd, ChannelPolicy, GraphChannel, NetworkGraph, NodeState};
+use crate::lightning::{ChannelEntry, ChannelState, NodeInfo};
+
+pub const ALICE: &str = "02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+pub const BOB: &str = "03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+pub const CAROL: &str = "02cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc";
+
+/// `getinfo` d'un nœud avec un pair et un canal actif.
+pub fn node_info(id: &str, alias: &str) -> NodeInfo {
+    NodeInfo {
+        id: id.to_string(),
+        alias: Some(alias.to_string()),
+        color: "3399ff".to_string(),
+        network: "regtest".to_string(),
+        version: "v24.02".to_string(),
+        blockheight: 110,
+        num_peers: 1,
+        num_active_channels: 1,
+        num_pending_channels: 0,
+        num_inactive_channels: 0,
+        fees_collected_msat: 0,
+        addresses: Vec::new(),
+    }
+}
+
+/// Canal ouvert de 1 000 000 sats vers `peer_id`.
+pub fn channel(peer_id: &str, scid: Option<&str>, our_amount_msat: u64) -> ChannelEntry {
+    ChannelEntry {
+        peer_id: peer_id.to_string(),
+        short_channel_id: scid.map(str::to_string),
+        channel_id: None,
+        funding_txid: "ab".repeat(32),
+        funding_output: 0,
+        state: ChannelState::ChanneldNormal,
+        connected: true,
+        amount_msat: 1_000_000_000,
+        our_amount_msat,
+        liquidity: None,
+    }
+}
+
+/// Compare `actual` au fichier de référence `testdata/<name>`.
+/// `UPDATE_GOLDEN=1 cargo test` régénère les fichiers.
+pub fn assert_golden(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/visualization/testdata")
+        .join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("golden file {:?} unreadable: {}", path, e));
+    assert_eq!(actual, expected, "output differs from {:?}", path);
+}
+
+pub fn two_node_network() -> NetworkGraph {
+    let mut network = NetworkGraph::new();
+    let alice = network.add_node(ALICE, Some("alice"));
+    network.graph[alice].color = Some("3399ff".to_string());
+    network.graph[alice].capacity_msat = 1_500_000_000;
+    network.graph[alice].state = NodeState::Active;
+    network.add_node(BOB, None);
+    network.add_channel(ALICE, BOB, GraphChannel {
+        short_channel_id: Some("103x1x0".to_string()),
+        capacity_msat: 1_000_000_000,
+        state: Some(ChannelState::ChanneldNormal),
+        source: ChannelEnd {
+            balance_msat: Some(700_000_000),
+            reserve_msat: Some(10_000_000),
+            spendable_msat: Some(690_000_000),
+            policy: Some(ChannelPolicy {
+                active: true,
+                base_fee_msat: 1000,
+                fee_ppm: 10,
+                cltv_delta: 40,
+                htlc_minimum_msat: 1000,
+                htlc_maximum_msat: Some(990_000_000),
+                last_update: 1_700_000_000,
+            }),
+        },
+        target: ChannelEnd {
+            balance_msat: Some(300_000_000),
+            reserve_msat: Some(10_000_000),
+            spendable_msat: Some(290_000_000),
+            policy: None,
+        },
+        ..Default::default()
+    });
+    network.add_channel(ALICE, BOB, GraphChannel {
+        funding_outpoint: Some(format!("{}:1", "ab".repeat(32))),
+        capacity_msat: 500_000_000,
+        state: Some(ChannelState::ChanneldAwaitingLockin),
+        ..Default::default()
+    });
+    network.add_channel(BOB, ALICE, GraphChannel {
+        short_channel_id: Some("104x1x0".to_string()),
+        capacity_msat: 200_000_000,
+        state: Some(ChannelState::Onchain),
+        ..Default::default()
+    });
+    network.add_channel(ALICE, BOB, GraphChannel {
+        short_channel_id: Some("101x1x0".to_string()),
+        capacity_msat: 300_000_000,
+        state: Some(ChannelState::Closed),
+        closing_txid: Some("cd".repeat(32)),
+        ..Default::default()
+    });
+    network
+}
@@ -0,0 +1,93 @@
+use petgraph::visit::EdgeRef;
+use std::fmt::Write;
+
+use super::attributes::{
+    channel_attributes, node_attributes, node_display_color, AttributeValue, CHANNEL_ATTRIBUTES, NODE_ATTRIBUTES,
+};
+use super::style::{pen_width, ChannelKind};
+use super::svg::escape;
+use super::NetworkGraph;
+
+/// Écrit le graphe au format GEXF 1.3 pour Gephi : attributs typés, et
+/// couleurs et épaisseurs `viz:` reprises des autres rendus.
+pub(super) fn write_gexf(network: &NetworkGraph) -> String {
+    let graph = &network.graph;
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<gexf xmlns=\"http://gexf.net/1.3\" xmlns:viz=\"http://gexf.net/1.3/viz\" version=\"1.3\">\n");
+    let _ = writeln!(
+        xml,
+        "  <meta>\n    <creator>{} {}</creator>\n  </meta>",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+    xml.push_str("  <graph mode=\"static\" defaultedgetype=\"directed\">\n");
+    for (class, attributes) in [("node", NODE_ATTRIBUTES), ("edge", CHANNEL_ATTRIBUTES)] {
+        let _ = writeln!(xml, "    <attributes class=\"{}\">", class);
+        for (name, kind) in attributes {
+            let _ = writeln!(xml, "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>", name, name, kind.name());
+        }
+        xml.push_str("    </attributes>\n");
+    }
+
+    xml.push_str("    <nodes>\n");
+    for node in graph.node_weights() {
+        let _ = writeln!(xml, "      <node id=\"{}\" label=\"{}\">", escape(&node.id), escape(&node.display_name()));
+        write_attvalues(&mut xml, node_attributes(node));
+        let _ = writeln!(xml, "        {}", viz_color(&node_display_color(node)));
+        xml.push_str("      </node>\n");
+    }
+    xml.push_str("    </nodes>\n");
+
+    xml.push_str("    <edges>\n");
+    for edge in graph.edge_references() {
+        let channel = edge.weight();
+        let label = channel.short_channel_id.as_deref().unwrap_or_default();
+        let _ = writeln!(
+            xml,
+            "      <edge id=\"{}\" source=\"{}\" target=\"{}\" label=\"{}\">",
+            edge.id().index(),
+            escape(&graph[edge.source()].id),
+            escape(&graph[edge.target()].id),
+            escape(label)
+        );
+        write_attvalues(&mut xml, channel_attributes(channel));
+        let _ = writeln!(xml, "        {}", viz_color(ChannelKind::of(channel).style().color));
+        let _ = writeln!(xml, "        <viz:thickness value=\"{:.1}\"/>", pen_width(channel.capacity_msat));
+        xml.push_str("      </edge>\n");
+    }
+    xml.push_str("    </edges>\n");
+    xml.push_str("  </graph>\n");
+    xml.push_str("</gexf>\n");
+
+    xml
+}
+
+fn write_attvalues(xml: &mut String, values: Vec<(&'static str, AttributeValue)>) {
+    xml.push_str("        <attvalues>\n");
+    for (name, value) in values {
+        let _ = writeln!(xml, "          <attvalue for=\"{}\" value=\"{}\"/>", name, escape(&value.to_string()));
+    }
+    xml.push_str("        </attvalues>\n");
+}
+
+/// Élément `viz:color` d'une couleur `#rrggbb`.
+fn viz_color(color: &str) -> String {
+    let channel = |range| u8::from_str_radix(color.trim_start_matches('#').get(range).unwrap_or("0"), 16).unwrap_or(0);
+    format!("<viz:color r=\"{}\" g=\"{}\" b=\"{}\"/>", channel(0..2), channel(2..4), channel(4..6))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::fixtures::{assert_golden, two_node_network};
+
+    #[test]
+    fn two_nodes_match_golden_file() {
+        assert_golden("two_nodes.gexf", &two_node_network().to_gexf());
+    }
+
+    #[test]
+    fn colors_are_split_into_components() {
+        assert_eq!(viz_color("#3399ff"), "<viz:color r=\"51\" g=\"153\" b=\"255\"/>");
+    }
+}
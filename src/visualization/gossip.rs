@@ -0,0 +1,103 @@
+use super::{ChannelEnd, ChannelPolicy, GraphChannel, NetworkGraph, NodeState};
+use crate::lightning::{GossipChannel, GossipNode};
+
+impl NetworkGraph {
+    /// Intègre la vue gossip d'un nœud (`listnodes` et `listchannels`).
+    ///
+    /// Chaque direction d'un canal apporte la politique de relai de sa source ;
+    /// les deux directions sont fusionnées dans une seule arête via le
+    /// `short_channel_id`. Les nœuds interrogés directement gardent leur état.
+    pub fn update_from_gossip(&mut self, nodes: &[GossipNode], channels: &[GossipChannel]) {
+        for gossip_node in nodes {
+            let idx = self.add_node(&gossip_node.id, gossip_node.alias.as_deref());
+            let node = &mut self.graph[idx];
+            if gossip_node.color.is_some() {
+                node.color = gossip_node.color.clone();
+            }
+            if !gossip_node.addresses.is_empty() {
+                node.addresses = gossip_node.addresses.clone();
+            }
+            if node.state == NodeState::Unknown {
+                node.state = NodeState::Announced;
+            }
+        }
+
+        for channel in channels {
+            self.add_node(&channel.source, None);
+            self.add_node(&channel.destination, None);
+            self.add_channel(&channel.source, &channel.destination, GraphChannel {
+                short_channel_id: Some(channel.short_channel_id.clone()),
+                capacity_msat: channel.amount_msat,
+                source: ChannelEnd {
+                    policy: Some(ChannelPolicy {
+                        active: channel.active,
+                        base_fee_msat: channel.base_fee_msat,
+                        fee_ppm: channel.fee_ppm,
+                        cltv_delta: channel.cltv_delta,
+                        htlc_minimum_msat: channel.htlc_minimum_msat,
+                        htlc_maximum_msat: channel.htlc_maximum_msat,
+                        last_update: channel.last_update,
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        }
+
+        self.refresh_capacities();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::ChannelKey;
+
+    use crate::lightning::gossip_node;
+    use crate::visualization::fixtures::{ALICE, BOB, CAROL};
+
+    /// Direction de 2 000 000 sats annoncée avec `fee_ppm`.
+    fn direction(source: &str, destination: &str, scid: &str, fee_ppm: u32) -> GossipChannel {
+        GossipChannel { fee_ppm, ..crate::lightning::gossip_channel(source, destination, scid, 2_000_000) }
+    }
+
+    #[test]
+    fn both_directions_become_one_edge_with_two_policies() {
+        let mut network = NetworkGraph::new();
+        network.update_from_gossip(
+            &[gossip_node(ALICE, "alice"), gossip_node(BOB, "bob")],
+            &[
+                direction(ALICE, BOB, "103x1x0", 10),
+                direction(BOB, ALICE, "103x1x0", 20),
+                direction(BOB, CAROL, "104x1x0", 30),
+            ],
+        );
+
+        assert_eq!(network.node_count(), 3);
+        assert_eq!(network.channel_count(), 2);
+
+        let edge = network.channel_indices[&ChannelKey::ShortChannelId("103x1x0".to_string())];
+        let channel = &network.graph[edge];
+        assert_eq!(channel.source.policy.as_ref().unwrap().fee_ppm, 10);
+        assert_eq!(channel.target.policy.as_ref().unwrap().fee_ppm, 20);
+        assert!(channel.is_active());
+
+        let carol = network.node_indices[CAROL];
+        assert_eq!(network.graph[carol].state, NodeState::Unknown);
+        let bob = network.node_indices[BOB];
+        assert_eq!(network.graph[bob].state, NodeState::Announced);
+        assert_eq!(network.graph[bob].capacity_msat, 4_000_000_000);
+    }
+
+    #[test]
+    fn gossip_keeps_state_of_queried_nodes() {
+        let mut network = NetworkGraph::new();
+        let idx = network.add_node(ALICE, Some("alice"));
+        network.graph[idx].state = NodeState::Active;
+
+        network.update_from_gossip(&[gossip_node(ALICE, "alice-gossip")], &[]);
+
+        assert_eq!(network.graph[idx].state, NodeState::Active);
+        assert_eq!(network.graph[idx].alias.as_deref(), Some("alice-gossip"));
+    }
+}
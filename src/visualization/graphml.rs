@@ -0,0 +1,74 @@
+use petgraph::visit::EdgeRef;
+use std::fmt::Write;
+
+use super::attributes::{channel_attributes, node_attributes, CHANNEL_ATTRIBUTES, NODE_ATTRIBUTES};
+use super::svg::escape;
+use super::NetworkGraph;
+
+/// Écrit le graphe au format GraphML (yEd, Gephi, NetworkX...). Les clés sont
+/// préfixées par leur domaine (`n_`, `e_`), les nœuds identifiés par leur clé publique.
+pub(super) fn write_graphml(network: &NetworkGraph) -> String {
+    let graph = &network.graph;
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" ");
+    xml.push_str("xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" ");
+    xml.push_str("xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns ");
+    xml.push_str("http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n");
+    for (domain, prefix, attributes) in [("node", "n", NODE_ATTRIBUTES), ("edge", "e", CHANNEL_ATTRIBUTES)] {
+        for (name, kind) in attributes {
+            let _ = writeln!(
+                xml,
+                "  <key id=\"{}_{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                prefix,
+                name,
+                domain,
+                name,
+                kind.name()
+            );
+        }
+    }
+
+    xml.push_str("  <graph id=\"lightning\" edgedefault=\"directed\">\n");
+    for node in graph.node_weights() {
+        let _ = writeln!(xml, "    <node id=\"{}\">", escape(&node.id));
+        for (name, value) in node_attributes(node) {
+            let _ = writeln!(xml, "      <data key=\"n_{}\">{}</data>", name, escape(&value.to_string()));
+        }
+        xml.push_str("    </node>\n");
+    }
+    for edge in graph.edge_references() {
+        let _ = writeln!(
+            xml,
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">",
+            edge.id().index(),
+            escape(&graph[edge.source()].id),
+            escape(&graph[edge.target()].id)
+        );
+        for (name, value) in channel_attributes(edge.weight()) {
+            let _ = writeln!(xml, "      <data key=\"e_{}\">{}</data>", name, escape(&value.to_string()));
+        }
+        xml.push_str("    </edge>\n");
+    }
+    xml.push_str("  </graph>\n");
+    xml.push_str("</graphml>\n");
+
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::visualization::fixtures::{assert_golden, two_node_network};
+    use crate::visualization::NetworkGraph;
+
+    #[test]
+    fn two_nodes_match_golden_file() {
+        assert_golden("two_nodes.graphml", &two_node_network().to_graphml());
+    }
+
+    #[test]
+    fn alias_is_escaped() {
+        let mut network = NetworkGraph::new();
+        network.add_node("02aa", Some("<b>&co"));
+        assert!(network.to_graphml().contains("<data key=\"n_alias\">&lt;b&gt;&amp;co</data>"));
+    }
+}
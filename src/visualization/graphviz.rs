@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Convertit une source DOT en image (`png`, `svg`, `pdf`...) avec la commande `dot` de Graphviz.
+pub fn render_dot(dot_source: &str, format: &str, output: &Path) -> Result<()> {
+    run_graphviz("dot", dot_source, format, output)
+}
+
+fn run_graphviz(program: &str, dot_source: &str, format: &str, output: &Path) -> Result<()> {
+    let mut child = match Command::new(program)
+        .arg(format!("-T{}", format))
+        .arg("-o")
+        .arg(output)
+        .stdin(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(anyhow::anyhow!(
+                "Graphviz (`dot`) est introuvable, or il n'est nécessaire que pour convertir un fichier DOT. \
+                 Sans Graphviz, `snapshot --format {0}` (ou svg/png/pdf) dessine directement le graphe, et \
+                 `render --format {0}` convertit un snapshot JSON (`snapshot --format json`) ou un SVG",
+                format
+            ))
+        }
+        Err(e) => return Err(e).context("Impossible de lancer `dot`"),
+    };
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("stdin de `dot` indisponible"))?
+        .write_all(dot_source.as_bytes())?;
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("`dot -T{}` a échoué ({})", format, status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_graphviz_points_to_the_in_process_formats() {
+        let output = std::env::temp_dir().join(format!("lightning_viz_{}_missing_dot.png", std::process::id()));
+        let message = run_graphviz("lightning_viz_no_such_dot", "digraph {}", "png", &output).unwrap_err().to_string();
+        assert!(message.contains("`snapshot --format png`") && message.contains("svg/png/pdf"), "{}", message);
+        assert!(message.contains("snapshot JSON"), "{}", message);
+        assert!(!output.exists());
+    }
+}
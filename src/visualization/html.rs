@@ -0,0 +1,54 @@
+use std::fmt::Write;
+
+const STYLE: &str = include_str!("assets/viewer.css");
+const SCRIPT: &str = include_str!("assets/viewer.js");
+
+/// Page HTML autonome à partir d'un SVG écrit par `NetworkGraph::to_svg` : le
+/// dessin est en ligne, avec la feuille de style et le script de navigation
+/// (zoom, déplacement, infobulles, recherche), sans aucune ressource externe.
+pub fn svg_to_html(svg: &str) -> String {
+    // La déclaration XML n'a pas sa place au milieu d'un document HTML
+    let svg = match svg.trim_start().strip_prefix("<?xml") {
+        Some(rest) => rest.split_once("?>").map(|(_, body)| body).unwrap_or(rest).trim_start(),
+        None => svg.trim_start(),
+    };
+
+    let mut html = String::from("<!DOCTYPE html>\n<html lang=\"fr\">\n<head>\n");
+    html.push_str("<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Réseau Lightning</title>\n");
+    let _ = writeln!(html, "<style>\n{}</style>", STYLE);
+    html.push_str("</head>\n<body>\n");
+    html.push_str("<header>\n");
+    html.push_str("<h1>Réseau Lightning</h1>\n");
+    html.push_str("<input id=\"search\" type=\"search\" placeholder=\"Alias, clé publique ou short channel id\">\n");
+    html.push_str("<span id=\"matches\"></span>\n");
+    html.push_str("<button id=\"reset\" type=\"button\">Recentrer</button>\n");
+    html.push_str("</header>\n");
+    let _ = writeln!(html, "<main id=\"viewport\">\n{}</main>", svg);
+    html.push_str("<div id=\"tooltip\" hidden></div>\n");
+    let _ = writeln!(html, "<script>\n{}</script>", SCRIPT);
+    html.push_str("</body>\n</html>\n");
+
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::visualization::fixtures::{two_node_network, ALICE};
+
+    #[test]
+    fn page_is_self_contained() {
+        let html = two_node_network().to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("<?xml"));
+        assert!(!html.contains("src=\"") && !html.contains("href=\""), "external resource in {}", html);
+        assert_eq!(html.matches("<svg").count(), 1);
+    }
+
+    #[test]
+    fn nodes_and_channels_are_searchable() {
+        let html = two_node_network().to_html();
+        assert!(html.contains(&format!("data-search=\"alice {}\"", ALICE)), "{}", html);
+        assert!(html.contains("data-search=\"103x1x0\""));
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use petgraph::visit::EdgeRef;
+use serde::{Deserialize, Serialize};
+
+use super::{ChannelEnd, GraphChannel, NetworkGraph, NodeState};
+use crate::lightning::ChannelState;
+
+/// Version du schéma JSON, à incrémenter à chaque changement incompatible.
+/// Un document d'une version plus récente est refusé plutôt que mal lu.
+const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Document JSON d'un snapshot : le graphe, daté, avec l'outil qui l'a produit.
+#[derive(Debug, Serialize, Deserialize)]
+struct GraphDocument {
+    version: u32,
+    generated_at: DateTime<Utc>,
+    metadata: Metadata,
+    nodes: Vec<NodeRecord>,
+    channels: Vec<ChannelRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Metadata {
+    generator: String,
+    node_count: usize,
+    channel_count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NodeRecord {
+    id: String,
+    alias: Option<String>,
+    color: Option<String>,
+    #[serde(default)]
+    addresses: Vec<String>,
+    capacity_msat: u64,
+    state: NodeState,
+}
+
+/// Canal de `source` vers `target`, identifiés par leur clé publique.
+#[derive(Debug, Serialize, Deserialize)]
+struct ChannelRecord {
+    source: String,
+    target: String,
+    short_channel_id: Option<String>,
+    funding_outpoint: Option<String>,
+    capacity_msat: u64,
+    state: Option<ChannelState>,
+    closing_txid: Option<String>,
+    #[serde(default)]
+    source_end: ChannelEnd,
+    #[serde(default)]
+    target_end: ChannelEnd,
+}
+
+/// Seul champ lu avant le reste du document, pour refuser une version inconnue
+/// avec un message clair plutôt qu'une erreur de champ.
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+pub(super) fn write_json(network: &NetworkGraph, generated_at: DateTime<Utc>) -> Result<String> {
+    let graph = &network.graph;
+    let document = GraphDocument {
+        version: JSON_SCHEMA_VERSION,
+        generated_at,
+        metadata: Metadata {
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            node_count: graph.node_count(),
+            channel_count: graph.edge_count(),
+        },
+        nodes: graph
+            .node_weights()
+            .map(|node| NodeRecord {
+                id: node.id.clone(),
+                alias: node.alias.clone(),
+                color: node.color.clone(),
+                addresses: node.addresses.clone(),
+                capacity_msat: node.capacity_msat,
+                state: node.state,
+            })
+            .collect(),
+        channels: graph
+            .edge_references()
+            .map(|edge| {
+                let channel = edge.weight();
+                ChannelRecord {
+                    source: graph[edge.source()].id.clone(),
+                    target: graph[edge.target()].id.clone(),
+                    short_channel_id: channel.short_channel_id.clone(),
+                    funding_outpoint: channel.funding_outpoint.clone(),
+                    capacity_msat: channel.capacity_msat,
+                    state: channel.state,
+                    closing_txid: channel.closing_txid.clone(),
+                    source_end: channel.source.clone(),
+                    target_end: channel.target.clone(),
+                }
+            })
+            .collect(),
+    };
+    let mut json = serde_json::to_string_pretty(&document)?;
+    json.push('\n');
+    Ok(json)
+}
+
+pub(super) fn read_json(source: &str) -> Result<NetworkGraph> {
+    let Version { version } = serde_json::from_str(source).context("Snapshot JSON invalide")?;
+    if version > JSON_SCHEMA_VERSION {
+        return Err(anyhow::anyhow!(
+            "Snapshot JSON en version {} : cette version de l'outil lit jusqu'à la version {}",
+            version,
+            JSON_SCHEMA_VERSION
+        ));
+    }
+    let document: GraphDocument = serde_json::from_str(source).context("Snapshot JSON invalide")?;
+
+    let mut network = NetworkGraph::new();
+    for record in document.nodes {
+        let idx = network.add_node(&record.id, record.alias.as_deref());
+        let node = &mut network.graph[idx];
+        node.color = record.color;
+        node.addresses = record.addresses;
+        node.capacity_msat = record.capacity_msat;
+        node.state = record.state;
+    }
+    for record in document.channels {
+        let channel = GraphChannel {
+            short_channel_id: record.short_channel_id,
+            funding_outpoint: record.funding_outpoint,
+            capacity_msat: record.capacity_msat,
+            state: record.state,
+            closing_txid: record.closing_txid,
+            source: record.source_end,
+            target: record.target_end,
+        };
+        network
+            .add_channel(&record.source, &record.target, channel)
+            .with_context(|| format!("Canal {} -> {} : nœud absent du snapshot", record.source, record.target))?;
+    }
+    Ok(network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::fixtures::{assert_golden, two_node_network};
+
+    fn generated_at() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn two_nodes_match_golden_file() {
+        assert_golden("two_nodes.json", &write_json(&two_node_network(), generated_at()).unwrap());
+    }
+
+    #[test]
+    fn snapshot_round_trips_to_the_same_graph() {
+        let network = two_node_network();
+        let restored = NetworkGraph::from_json(&network.to_json().unwrap()).unwrap();
+        assert_eq!(restored.to_dot(), network.to_dot());
+    }
+
+    #[test]
+    fn newer_schema_is_rejected() {
+        let json = write_json(&NetworkGraph::new(), generated_at()).unwrap().replace("\"version\": 1", "\"version\": 2");
+        let err = NetworkGraph::from_json(&json).err().unwrap();
+        assert!(err.to_string().contains("version 2"), "{}", err);
+    }
+}
@@ -0,0 +1,192 @@
+use petgraph::visit::EdgeRef;
+use std::collections::BTreeSet;
+
+use super::NetworkGraph;
+
+/// Itérations de l'algorithme de Fruchterman-Reingold.
+const ITERATIONS: usize = 300;
+/// Marge autour des nœuds, en pixels.
+pub const MARGIN: f64 = 20.0;
+/// Taille de police des étiquettes et largeur moyenne d'un caractère, pour
+/// estimer la taille des boîtes sans moteur de rendu de texte.
+pub const FONT_SIZE: f64 = 12.0;
+pub const CHAR_WIDTH: f64 = 6.6;
+pub const LINE_HEIGHT: f64 = 15.0;
+/// Taille de police des étiquettes des canaux, comme `fontsize` des arêtes en DOT.
+pub const EDGE_FONT_SIZE: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+/// Position et taille d'un nœud, en pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeBox {
+    pub center: Point,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl NodeBox {
+    /// Point du bord de la boîte sur la demi-droite qui va du centre vers `toward`.
+    pub fn border_towards(&self, toward: Point) -> Point {
+        let (dx, dy) = (toward.x - self.center.x, toward.y - self.center.y);
+        if dx == 0.0 && dy == 0.0 {
+            return self.center;
+        }
+        let scale_x = if dx == 0.0 { f64::INFINITY } else { self.width / 2.0 / dx.abs() };
+        let scale_y = if dy == 0.0 { f64::INFINITY } else { self.height / 2.0 / dy.abs() };
+        let scale = scale_x.min(scale_y);
+        Point::new(self.center.x + dx * scale, self.center.y + dy * scale)
+    }
+}
+
+/// Disposition d'un graphe, indexée comme ses nœuds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub nodes: Vec<NodeBox>,
+}
+
+/// Taille de la boîte d'une étiquette de plusieurs lignes.
+pub fn text_size(text: &str, font_size: f64) -> (f64, f64) {
+    let scale = font_size / FONT_SIZE;
+    let lines = text.lines().count().max(1) as f64;
+    let longest = text.lines().map(|line| line.chars().count()).max().unwrap_or(0) as f64;
+    (longest * CHAR_WIDTH * scale, lines * LINE_HEIGHT * scale)
+}
+
+/// Disposition par forces (Fruchterman-Reingold) : les nœuds se repoussent,
+/// les canaux les attirent. Les positions de départ forment une grille, sans
+/// hasard ni trigonométrie, pour qu'un même graphe donne toujours la même image.
+pub fn force_directed(network: &NetworkGraph) -> Layout {
+    let graph = &network.graph;
+    let sizes: Vec<(f64, f64)> = graph
+        .node_weights()
+        .map(|node| {
+            let (width, height) = text_size(&node.label(), FONT_SIZE);
+            (width + 20.0, height + 10.0)
+        })
+        .collect();
+    let count = sizes.len();
+    if count == 0 {
+        return Layout { nodes: Vec::new() };
+    }
+
+    // Distance idéale entre deux nœuds : assez pour les boîtes et, entre elles,
+    // les étiquettes des canaux
+    let largest = sizes.iter().map(|(w, h)| w.max(*h)).fold(0.0, f64::max);
+    let widest_label = graph.edge_weights().map(|channel| text_size(&channel.label(), EDGE_FONT_SIZE).0).fold(0.0, f64::max);
+    let k = (largest * 1.8).max(largest + widest_label + 40.0).max(180.0);
+    let columns = (count as f64).sqrt().ceil() as usize;
+    let mut positions: Vec<Point> = (0..count)
+        .map(|i| {
+            let (row, column) = (i / columns, i % columns);
+            let shift = if row % 2 == 1 { k / 2.0 } else { 0.0 };
+            Point::new(column as f64 * k + shift, row as f64 * k)
+        })
+        .collect();
+    // Des canaux parallèles n'attirent pas davantage leurs nœuds qu'un seul
+    let edges: BTreeSet<(usize, usize)> = graph
+        .edge_references()
+        .map(|edge| (edge.source().index(), edge.target().index()))
+        .filter(|(source, target)| source != target)
+        .map(|(source, target)| (source.min(target), source.max(target)))
+        .collect();
+
+    let initial_temperature = k;
+    for iteration in 0..ITERATIONS {
+        let mut displacement = vec![Point::new(0.0, 0.0); count];
+        for i in 0..count {
+            for j in (i + 1)..count {
+                let (dx, dy) = (positions[i].x - positions[j].x, positions[i].y - positions[j].y);
+                let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+                let force = k * k / distance;
+                let (fx, fy) = (dx / distance * force, dy / distance * force);
+                displacement[i].x += fx;
+                displacement[i].y += fy;
+                displacement[j].x -= fx;
+                displacement[j].y -= fy;
+            }
+        }
+        for &(source, target) in &edges {
+            let (dx, dy) = (positions[source].x - positions[target].x, positions[source].y - positions[target].y);
+            let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+            let force = distance * distance / k;
+            let (fx, fy) = (dx / distance * force, dy / distance * force);
+            displacement[source].x -= fx;
+            displacement[source].y -= fy;
+            displacement[target].x += fx;
+            displacement[target].y += fy;
+        }
+
+        // Refroidissement linéaire : les déplacements sont de plus en plus bornés
+        let temperature = initial_temperature * (1.0 - iteration as f64 / ITERATIONS as f64);
+        for (position, moved) in positions.iter_mut().zip(&displacement) {
+            let length = (moved.x * moved.x + moved.y * moved.y).sqrt();
+            if length > 0.0 {
+                let step = length.min(temperature);
+                position.x += moved.x / length * step;
+                position.y += moved.y / length * step;
+            }
+        }
+    }
+
+    let min_x = positions.iter().zip(&sizes).map(|(p, (w, _))| p.x - w / 2.0).fold(f64::INFINITY, f64::min);
+    let min_y = positions.iter().zip(&sizes).map(|(p, (_, h))| p.y - h / 2.0).fold(f64::INFINITY, f64::min);
+    let nodes: Vec<NodeBox> = positions
+        .iter()
+        .zip(&sizes)
+        .map(|(position, &(width, height))| NodeBox {
+            center: Point::new(round(position.x - min_x + MARGIN), round(position.y - min_y + MARGIN)),
+            width,
+            height,
+        })
+        .collect();
+    Layout { nodes }
+}
+
+/// Arrondit au dixième de pixel, ce que garde le SVG.
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connected_nodes_do_not_overlap() {
+        let mut network = NetworkGraph::new();
+        for id in ["02aa", "03bb", "02cc"] {
+            network.add_node(id, None);
+        }
+        network.add_channel("02aa", "03bb", Default::default());
+        network.add_channel("03bb", "02cc", Default::default());
+
+        let layout = force_directed(&network);
+        assert_eq!(layout, force_directed(&network), "layout is deterministic");
+        for (i, a) in layout.nodes.iter().enumerate() {
+            assert!(a.center.x - a.width / 2.0 >= MARGIN - 0.1 && a.center.y - a.height / 2.0 >= MARGIN - 0.1);
+            for b in &layout.nodes[i + 1..] {
+                let apart_x = (a.center.x - b.center.x).abs() >= (a.width + b.width) / 2.0;
+                let apart_y = (a.center.y - b.center.y).abs() >= (a.height + b.height) / 2.0;
+                assert!(apart_x || apart_y, "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn border_point_lies_on_the_box() {
+        let node = NodeBox { center: Point::new(100.0, 100.0), width: 80.0, height: 40.0 };
+        assert_eq!(node.border_towards(Point::new(300.0, 100.0)), Point::new(140.0, 100.0));
+        assert_eq!(node.border_towards(Point::new(100.0, 0.0)), Point::new(100.0, 80.0));
+    }
+}
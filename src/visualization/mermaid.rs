@@ -0,0 +1,101 @@
+use petgraph::visit::EdgeRef;
+use std::fmt::Write;
+
+use super::style::{first_color, node_fill, node_stroke, pen_width, ChannelKind, LineStyle};
+use super::NetworkGraph;
+
+/// Écrit le graphe en `flowchart` Mermaid : nœuds étiquetés par leur alias,
+/// canaux par leur capacité, couleurs et tracés repris du rendu DOT.
+pub(super) fn write_mermaid(network: &NetworkGraph) -> String {
+    let graph = &network.graph;
+    let mut mermaid = String::from("flowchart LR\n");
+
+    for state in network.legend().nodes {
+        let _ = writeln!(
+            mermaid,
+            "    classDef {} fill:{},stroke:#4c566a,color:#2e3440",
+            state.name(),
+            first_color(node_fill(state))
+        );
+    }
+    for idx in graph.node_indices() {
+        let node = &graph[idx];
+        let _ = writeln!(
+            mermaid,
+            "    n{}[\"{}\"]:::{}",
+            idx.index(),
+            escape(&node.display_name()),
+            node.state.name()
+        );
+        if let Some(color) = node_stroke(node) {
+            let _ = writeln!(mermaid, "    style n{} stroke:{},stroke-width:2px", idx.index(), color);
+        }
+    }
+
+    // `linkStyle` désigne les arêtes par leur ordre de déclaration
+    for (position, edge) in graph.edge_references().enumerate() {
+        let channel = edge.weight();
+        let style = ChannelKind::of(channel).style();
+        let arrow = match style.line {
+            LineStyle::Solid => "-->",
+            LineStyle::Dashed | LineStyle::Dotted => "-.->",
+            LineStyle::Bold => "==>",
+        };
+        let _ = writeln!(
+            mermaid,
+            "    n{} {}|\"{}\"| n{}",
+            edge.source().index(),
+            arrow,
+            escape(&channel.short_label()),
+            edge.target().index()
+        );
+        let dash = style.line.dasharray().map(|dash| format!(",stroke-dasharray:{}", dash)).unwrap_or_default();
+        let _ = writeln!(
+            mermaid,
+            "    linkStyle {} stroke:{},stroke-width:{:.0}px{}",
+            position,
+            style.color,
+            pen_width(channel.capacity_msat),
+            dash
+        );
+    }
+
+    mermaid
+}
+
+/// Échappe un libellé entre guillemets avec les entités Mermaid ; les retours
+/// à la ligne deviennent des `<br/>`.
+fn escape(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => escaped.push_str("#quot;"),
+            '#' => escaped.push_str("#35;"),
+            '<' => escaped.push_str("#lt;"),
+            '>' => escaped.push_str("#gt;"),
+            '\n' => escaped.push_str("<br/>"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::fixtures::{assert_golden, two_node_network, ALICE};
+
+    #[test]
+    fn two_nodes_match_golden_file() {
+        assert_golden("two_nodes.mmd", &two_node_network().to_mermaid());
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        let mut network = NetworkGraph::new();
+        network.add_node(ALICE, Some("\"al#ice\"\n<node>"));
+        let mermaid = network.to_mermaid();
+        assert!(mermaid.contains("n0[\"#quot;al#35;ice#quot;<br/>#lt;node#gt;\"]"), "{}", mermaid);
+    }
+}
@@ -1,130 +1,582 @@
-use petgraph::graph::{Graph, NodeIndex};
-use petgraph::dot::{Dot, Config};
-use std::collections::HashMap;
-use serde_json::Value;
-use anyhow::Result;
-
-pub struct NetworkGraph {
-    graph: Graph<String, String>,
-    node_indices: HashMap<String, NodeIndex>,
-    node_capacities: HashMap<String, u64>,
-    node_states: HashMap<String, String>,
-}
-
-impl NetworkGraph {
-    pub fn new() -> Self {
-        Self {
-            graph: Graph::new(),
-            node_indices: HashMap::new(),
-            node_capacities: HashMap::new(),
-            node_states: HashMap::new(),
-        }
-    }
-
-    pub fn add_node(&mut self, node_id: &str, alias: &str) -> NodeIndex {
-        if let Some(&idx) = self.node_indices.get(node_id) {
-            return idx;
-        }
-        let capacity = self.node_capacities.get(node_id).unwrap_or(&0);
-        let unknown = "unknown".to_string();
-        let state = self.node_states.get(node_id).unwrap_or(&unknown);
-        let node_label = format!(
-            "{}\n({})\nCapacité: {} sats\nÉtat: {}",
-            alias,
-            node_id.chars().take(8).collect::<String>(),
-            capacity,
-            state
-        );
-        let idx = self.graph.add_node(node_label);
-        self.node_indices.insert(node_id.to_string(), idx);
-        idx
-    }
-
-    pub fn add_channel(&mut self, from_id: &str, to_id: &str, capacity: &str) {
-        if let (Some(&from_idx), Some(&to_idx)) = (
-            self.node_indices.get(from_id),
-            self.node_indices.get(to_id)
-        ) {
-            let edge_label = format!(
-                "Capacité: {}\nÉtat: actif",
-                capacity
-            );
-            self.graph.add_edge(from_idx, to_idx, edge_label);
-        }
-    }
-
-    pub fn to_dot(&self) -> String {
-        let mut dot = String::from("digraph {\n");
-        dot.push_str("    rankdir=LR;\n");
-        dot.push_str("    splines=curved;\n");
-        dot.push_str("    bgcolor=\"#ffffff\";\n");
-        dot.push_str("    node [\n");
-        dot.push_str("        style=\"filled\",\n");
-        dot.push_str("        gradientangle=270,\n");
-        dot.push_str("        fillcolor=\"#88c0d0:#5e81ac\",\n");
-        dot.push_str("        shape=\"box\",\n");
-        dot.push_str("        rounded=true,\n");
-        dot.push_str("        fontname=\"Arial\",\n");
-        dot.push_str("        fontsize=12\n");
-        dot.push_str("    ];\n");
-        dot.push_str("    edge [\n");
-        dot.push_str("        color=\"#a3be8c\",\n");
-        dot.push_str("        penwidth=2.0,\n");
-        dot.push_str("        arrowsize=0.8\n");
-        dot.push_str("    ];\n");
-        
-        dot.push_str(&format!("{:?}", Dot::with_config(&self.graph, &[
-            Config::NodeIndexLabel,
-            Config::EdgeNoLabel,
-            Config::GraphContentOnly,
-        ])));
-        
-        dot.push_str("\n    subgraph cluster_legend {\n");
-        dot.push_str("        label=\"Légende\";\n");
-        dot.push_str("        node [shape=none];\n");
-        dot.push_str("        legend [label=<\n");
-        dot.push_str("            <table border=\"0\">\n");
-        dot.push_str("                <tr><td>Nœud actif</td></tr>\n");
-        dot.push_str("                <tr><td>Canal ouvert</td></tr>\n");
-        dot.push_str("            </table>\n");
-        dot.push_str("        >];\n");
-        dot.push_str("    }\n");
-        dot.push_str("}\n");
-        
-        dot
-    }
-
-    pub fn update_from_node_info(&mut self, node_info: &Value, channels: &Value) -> Result<()> {
-        let node_id = node_info["result"]["id"].as_str()
-            .ok_or_else(|| anyhow::anyhow!("Node ID not found"))?;
-        let alias = node_info["result"]["alias"].as_str()
-            .ok_or_else(|| anyhow::anyhow!("Node alias not found"))?;
-
-        if let Some(channel_list) = channels["result"]["channels"].as_array() {
-            let total_capacity: u64 = channel_list.iter()
-                .filter_map(|c| c["amount_msat"].as_str())
-                .filter_map(|amount| amount.parse::<u64>().ok())
-                .sum();
-            self.node_capacities.insert(node_id.to_string(), total_capacity);
-            self.node_states.insert(node_id.to_string(), "actif".to_string());
-        }
-
-        self.add_node(node_id, alias);
-
-        if let Some(channel_list) = channels["result"]["channels"].as_array() {
-            for channel in channel_list {
-                if let (Some(peer_id), Some(capacity)) = (
-                    channel["peer_id"].as_str(),
-                    channel["amount_msat"].as_str()
-                ) {
-                    if !self.node_indices.contains_key(peer_id) {
-                        self.add_node(peer_id, "Unknown");
-                    }
-                    self.add_channel(node_id, peer_id, capacity);
-                }
-            }
-        }
-
-        Ok(())
-    }
-} 
\ No newline at end of file
+use petgraph::graph::{EdgeIndex, Graph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use anyhow::Result;
+
+use crate::lightning::{ChannelState, ClosedChannel, Funds, LightningBackend, NodeInfo};
+
+mod attributes;
+mod dot;
+#[cfg(test)]
+pub mod fixtures;
+mod gexf;
+mod gossip;
+mod graphml;
+mod graphviz;
+mod html;
+mod json;
+mod layout;
+mod mermaid;
+mod plantuml;
+mod raster;
+mod style;
+mod svg;
+mod terminal;
+
+pub use graphviz::render_dot;
+pub use html::svg_to_html;
+pub use raster::{svg_to_pdf, svg_to_png};
+pub use style::ChannelKind;
+pub use terminal::{terminal_color, TerminalGraph};
+
+/// État d'un nœud dans le graphe : interrogé directement, connu par le
+/// gossip, ou seulement vu comme pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeState {
+    Active,
+    Announced,
+    Unknown,
+}
+
+impl NodeState {
+    /// Nom stable de l'état, celui du JSON, pour les identifiants et les exports.
+    pub fn name(self) -> &'static str {
+        match self {
+            NodeState::Active => "active",
+            NodeState::Announced => "announced",
+            NodeState::Unknown => "unknown",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NodeState::Active => "actif",
+            NodeState::Announced => "annoncé",
+            NodeState::Unknown => "inconnu",
+        }
+    }
+}
+
+/// Nœud du graphe, identifié par sa clé publique.
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+    pub id: String,
+    pub alias: Option<String>,
+    /// Couleur annoncée par le nœud, au format `rrggbb`.
+    pub color: Option<String>,
+    /// Adresses annoncées, au format `hôte:port`.
+    pub addresses: Vec<String>,
+    pub capacity_msat: u64,
+    pub state: NodeState,
+}
+
+impl GraphNode {
+    pub fn short_id(&self) -> String {
+        self.id.chars().take(8).collect()
+    }
+
+    pub fn display_name(&self) -> String {
+        match &self.alias {
+            Some(alias) if !alias.is_empty() => alias.clone(),
+            _ => self.short_id(),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        format!(
+            "{}\n({})\nCapacité: {} sats\nÉtat: {}",
+            self.display_name(),
+            self.short_id(),
+            self.capacity_msat / 1000,
+            self.state.label()
+        )
+    }
+}
+
+/// Identifiant sous lequel un canal peut être retrouvé dans le graphe.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ChannelKey {
+    ShortChannelId(String),
+    /// Sortie de financement, au format `txid:vout`.
+    FundingOutpoint(String),
+}
+
+/// Politique de relai annoncée par une extrémité d'un canal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelPolicy {
+    pub active: bool,
+    pub base_fee_msat: u32,
+    pub fee_ppm: u32,
+    pub cltv_delta: u32,
+    pub htlc_minimum_msat: u64,
+    pub htlc_maximum_msat: Option<u64>,
+    pub last_update: u32,
+}
+
+/// Informations propres à une extrémité d'un canal.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelEnd {
+    /// Solde de cette extrémité, connu seulement si l'un des nœuds a été interrogé.
+    pub balance_msat: Option<u64>,
+    /// Solde que cette extrémité doit conserver dans le canal.
+    pub reserve_msat: Option<u64>,
+    /// Montant que cette extrémité peut envoyer par le canal.
+    pub spendable_msat: Option<u64>,
+    /// Politique de relai de cette extrémité, connue par le gossip.
+    pub policy: Option<ChannelPolicy>,
+}
+
+impl ChannelEnd {
+    fn merge(&mut self, other: ChannelEnd) {
+        self.balance_msat = other.balance_msat.or(self.balance_msat);
+        self.reserve_msat = other.reserve_msat.or(self.reserve_msat);
+        self.spendable_msat = other.spendable_msat.or(self.spendable_msat);
+        self.policy = other.policy.or(self.policy.take());
+    }
+}
+
+/// Canal entre deux nœuds du graphe. `source` et `target` décrivent les
+/// extrémités dans le sens de l'arête.
+#[derive(Debug, Clone, Default)]
+pub struct GraphChannel {
+    pub short_channel_id: Option<String>,
+    pub funding_outpoint: Option<String>,
+    pub capacity_msat: u64,
+    /// État vu par l'un des nœuds du canal, `None` s'il n'est connu que par le gossip.
+    pub state: Option<ChannelState>,
+    /// Transaction de fermeture, connue une fois le canal fermé.
+    pub closing_txid: Option<String>,
+    pub source: ChannelEnd,
+    pub target: ChannelEnd,
+}
+
+impl GraphChannel {
+    pub fn keys(&self) -> Vec<ChannelKey> {
+        let mut keys = Vec::new();
+        if let Some(scid) = &self.short_channel_id {
+            keys.push(ChannelKey::ShortChannelId(scid.clone()));
+        }
+        if let Some(outpoint) = &self.funding_outpoint {
+            keys.push(ChannelKey::FundingOutpoint(outpoint.clone()));
+        }
+        keys
+    }
+
+    /// Un canal connu seulement par le gossip est actif si l'une de ses
+    /// directions est annoncée active.
+    pub fn is_active(&self) -> bool {
+        match self.state {
+            Some(state) => state.is_normal(),
+            None => self.policies().any(|policy| policy.active),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state == Some(ChannelState::Closed)
+    }
+
+    /// Part du solde de `source` dans le canal, entre 0 et 1, quand les deux
+    /// soldes sont connus.
+    pub fn local_ratio(&self) -> Option<f64> {
+        let (Some(source), Some(target)) = (self.source.balance_msat, self.target.balance_msat) else {
+            return None;
+        };
+        let total = source + target;
+        (total > 0).then(|| source as f64 / total as f64)
+    }
+
+    /// `local_ratio` des seuls canaux utilisables, dont les renderers
+    /// découpent l'arête selon les soldes.
+    pub fn liquidity_ratio(&self) -> Option<f64> {
+        self.local_ratio().filter(|_| self.state.is_some_and(ChannelState::is_normal))
+    }
+
+    fn policies(&self) -> impl Iterator<Item = &ChannelPolicy> {
+        self.source.policy.iter().chain(self.target.policy.iter())
+    }
+
+    pub fn state_label(&self) -> &'static str {
+        ChannelKind::of(self).label()
+    }
+
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        if let Some(scid) = &self.short_channel_id {
+            label.push_str(scid);
+            label.push('\n');
+        }
+        label.push_str(&format!("Capacité: {} sats\nÉtat: {}", self.capacity_msat / 1000, self.state_label()));
+        if let (Some(source), Some(target)) = (self.source.balance_msat, self.target.balance_msat) {
+            label.push_str(&format!("\nSoldes: {} / {} sats", source / 1000, target / 1000));
+        }
+        if let (Some(source), Some(target)) = (self.source.spendable_msat, self.target.spendable_msat) {
+            label.push_str(&format!("\nEnvoyable: {} / {} sats", source / 1000, target / 1000));
+        }
+        if let Some(txid) = &self.closing_txid {
+            label.push_str(&format!("\nFermeture: {}…", &txid[..txid.len().min(8)]));
+        }
+        for (arrow, end) in [("→", &self.source), ("←", &self.target)] {
+            if let Some(policy) = &end.policy {
+                label.push_str(&format!(
+                    "\n{} {} msat + {} ppm, cltv {}",
+                    arrow, policy.base_fee_msat, policy.fee_ppm, policy.cltv_delta
+                ));
+            }
+        }
+        label
+    }
+
+    /// Étiquette courte des diagrammes : short channel id et capacité.
+    pub fn short_label(&self) -> String {
+        let capacity = format!("{} sats", self.capacity_msat / 1000);
+        match &self.short_channel_id {
+            Some(scid) => format!("{}\n{}", scid, capacity),
+            None => capacity,
+        }
+    }
+
+    /// Fusionne une observation plus récente du même canal, exprimée dans le même sens.
+    fn merge(&mut self, other: GraphChannel) {
+        self.short_channel_id = other.short_channel_id.or(self.short_channel_id.take());
+        self.funding_outpoint = other.funding_outpoint.or(self.funding_outpoint.take());
+        if other.capacity_msat > 0 {
+            self.capacity_msat = other.capacity_msat;
+        }
+        // Un canal fermé ne redevient pas ouvert parce qu'un autre nœud
+        // l'annonce encore
+        if other.state.is_some() && !self.is_closed() {
+            self.state = other.state;
+        }
+        self.closing_txid = other.closing_txid.or(self.closing_txid.take());
+        self.source.merge(other.source);
+        self.target.merge(other.target);
+    }
+
+    fn reversed(mut self) -> Self {
+        std::mem::swap(&mut self.source, &mut self.target);
+        self
+    }
+}
+
+pub struct NetworkGraph {
+    graph: Graph<GraphNode, GraphChannel>,
+    node_indices: HashMap<String, NodeIndex>,
+    channel_indices: HashMap<ChannelKey, EdgeIndex>,
+}
+
+impl NetworkGraph {
+    pub fn new() -> Self {
+        Self {
+            graph: Graph::new(),
+            node_indices: HashMap::new(),
+            channel_indices: HashMap::new(),
+        }
+    }
+
+    /// Ajoute un nœud, ou renvoie l'index du nœud existant en mettant à jour son alias.
+    pub fn add_node(&mut self, node_id: &str, alias: Option<&str>) -> NodeIndex {
+        if let Some(&idx) = self.node_indices.get(node_id) {
+            if let Some(alias) = alias {
+                self.graph[idx].alias = Some(alias.to_string());
+            }
+            return idx;
+        }
+        let idx = self.graph.add_node(GraphNode {
+            id: node_id.to_string(),
+            alias: alias.map(str::to_string),
+            color: None,
+            addresses: Vec::new(),
+            capacity_msat: 0,
+            state: NodeState::Unknown,
+        });
+        self.node_indices.insert(node_id.to_string(), idx);
+        idx
+    }
+
+    /// Ajoute un canal, ou fusionne `channel` dans le canal existant portant le
+    /// même `short_channel_id` ou la même sortie de financement. Renvoie `None`
+    /// si l'une des extrémités n'est pas encore dans le graphe.
+    pub fn add_channel(&mut self, from_id: &str, to_id: &str, channel: GraphChannel) -> Option<EdgeIndex> {
+        let from_idx = *self.node_indices.get(from_id)?;
+        let to_idx = *self.node_indices.get(to_id)?;
+
+        let existing = channel.keys().iter().find_map(|key| self.channel_indices.get(key).copied());
+        let edge = match existing {
+            Some(edge) => {
+                let (source, _) = self.graph.edge_endpoints(edge)?;
+                let channel = if source == from_idx { channel } else { channel.reversed() };
+                self.graph[edge].merge(channel);
+                edge
+            }
+            None => self.graph.add_edge(from_idx, to_idx, channel),
+        };
+
+        for key in self.graph[edge].keys() {
+            self.channel_indices.insert(key, edge);
+        }
+        Some(edge)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    /// Canaux du graphe avec leurs deux extrémités, dans le sens de l'arête.
+    pub fn channels(&self) -> impl Iterator<Item = (&GraphNode, &GraphNode, &GraphChannel)> {
+        self.graph
+            .edge_references()
+            .map(|edge| (&self.graph[edge.source()], &self.graph[edge.target()], edge.weight()))
+    }
+
+    /// Recalcule la capacité de chaque nœud à partir des canaux non fermés qui le touchent.
+    fn refresh_capacities(&mut self) {
+        for idx in self.graph.node_indices().collect::<Vec<_>>() {
+            self.graph[idx].capacity_msat = self
+                .graph
+                .edges_directed(idx, Direction::Outgoing)
+                .chain(self.graph.edges_directed(idx, Direction::Incoming))
+                .filter(|edge| !edge.weight().is_closed())
+                .map(|edge| edge.weight().capacity_msat)
+                .sum();
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        dot::write_dot(self)
+    }
+
+    /// Rendu SVG natif, sans Graphviz.
+    pub fn to_svg(&self) -> String {
+        svg::write_svg(self)
+    }
+
+    /// Page HTML interactive et autonome, à partager telle quelle.
+    pub fn to_html(&self) -> String {
+        svg_to_html(&self.to_svg())
+    }
+
+    /// Export GraphML pour yEd et les bibliothèques d'analyse de graphes.
+    pub fn to_graphml(&self) -> String {
+        graphml::write_graphml(self)
+    }
+
+    /// Export GEXF pour Gephi.
+    pub fn to_gexf(&self) -> String {
+        gexf::write_gexf(self)
+    }
+
+    /// Diagramme Mermaid (`flowchart`), affiché tel quel dans du Markdown.
+    pub fn to_mermaid(&self) -> String {
+        mermaid::write_mermaid(self)
+    }
+
+    /// Diagramme PlantUML équivalent à `to_mermaid`.
+    pub fn to_plantuml(&self) -> String {
+        plantuml::write_plantuml(self)
+    }
+
+    /// Dessin du graphe pour un canevas de terminal.
+    pub fn to_terminal(&self) -> TerminalGraph {
+        TerminalGraph::new(self)
+    }
+
+    /// Snapshot JSON versionné, daté de maintenant.
+    pub fn to_json(&self) -> Result<String> {
+        json::write_json(self, chrono::Utc::now())
+    }
+
+    /// Reconstruit un graphe à partir d'un snapshot écrit par `to_json`, sans
+    /// interroger aucun nœud.
+    pub fn from_json(source: &str) -> Result<Self> {
+        json::read_json(source)
+    }
+
+    /// Interroge un nœud, quelle que soit son implémentation, et intègre ses
+    /// canaux, y compris fermés, ainsi que, si demandé, sa vue gossip du réseau.
+    pub async fn update_from_backend(&mut self, backend: &dyn LightningBackend, with_gossip: bool) -> Result<()> {
+        if with_gossip {
+            let (nodes, channels) = tokio::try_join!(backend.list_nodes(), backend.list_channels())?;
+            self.update_from_gossip(&nodes, &channels);
+        }
+        let (node_info, funds, closed) =
+            tokio::try_join!(backend.get_node_info(), backend.list_funds(), backend.list_closed_channels())?;
+        self.update_from_node_info(&node_info, &funds)?;
+        self.update_from_closed_channels(&node_info.id, &closed);
+        Ok(())
+    }
+
+    /// Conserve les canaux fermés de `node_id` dans le graphe, avec l'état
+    /// `ChannelState::Closed`, au lieu de les faire disparaître.
+    pub fn update_from_closed_channels(&mut self, node_id: &str, closed: &[ClosedChannel]) {
+        for channel in closed {
+            // Canal jamais ouvert, ou pair oublié par le nœud
+            let Some(peer_id) = &channel.peer_id else { continue };
+            self.add_node(node_id, None);
+            self.add_node(peer_id, None);
+            self.add_channel(node_id, peer_id, GraphChannel {
+                short_channel_id: channel.short_channel_id.clone(),
+                funding_outpoint: Some(format!("{}:{}", channel.funding_txid, channel.funding_output)),
+                capacity_msat: channel.amount_msat,
+                state: Some(ChannelState::Closed),
+                closing_txid: channel.closing_txid.clone(),
+                source: ChannelEnd {
+                    balance_msat: Some(channel.final_to_us_msat),
+                    ..Default::default()
+                },
+                target: ChannelEnd::default(),
+            });
+        }
+        self.refresh_capacities();
+    }
+
+    pub fn update_from_node_info(&mut self, node_info: &NodeInfo, funds: &Funds) -> Result<()> {
+        let node_id = node_info.id.as_str();
+
+        let idx = self.add_node(node_id, node_info.alias.as_deref());
+        let node = &mut self.graph[idx];
+        node.color = Some(node_info.color.clone());
+        node.state = NodeState::Active;
+
+        for channel in &funds.channels {
+            let liquidity = channel.liquidity;
+            self.add_node(&channel.peer_id, None);
+            self.add_channel(node_id, &channel.peer_id, GraphChannel {
+                short_channel_id: channel.short_channel_id.clone(),
+                funding_outpoint: Some(format!("{}:{}", channel.funding_txid, channel.funding_output)),
+                capacity_msat: channel.amount_msat,
+                state: Some(channel.state),
+                closing_txid: None,
+                source: ChannelEnd {
+                    balance_msat: Some(channel.our_amount_msat),
+                    reserve_msat: liquidity.map(|l| l.our_reserve_msat),
+                    spendable_msat: liquidity.map(|l| l.spendable_msat),
+                    policy: None,
+                },
+                // Ce que le pair peut envoyer est ce que le nœud local peut recevoir
+                target: ChannelEnd {
+                    balance_msat: Some(channel.their_amount_msat()),
+                    reserve_msat: liquidity.map(|l| l.their_reserve_msat),
+                    spendable_msat: liquidity.map(|l| l.receivable_msat),
+                    policy: None,
+                },
+            });
+        }
+
+        self.refresh_capacities();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin::{BitcoinBackend, MockBitcoin};
+    use crate::lightning::{gossip_channel, gossip_node, ChannelEntry, MockLightning};
+    use fixtures::{channel, node_info, ALICE, BOB};
+
+    fn funds(channels: Vec<ChannelEntry>) -> Funds {
+        Funds { outputs: Vec::new(), channels }
+    }
+
+    #[test]
+    fn repeated_updates_do_not_duplicate_channels() {
+        let mut network = NetworkGraph::new();
+        let alice_funds = funds(vec![channel(BOB, Some("103x1x0"), 700_000_000)]);
+
+        for _ in 0..3 {
+            network.update_from_node_info(&node_info(ALICE, "alice"), &alice_funds).unwrap();
+        }
+
+        assert_eq!(network.node_count(), 2);
+        assert_eq!(network.channel_count(), 1);
+    }
+
+    #[test]
+    fn both_endpoints_are_merged_into_one_channel() {
+        let mut network = NetworkGraph::new();
+        network
+            .update_from_node_info(&node_info(ALICE, "alice"), &funds(vec![channel(BOB, None, 700_000_000)]))
+            .unwrap();
+        // Bob voit le canal après confirmation, avec son short_channel_id.
+        network
+            .update_from_node_info(&node_info(BOB, "bob"), &funds(vec![channel(ALICE, Some("103x1x0"), 300_000_000)]))
+            .unwrap();
+
+        assert_eq!(network.channel_count(), 1);
+        let edge = network.graph.edge_indices().next().unwrap();
+        let (source, _) = network.graph.edge_endpoints(edge).unwrap();
+        let channel = &network.graph[edge];
+        assert_eq!(network.graph[source].id, ALICE);
+        assert_eq!(channel.short_channel_id.as_deref(), Some("103x1x0"));
+        assert_eq!(channel.source.balance_msat, Some(700_000_000));
+        assert_eq!(channel.target.balance_msat, Some(300_000_000));
+        assert_eq!(channel.local_ratio(), Some(0.7));
+
+        let bob = network.node_indices[BOB];
+        assert_eq!(network.graph[bob].alias.as_deref(), Some("bob"));
+        assert_eq!(network.graph[bob].state, NodeState::Active);
+        assert_eq!(network.graph[bob].capacity_msat, 1_000_000_000);
+    }
+
+    #[tokio::test]
+    async fn backend_view_completes_gossip_with_local_channels() {
+        let alice = MockLightning::new("node1", ALICE)
+            .with_alias("alice")
+            .with_channel(channel(BOB, Some("103x1x0"), 700_000_000))
+            .with_gossip(
+                vec![gossip_node(BOB, "bob")],
+                vec![gossip_channel(BOB, ALICE, "103x1x0", 1_000_000)],
+            );
+
+        let mut network = NetworkGraph::new();
+        network.update_from_backend(&alice, true).await.unwrap();
+
+        assert_eq!(network.channel_count(), 1);
+        // Le gossip a créé l'arête dans le sens Bob → Alice.
+        let edge = network.graph.edge_indices().next().unwrap();
+        let channel = &network.graph[edge];
+        assert!(channel.source.policy.is_some(), "Bob's gossip policy is kept");
+        assert_eq!(channel.target.balance_msat, Some(700_000_000));
+        let alice = network.node_indices[ALICE];
+        assert_eq!(network.graph[alice].alias.as_deref(), Some("alice"));
+        assert_eq!(network.graph[alice].state, NodeState::Active);
+    }
+
+    #[tokio::test]
+    async fn closed_channels_stay_in_the_graph() {
+        let chain = MockBitcoin::new();
+        let alice = MockLightning::new("node1", ALICE)
+            .with_chain(&chain)
+            .with_onchain_balance(1_000_000)
+            .with_onchain_balance(1_000_000);
+        alice.connect_peer(BOB, "127.0.0.1", 9736).await.unwrap();
+        for _ in 0..2 {
+            alice.open_channel(BOB, 1_000_000, 0).await.unwrap();
+        }
+        chain.generate_blocks(6).await.unwrap();
+        let channels = alice.list_funds().await.unwrap().channels;
+        // Le premier canal est oublié par le nœud, le second encore en cours de fermeture
+        for (channel, blocks) in channels.iter().zip([100, 1]) {
+            alice.close_channel(channel, None).await.unwrap();
+            chain.generate_blocks(blocks).await.unwrap();
+        }
+
+        let mut network = NetworkGraph::new();
+        network.update_from_backend(&alice, false).await.unwrap();
+        let open = network.graph.edge_weights().next().unwrap();
+        assert_eq!(open.source.spendable_msat, None, "a closing channel has no spendable amount");
+
+        assert_eq!(network.channel_count(), 2);
+        let states: Vec<_> = network.graph.edge_weights().map(|c| c.state_label().to_string()).collect();
+        assert_eq!(states, ["fermeture confirmée", "fermé"]);
+        assert!(network.graph.edge_weights().last().unwrap().closing_txid.is_some());
+        assert_eq!(network.graph[network.node_indices[ALICE]].capacity_msat, 1_000_000_000);
+    }
+}
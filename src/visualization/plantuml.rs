@@ -0,0 +1,69 @@
+use petgraph::visit::EdgeRef;
+use std::fmt::Write;
+
+use super::style::{first_color, node_fill, node_stroke, pen_width, ChannelKind, LineStyle};
+use super::NetworkGraph;
+
+/// Écrit le graphe en PlantUML, avec les mêmes nœuds, étiquettes et styles
+/// que `write_mermaid`.
+pub(super) fn write_plantuml(network: &NetworkGraph) -> String {
+    let graph = &network.graph;
+    let mut uml = String::from("@startuml\n");
+    uml.push_str("left to right direction\n");
+    uml.push_str("skinparam defaultFontName Arial\n");
+    uml.push_str("skinparam rectangleRoundCorner 10\n");
+
+    for idx in graph.node_indices() {
+        let node = &graph[idx];
+        let border = node_stroke(node).map(|color| format!(";line:{}", color)).unwrap_or_default();
+        let _ = writeln!(
+            uml,
+            "rectangle \"{}\" as n{} {}{}",
+            escape(&node.display_name()),
+            idx.index(),
+            first_color(node_fill(node.state)),
+            border
+        );
+    }
+
+    for edge in graph.edge_references() {
+        let channel = edge.weight();
+        let style = ChannelKind::of(channel).style();
+        let line = match style.line {
+            LineStyle::Solid => String::new(),
+            LineStyle::Dashed => ",dashed".to_string(),
+            LineStyle::Dotted => ",dotted".to_string(),
+            LineStyle::Bold => ",bold".to_string(),
+        };
+        let _ = writeln!(
+            uml,
+            "n{} -[{}{},thickness={:.0}]-> n{} : {}",
+            edge.source().index(),
+            style.color,
+            line,
+            pen_width(channel.capacity_msat),
+            edge.target().index(),
+            escape(&channel.short_label())
+        );
+    }
+    uml.push_str("@enduml\n");
+
+    uml
+}
+
+/// Les libellés tiennent sur une ligne de source : les retours à la ligne
+/// deviennent des `\n` interprétés par PlantUML, et les guillemets, qui
+/// fermeraient le nom d'un rectangle, des apostrophes.
+fn escape(label: &str) -> String {
+    label.replace('\r', "").replace('\n', "\\n").replace('"', "'")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::visualization::fixtures::{assert_golden, two_node_network};
+
+    #[test]
+    fn two_nodes_match_golden_file() {
+        assert_golden("two_nodes.puml", &two_node_network().to_plantuml());
+    }
+}
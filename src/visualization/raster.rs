@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg::{fontdb, PostProcessingSteps, Tree, TreeParsing, TreePostProc};
+use std::sync::OnceLock;
+
+/// Facteur d'échelle des PNG, pour des étiquettes lisibles une fois zoomées.
+const PNG_SCALE: f32 = 2.0;
+
+/// Polices du système, chargées une seule fois : `resvg` et `svg2pdf`
+/// convertissent le texte en tracés avant le rendu. Sans Arial, le texte
+/// passe à la première police sans empattement installée (DejaVu Sans...).
+fn fonts() -> &'static fontdb::Database {
+    static FONTS: OnceLock<fontdb::Database> = OnceLock::new();
+    FONTS.get_or_init(|| {
+        let mut fonts = fontdb::Database::new();
+        fonts.load_system_fonts();
+        let families: Vec<&str> =
+            fonts.faces().flat_map(|face| face.families.iter().map(|(name, _)| name.as_str())).collect();
+        if !families.contains(&"Arial") {
+            if let Some(sans) = families.iter().find(|name| name.contains("Sans")).map(|name| name.to_string()) {
+                fonts.set_sans_serif_family(sans);
+            }
+        }
+        fonts
+    })
+}
+
+fn parse(svg: &str) -> Result<Tree> {
+    let mut tree = Tree::from_str(svg, &Default::default()).context("SVG invalide")?;
+    tree.postprocess(PostProcessingSteps { convert_text_into_paths: true }, fonts());
+    Ok(tree)
+}
+
+/// Convertit un document SVG en image PNG, sans Graphviz.
+pub fn svg_to_png(svg: &str) -> Result<Vec<u8>> {
+    let tree = parse(svg)?;
+    let size = tree.size.to_int_size().scale_by(PNG_SCALE).context("Image trop grande")?;
+    let mut pixmap =
+        Pixmap::new(size.width(), size.height()).with_context(|| format!("Image de {:?} impossible", size))?;
+    resvg::render(&tree, Transform::from_scale(PNG_SCALE, PNG_SCALE), &mut pixmap.as_mut());
+    pixmap.encode_png().context("Encodage PNG impossible")
+}
+
+/// Convertit un document SVG en PDF d'une page, sans Graphviz.
+pub fn svg_to_pdf(svg: &str) -> Result<Vec<u8>> {
+    Ok(svg2pdf::convert_tree(&parse(svg)?, svg2pdf::Options::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::fixtures::two_node_network;
+
+    #[test]
+    fn network_is_rasterized_at_twice_its_size() {
+        let svg = two_node_network().to_svg();
+        let png = svg_to_png(&svg).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // Largeur de l'en-tête IHDR, comparée à celle déclarée par le SVG
+        let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+        let declared: u32 = svg.split("width=\"").nth(1).and_then(|rest| rest.split('"').next()).unwrap().parse().unwrap();
+        assert_eq!(width, declared * 2);
+    }
+
+    #[test]
+    fn network_is_converted_to_pdf() {
+        let pdf = svg_to_pdf(&two_node_network().to_svg()).unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+    }
+
+    #[test]
+    fn invalid_svg_is_an_error() {
+        assert!(svg_to_png("digraph lightning {}").is_err());
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::BTreeSet;
+
+use super::{GraphChannel, GraphNode, NetworkGraph, NodeState};
+use crate::lightning::ChannelState;
+
+/// Part du solde de la cible d'un canal ouvert, la part de la source gardant
+/// la couleur de `ChannelState::ChanneldNormal`.
+pub const REMOTE_LIQUIDITY_COLOR: &str = "#81a1c1";
+
+/// Tracé d'une arête.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStyle {
+    Solid,
+    Dashed,
+    Dotted,
+    Bold,
+}
+
+impl LineStyle {
+    /// Valeur de l'attribut `style` de Graphviz.
+    pub fn dot_name(self) -> &'static str {
+        match self {
+            LineStyle::Solid => "solid",
+            LineStyle::Dashed => "dashed",
+            LineStyle::Dotted => "dotted",
+            LineStyle::Bold => "bold",
+        }
+    }
+
+    /// Valeur de l'attribut SVG `stroke-dasharray`, `None` pour un trait plein.
+    pub fn dasharray(self) -> Option<&'static str> {
+        match self {
+            LineStyle::Dashed => Some("8 4"),
+            LineStyle::Dotted => Some("2 4"),
+            LineStyle::Solid | LineStyle::Bold => None,
+        }
+    }
+
+    /// Échantillon du tracé affiché dans la légende.
+    pub fn sample(self) -> &'static str {
+        match self {
+            LineStyle::Solid => "━━",
+            LineStyle::Dashed => "╍╍",
+            LineStyle::Dotted => "┈┈",
+            LineStyle::Bold => "▬▬",
+        }
+    }
+}
+
+/// Apparence d'une arête, commune à tous les rendus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgeStyle {
+    pub color: &'static str,
+    pub line: LineStyle,
+}
+
+/// Catégorie d'un canal pour son style et la légende : son état quand l'un de
+/// ses nœuds a été interrogé, sinon ce qu'en dit le gossip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChannelKind {
+    State(ChannelState),
+    /// Annoncé actif dans au moins une direction.
+    Announced,
+    /// Annoncé, mais désactivé dans les deux directions.
+    Disabled,
+}
+
+impl ChannelKind {
+    pub fn of(channel: &GraphChannel) -> Self {
+        match channel.state {
+            Some(state) => ChannelKind::State(state),
+            None if channel.is_active() => ChannelKind::Announced,
+            None => ChannelKind::Disabled,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChannelKind::State(state) => state.label(),
+            ChannelKind::Announced => "annoncé",
+            ChannelKind::Disabled => "désactivé",
+        }
+    }
+
+    /// Nom stable de la catégorie pour les outils d'analyse : celui de l'état
+    /// dans lightningd, ou `ANNOUNCED` / `DISABLED` pour un canal vu par le gossip.
+    pub fn name(self) -> &'static str {
+        match self {
+            ChannelKind::State(state) => state.as_str(),
+            ChannelKind::Announced => "ANNOUNCED",
+            ChannelKind::Disabled => "DISABLED",
+        }
+    }
+
+    /// Libellé de la légende, avec le nom lightningd de l'état.
+    pub fn legend_label(self) -> String {
+        match self {
+            ChannelKind::State(state) => format!("{} ({})", state.label(), state),
+            other => format!("{} (gossip)", other.label()),
+        }
+    }
+
+    pub fn style(self) -> EdgeStyle {
+        let (color, line) = match self {
+            ChannelKind::State(state) => match state {
+                ChannelState::Openingd | ChannelState::DualopendOpenInit => ("#ebcb8b", LineStyle::Dotted),
+                ChannelState::ChanneldAwaitingLockin | ChannelState::DualopendAwaitingLockin => {
+                    ("#ebcb8b", LineStyle::Dashed)
+                }
+                ChannelState::ChanneldNormal => ("#a3be8c", LineStyle::Solid),
+                ChannelState::ChanneldAwaitingSplice => ("#8fbcbb", LineStyle::Solid),
+                ChannelState::ChanneldShuttingDown | ChannelState::ClosingdSigexchange => {
+                    ("#d08770", LineStyle::Dashed)
+                }
+                ChannelState::ClosingdComplete => ("#d08770", LineStyle::Bold),
+                ChannelState::AwaitingUnilateral => ("#bf616a", LineStyle::Bold),
+                ChannelState::FundingSpendSeen => ("#bf616a", LineStyle::Dashed),
+                ChannelState::Onchain => ("#b48ead", LineStyle::Dashed),
+                ChannelState::Closed => ("#4c566a", LineStyle::Dotted),
+            },
+            ChannelKind::Announced => ("#88c0d0", LineStyle::Solid),
+            ChannelKind::Disabled => ("#bf616a", LineStyle::Dotted),
+        };
+        EdgeStyle { color, line }
+    }
+}
+
+/// Dégradé de remplissage d'un nœud, au format Graphviz `début:fin`.
+pub fn node_fill(state: NodeState) -> &'static str {
+    match state {
+        NodeState::Active => "#88c0d0:#5e81ac",
+        NodeState::Announced => "#ebcb8b:#d08770",
+        NodeState::Unknown => "#eceff4:#d8dee9",
+    }
+}
+
+/// Contour d'un nœud : la couleur qu'il annonce, si elle est valide.
+pub fn node_stroke(node: &GraphNode) -> Option<String> {
+    node.color
+        .as_deref()
+        .filter(|color| color.len() == 6 && color.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(|color| format!("#{}", color))
+}
+
+pub fn first_color(gradient: &str) -> &str {
+    gradient.split(':').next().unwrap_or(gradient)
+}
+
+/// Épaisseur du trait proportionnelle au logarithme de la capacité.
+pub fn pen_width(capacity_msat: u64) -> f64 {
+    let sats = (capacity_msat / 1000).max(1) as f64;
+    (1.0 + sats.log10() * 0.4).min(6.0)
+}
+
+/// Segments de couleur d'une arête découpée selon les soldes, avec leur part
+/// de la longueur : la couleur de la source côté source, `REMOTE_LIQUIDITY_COLOR`
+/// côté cible. Un solde presque nul ne laisse qu'un segment.
+pub fn liquidity_segments(local_color: &'static str, local_ratio: f64) -> Vec<(&'static str, f64)> {
+    if local_ratio < 0.01 {
+        vec![(REMOTE_LIQUIDITY_COLOR, 1.0)]
+    } else if local_ratio > 0.99 {
+        vec![(local_color, 1.0)]
+    } else {
+        vec![(local_color, local_ratio), (REMOTE_LIQUIDITY_COLOR, 1.0 - local_ratio)]
+    }
+}
+
+/// Entrées de légende des éléments présents dans un graphe, dans l'ordre du
+/// cycle de vie des canaux.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Legend {
+    pub nodes: Vec<NodeState>,
+    pub channels: Vec<ChannelKind>,
+    /// Au moins un canal ouvert est coloré selon ses soldes.
+    pub liquidity: bool,
+}
+
+impl Legend {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.channels.is_empty()
+    }
+}
+
+impl NetworkGraph {
+    pub fn legend(&self) -> Legend {
+        let nodes: BTreeSet<_> = self.graph.node_weights().map(|node| node.state).collect();
+        let channels: BTreeSet<_> = self.graph.edge_weights().map(ChannelKind::of).collect();
+        Legend {
+            nodes: nodes.into_iter().collect(),
+            channels: channels.into_iter().collect(),
+            liquidity: self.graph.edge_weights().any(|channel| channel.liquidity_ratio().is_some()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::fixtures::assert_golden;
+    use std::collections::HashSet;
+
+    fn all_kinds() -> Vec<ChannelKind> {
+        ChannelState::ALL
+            .into_iter()
+            .map(ChannelKind::State)
+            .chain([ChannelKind::Announced, ChannelKind::Disabled])
+            .collect()
+    }
+
+    #[test]
+    fn every_channel_kind_has_its_style() {
+        let table: String = all_kinds()
+            .into_iter()
+            .map(|kind| {
+                let style = kind.style();
+                format!("{:<26}{:<24}{} {}\n", kind.name(), kind.label(), style.color, style.line.dot_name())
+            })
+            .collect();
+        assert_golden("channel_styles.txt", &table);
+    }
+
+    #[test]
+    fn phases_never_share_a_color() {
+        let colors = |phase: fn(ChannelState) -> bool| -> HashSet<_> {
+            ChannelState::ALL
+                .into_iter()
+                .filter(|state| phase(*state))
+                .map(|state| ChannelKind::State(state).style().color)
+                .collect()
+        };
+        let opening = colors(ChannelState::is_opening);
+        let normal = colors(ChannelState::is_normal);
+        let closing = colors(ChannelState::is_closing);
+        assert!(opening.is_disjoint(&normal) && normal.is_disjoint(&closing) && opening.is_disjoint(&closing));
+    }
+}
@@ -0,0 +1,450 @@
+use petgraph::graph::EdgeIndex;
+use petgraph::visit::EdgeRef;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use super::layout::{self, text_size, NodeBox, Point, CHAR_WIDTH, EDGE_FONT_SIZE, FONT_SIZE, LINE_HEIGHT, MARGIN};
+use super::style::{
+    first_color, liquidity_segments, node_fill, node_stroke, pen_width, ChannelKind, Legend, LineStyle,
+    REMOTE_LIQUIDITY_COLOR,
+};
+use super::{GraphChannel, GraphNode, NetworkGraph, NodeState};
+
+/// Espace entre les étiquettes de canaux parallèles.
+const LABEL_GAP: f64 = 8.0;
+const LEGEND_ROW_HEIGHT: f64 = 18.0;
+
+/// Tracé d'un canal : une courbe quadratique entre les bords des deux nœuds,
+/// écartée des canaux parallèles, et la position de son étiquette.
+struct EdgeGeometry {
+    start: Point,
+    control: Point,
+    end: Point,
+    label_center: Point,
+}
+
+/// Rectangle englobant les éléments dessinés, pour la `viewBox`.
+struct Bounds {
+    min: Point,
+    max: Point,
+}
+
+impl Bounds {
+    fn new() -> Self {
+        Self {
+            min: Point { x: f64::INFINITY, y: f64::INFINITY },
+            max: Point { x: f64::NEG_INFINITY, y: f64::NEG_INFINITY },
+        }
+    }
+
+    fn include(&mut self, center: Point, width: f64, height: f64) {
+        self.min.x = self.min.x.min(center.x - width / 2.0);
+        self.min.y = self.min.y.min(center.y - height / 2.0);
+        self.max.x = self.max.x.max(center.x + width / 2.0);
+        self.max.y = self.max.y.max(center.y + height / 2.0);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.min.x > self.max.x
+    }
+}
+
+/// Écrit le graphe au format SVG, disposé par `layout::force_directed`, avec
+/// les mêmes couleurs, tracés et légende que le rendu DOT mais sans Graphviz.
+pub(super) fn write_svg(network: &NetworkGraph) -> String {
+    let graph = &network.graph;
+    let layout = layout::force_directed(network);
+    let geometries = edge_geometries(network, &layout.nodes);
+    let legend = network.legend();
+
+    let mut bounds = Bounds::new();
+    for node in &layout.nodes {
+        bounds.include(node.center, node.width, node.height);
+    }
+    for (edge, geometry) in graph.edge_references().zip(&geometries) {
+        let (width, height) = text_size(&edge.weight().label(), EDGE_FONT_SIZE);
+        // Le sommet de la courbe est sous l'étiquette, à mi-chemin du point de contrôle
+        bounds.include(geometry.label_center, width, height);
+    }
+    if bounds.is_empty() {
+        bounds.include(Point { x: 0.0, y: 0.0 }, 0.0, 0.0);
+    }
+    let legend_origin = Point { x: bounds.max.x + 2.0 * MARGIN, y: bounds.min.y };
+    if !legend.is_empty() {
+        let (width, height) = legend_size(&legend);
+        bounds.include(
+            Point { x: legend_origin.x + width / 2.0, y: legend_origin.y + height / 2.0 },
+            width,
+            height,
+        );
+    }
+    let (x, y) = (bounds.min.x - MARGIN, bounds.min.y - MARGIN);
+    let (width, height) = (bounds.max.x - bounds.min.x + 2.0 * MARGIN, bounds.max.y - bounds.min.y + 2.0 * MARGIN);
+
+    let mut svg = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"{:.1} {:.1} {:.1} {:.1}\" \
+         font-family=\"Arial, sans-serif\">",
+        width.ceil(),
+        height.ceil(),
+        x,
+        y,
+        width,
+        height
+    );
+    let _ = writeln!(
+        svg,
+        "  <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#ffffff\"/>",
+        x, y, width, height
+    );
+    write_defs(&mut svg, network, &geometries, &legend);
+
+    for (edge, geometry) in graph.edge_references().zip(&geometries) {
+        write_edge(&mut svg, edge.id().index(), edge.weight(), geometry);
+    }
+    for (node, node_box) in graph.node_weights().zip(&layout.nodes) {
+        write_node(&mut svg, node, node_box);
+    }
+    if !legend.is_empty() {
+        write_legend(&mut svg, &legend, legend_origin);
+    }
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+/// Dégradés des nœuds et des canaux découpés selon les soldes, et pointes de
+/// flèche, une par couleur de fin d'arête.
+fn write_defs(svg: &mut String, network: &NetworkGraph, geometries: &[EdgeGeometry], legend: &Legend) {
+    let graph = &network.graph;
+    svg.push_str("  <defs>\n");
+    for state in &legend.nodes {
+        let fill = node_fill(*state);
+        let end = fill.split(':').nth(1).unwrap_or(fill);
+        let _ = writeln!(
+            svg,
+            "    <linearGradient id=\"{}\" x1=\"0\" y1=\"0\" x2=\"0\" y2=\"1\">\
+             <stop offset=\"0\" stop-color=\"{}\"/><stop offset=\"1\" stop-color=\"{}\"/></linearGradient>",
+            node_gradient_id(*state),
+            first_color(fill),
+            end
+        );
+    }
+
+    let mut arrow_colors = BTreeSet::new();
+    for (edge, geometry) in graph.edge_references().zip(geometries) {
+        let segments = edge_segments(edge.weight());
+        arrow_colors.insert(segments[segments.len() - 1].0);
+        if let [(local, ratio), (remote, _)] = segments.as_slice() {
+            let _ = writeln!(
+                svg,
+                "    <linearGradient id=\"liquidity-{}\" gradientUnits=\"userSpaceOnUse\" \
+                 x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\">\
+                 <stop offset=\"{:.2}\" stop-color=\"{}\"/><stop offset=\"{:.2}\" stop-color=\"{}\"/></linearGradient>",
+                edge.id().index(),
+                geometry.start.x,
+                geometry.start.y,
+                geometry.end.x,
+                geometry.end.y,
+                ratio,
+                local,
+                ratio,
+                remote
+            );
+        }
+    }
+    for color in arrow_colors {
+        let _ = writeln!(
+            svg,
+            "    <marker id=\"{}\" viewBox=\"0 0 10 10\" refX=\"9\" refY=\"5\" markerWidth=\"4\" markerHeight=\"4\" \
+             orient=\"auto\"><path d=\"M0,0 L10,5 L0,10 z\" fill=\"{}\"/></marker>",
+            arrow_id(color),
+            color
+        );
+    }
+    svg.push_str("  </defs>\n");
+}
+
+fn write_node(svg: &mut String, node: &GraphNode, node_box: &NodeBox) {
+    let (stroke, stroke_width) = match node_stroke(node) {
+        Some(color) => (color, 2.0),
+        None => ("#4c566a".to_string(), 1.0),
+    };
+    let mut tooltip = format!("{}\n{}\nCapacité: {} sats", node.display_name(), node.id, node.capacity_msat / 1000);
+    for address in &node.addresses {
+        tooltip.push('\n');
+        tooltip.push_str(address);
+    }
+    // Termes retrouvés par la recherche de l'export HTML
+    let search = [node.alias.as_deref().unwrap_or_default(), &node.id].join(" ");
+    let _ = writeln!(svg, "  <g class=\"node\" data-search=\"{}\">", escape(search.trim()));
+    let _ = writeln!(svg, "    <title>{}</title>", escape(&tooltip));
+    let _ = writeln!(
+        svg,
+        "    <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"6\" fill=\"url(#{})\" stroke=\"{}\" \
+         stroke-width=\"{}\"/>",
+        node_box.center.x - node_box.width / 2.0,
+        node_box.center.y - node_box.height / 2.0,
+        node_box.width,
+        node_box.height,
+        node_gradient_id(node.state),
+        stroke,
+        stroke_width
+    );
+    write_text(svg, &node.label(), node_box.center, FONT_SIZE);
+    svg.push_str("  </g>\n");
+}
+
+fn write_edge(svg: &mut String, index: usize, channel: &GraphChannel, geometry: &EdgeGeometry) {
+    let style = ChannelKind::of(channel).style();
+    let segments = edge_segments(channel);
+    let stroke = if segments.len() > 1 { format!("url(#liquidity-{})", index) } else { segments[0].0.to_string() };
+    let mut width = pen_width(channel.capacity_msat);
+    if style.line == LineStyle::Bold {
+        width *= 1.5;
+    }
+    let dash = style.line.dasharray().map(|dash| format!(" stroke-dasharray=\"{}\"", dash)).unwrap_or_default();
+    let label = channel.label();
+
+    let search = [channel.short_channel_id.as_deref(), channel.funding_outpoint.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let _ = writeln!(svg, "  <g class=\"channel\" data-search=\"{}\">", escape(&search));
+    let _ = writeln!(svg, "    <title>{}</title>", escape(&label));
+    let _ = writeln!(
+        svg,
+        "    <path d=\"M{:.1},{:.1} Q{:.1},{:.1} {:.1},{:.1}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{:.1}\"{} \
+         marker-end=\"url(#{})\"/>",
+        geometry.start.x,
+        geometry.start.y,
+        geometry.control.x,
+        geometry.control.y,
+        geometry.end.x,
+        geometry.end.y,
+        stroke,
+        width,
+        dash,
+        arrow_id(segments[segments.len() - 1].0)
+    );
+    let (label_width, label_height) = text_size(&label, EDGE_FONT_SIZE);
+    let _ = writeln!(
+        svg,
+        "    <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#ffffff\" fill-opacity=\"0.8\"/>",
+        geometry.label_center.x - label_width / 2.0,
+        geometry.label_center.y - label_height / 2.0,
+        label_width,
+        label_height
+    );
+    write_text(svg, &label, geometry.label_center, EDGE_FONT_SIZE);
+    svg.push_str("  </g>\n");
+}
+
+/// Légende des seuls états de nœuds et de canaux présents dans le graphe, en
+/// haut à droite du dessin.
+fn write_legend(svg: &mut String, legend: &Legend, origin: Point) {
+    let (width, height) = legend_size(legend);
+    svg.push_str("  <g class=\"legend\">\n");
+    let _ = writeln!(
+        svg,
+        "    <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#ffffff\" stroke=\"#d8dee9\"/>",
+        origin.x, origin.y, width, height
+    );
+    let _ = writeln!(
+        svg,
+        "    <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{}\" font-weight=\"bold\">Légende</text>",
+        origin.x + 10.0,
+        origin.y + LEGEND_ROW_HEIGHT,
+        FONT_SIZE
+    );
+
+    let mut y = origin.y + LEGEND_ROW_HEIGHT;
+    for state in &legend.nodes {
+        y += LEGEND_ROW_HEIGHT;
+        let _ = writeln!(
+            svg,
+            "    <rect x=\"{:.1}\" y=\"{:.1}\" width=\"24\" height=\"12\" rx=\"3\" fill=\"url(#{})\"/>",
+            origin.x + 10.0,
+            y - 10.0,
+            node_gradient_id(*state)
+        );
+        write_legend_text(svg, origin.x, y, &format!("Nœud {}", state.label()));
+    }
+    let channels = legend.channels.iter().map(|kind| (kind.style().color, kind.style().line, kind.legend_label()));
+    let liquidity = legend
+        .liquidity
+        .then(|| (REMOTE_LIQUIDITY_COLOR, LineStyle::Solid, "Solde de la cible".to_string()));
+    for (color, line, text) in channels.chain(liquidity) {
+        y += LEGEND_ROW_HEIGHT;
+        let dash = line.dasharray().map(|dash| format!(" stroke-dasharray=\"{}\"", dash)).unwrap_or_default();
+        let _ = writeln!(
+            svg,
+            "    <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"{}\"{}/>",
+            origin.x + 10.0,
+            y - 4.0,
+            origin.x + 34.0,
+            y - 4.0,
+            color,
+            if line == LineStyle::Bold { 4 } else { 2 },
+            dash
+        );
+        write_legend_text(svg, origin.x, y, &text);
+    }
+    svg.push_str("  </g>\n");
+}
+
+fn write_legend_text(svg: &mut String, x: f64, y: f64, text: &str) {
+    let _ = writeln!(
+        svg,
+        "    <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{}\">{}</text>",
+        x + 42.0,
+        y,
+        FONT_SIZE,
+        escape(text)
+    );
+}
+
+fn legend_size(legend: &Legend) -> (f64, f64) {
+    let texts = legend
+        .nodes
+        .iter()
+        .map(|state| format!("Nœud {}", state.label()))
+        .chain(legend.channels.iter().map(|kind| kind.legend_label()))
+        .chain(legend.liquidity.then(|| "Solde de la cible".to_string()));
+    let mut rows = 1;
+    let mut widest = "Légende".chars().count();
+    for text in texts {
+        rows += 1;
+        widest = widest.max(text.chars().count() + 6);
+    }
+    (widest as f64 * CHAR_WIDTH + 20.0, rows as f64 * LEGEND_ROW_HEIGHT + 12.0)
+}
+
+/// Texte de plusieurs lignes centré sur `center`, une `tspan` par ligne.
+fn write_text(svg: &mut String, text: &str, center: Point, font_size: f64) {
+    let lines: Vec<&str> = text.lines().collect();
+    let line_height = LINE_HEIGHT * font_size / FONT_SIZE;
+    // Ligne de base de la première ligne, pour centrer le bloc verticalement
+    let top = center.y - (lines.len() as f64 - 1.0) * line_height / 2.0 + font_size * 0.35;
+    let _ = write!(svg, "    <text text-anchor=\"middle\" font-size=\"{}\">", font_size);
+    for (i, line) in lines.iter().enumerate() {
+        let _ = write!(
+            svg,
+            "<tspan x=\"{:.1}\" y=\"{:.1}\">{}</tspan>",
+            center.x,
+            top + i as f64 * line_height,
+            escape(line)
+        );
+    }
+    svg.push_str("</text>\n");
+}
+
+/// Courbes des canaux : ceux qui relient la même paire de nœuds, dans un sens
+/// ou dans l'autre, sont écartés de part et d'autre de la droite entre leurs
+/// centres, assez pour que leurs étiquettes ne se chevauchent pas.
+fn edge_geometries(network: &NetworkGraph, nodes: &[NodeBox]) -> Vec<EdgeGeometry> {
+    let graph = &network.graph;
+    let mut pairs: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+    for edge in graph.edge_references() {
+        let (source, target) = (edge.source().index(), edge.target().index());
+        pairs.entry((source.min(target), source.max(target))).or_default().push(edge.id().index());
+    }
+
+    let mut geometries: Vec<Option<EdgeGeometry>> = (0..graph.edge_count()).map(|_| None).collect();
+    for ((first, second), edges) in pairs {
+        let (a, b) = (nodes[first].center, nodes[second].center);
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let length = (dx * dx + dy * dy).sqrt();
+        let normal = if length > 0.0 { Point { x: -dy / length, y: dx / length } } else { Point { x: 0.0, y: -1.0 } };
+        // Encombrement des étiquettes dans la direction de l'écart
+        let extent = edges
+            .iter()
+            .map(|&edge| {
+                let (width, height) = text_size(&graph[EdgeIndex::new(edge)].label(), EDGE_FONT_SIZE);
+                normal.x.abs() * width + normal.y.abs() * height
+            })
+            .fold(0.0, f64::max);
+        // L'étiquette est au milieu de la courbe, à mi-chemin du point de contrôle
+        let step = 2.0 * (extent + LABEL_GAP);
+        let middle = Point { x: (a.x + b.x) / 2.0, y: (a.y + b.y) / 2.0 };
+        for (i, &edge) in edges.iter().enumerate() {
+            let offset = (i as f64 - (edges.len() as f64 - 1.0) / 2.0) * step;
+            let control = Point { x: middle.x + normal.x * offset, y: middle.y + normal.y * offset };
+            let label_center = Point { x: middle.x + normal.x * offset / 2.0, y: middle.y + normal.y * offset / 2.0 };
+            let (source, target) = graph
+                .edge_endpoints(EdgeIndex::new(edge))
+                .map(|(source, target)| (source.index(), target.index()))
+                .unwrap_or((first, second));
+            geometries[edge] = Some(EdgeGeometry {
+                start: nodes[source].border_towards(control),
+                control,
+                end: nodes[target].border_towards(control),
+                label_center,
+            });
+        }
+    }
+    geometries.into_iter().flatten().collect()
+}
+
+/// Couleurs du trait d'un canal : découpé selon les soldes s'ils sont connus.
+fn edge_segments(channel: &GraphChannel) -> Vec<(&'static str, f64)> {
+    let color = ChannelKind::of(channel).style().color;
+    match channel.liquidity_ratio() {
+        Some(ratio) => liquidity_segments(color, ratio),
+        None => vec![(color, 1.0)],
+    }
+}
+
+fn node_gradient_id(state: NodeState) -> String {
+    format!("node-{}", state.name())
+}
+
+fn arrow_id(color: &str) -> String {
+    format!("arrow-{}", color.trim_start_matches('#'))
+}
+
+/// Échappe les caractères spéciaux XML du texte et des attributs.
+pub(super) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::fixtures::{assert_golden, two_node_network, ALICE};
+
+    #[test]
+    fn two_nodes_match_golden_file() {
+        assert_golden("two_nodes.svg", &two_node_network().to_svg());
+    }
+
+    #[test]
+    fn empty_graph_is_a_valid_document() {
+        let svg = NetworkGraph::new().to_svg();
+        assert!(svg.starts_with("<?xml") && svg.ends_with("</svg>\n"), "{}", svg);
+        assert!(!svg.contains("Légende"));
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        let mut network = NetworkGraph::new();
+        network.add_node(ALICE, Some("<al&ice>"));
+        let svg = network.to_svg();
+        assert!(svg.contains("&lt;al&amp;ice&gt;"), "{}", svg);
+        assert!(!svg.contains("<al&ice>"));
+    }
+}
@@ -0,0 +1,133 @@
+use ratatui::style::{Color, Style};
+use ratatui::text::Span;
+use ratatui::widgets::canvas::{Canvas, Context, Line};
+use ratatui::widgets::{Block, Widget};
+
+use super::attributes::node_display_color;
+use super::layout::{force_directed, MARGIN};
+use super::style::ChannelKind;
+use super::NetworkGraph;
+
+/// Graphe dessiné en braille dans un terminal, avec les positions de la
+/// disposition par forces du rendu SVG. Calculé une fois par rafraîchissement,
+/// puis redessiné à chaque image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalGraph {
+    nodes: Vec<(f64, f64, String, Color)>,
+    channels: Vec<(f64, f64, f64, f64, Color)>,
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+}
+
+impl TerminalGraph {
+    pub(super) fn new(network: &NetworkGraph) -> Self {
+        let graph = &network.graph;
+        let layout = force_directed(network);
+        // Le canevas compte les ordonnées vers le haut, le SVG vers le bas
+        let position = |idx: usize| (layout.nodes[idx].center.x, -layout.nodes[idx].center.y);
+
+        let nodes: Vec<_> = graph
+            .node_indices()
+            .map(|idx| {
+                let node = &graph[idx];
+                let (x, y) = position(idx.index());
+                (x, y, node.display_name(), terminal_color(&node_display_color(node)))
+            })
+            .collect();
+        let channels = graph
+            .edge_indices()
+            .filter_map(|edge| {
+                let (source, target) = graph.edge_endpoints(edge)?;
+                let ((x1, y1), (x2, y2)) = (position(source.index()), position(target.index()));
+                Some((x1, y1, x2, y2, terminal_color(ChannelKind::of(&graph[edge]).style().color)))
+            })
+            .collect();
+
+        // Les étiquettes partent du nœud vers la droite : la largeur de la
+        // plus grande boîte leur laisse la place
+        let widest = layout.nodes.iter().map(|node| node.width).fold(0.0, f64::max);
+        let (mut x_bounds, mut y_bounds) = ([f64::MAX, f64::MIN], [f64::MAX, f64::MIN]);
+        for (x, y, _, _) in &nodes {
+            x_bounds = [x_bounds[0].min(*x), x_bounds[1].max(*x)];
+            y_bounds = [y_bounds[0].min(*y), y_bounds[1].max(*y)];
+        }
+        if nodes.is_empty() {
+            (x_bounds, y_bounds) = ([0.0, 0.0], [0.0, 0.0]);
+        }
+
+        Self {
+            nodes,
+            channels,
+            x_bounds: [x_bounds[0] - MARGIN, x_bounds[1] + widest],
+            y_bounds: [y_bounds[0] - MARGIN, y_bounds[1] + MARGIN],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Canevas braille du graphe, encadré par `block` : les canaux aux
+    /// couleurs de leur état, puis chaque nœud et son nom par-dessus.
+    pub fn canvas<'a>(&'a self, block: Block<'a>) -> impl Widget + 'a {
+        Canvas::default()
+            .block(block)
+            .x_bounds(self.x_bounds)
+            .y_bounds(self.y_bounds)
+            .paint(move |ctx: &mut Context| {
+                for &(x1, y1, x2, y2, color) in &self.channels {
+                    ctx.draw(&Line::new(x1, y1, x2, y2, color));
+                }
+                ctx.layer();
+                for (x, y, name, color) in &self.nodes {
+                    ctx.print(*x, *y, Span::styled(format!("● {}", name), Style::default().fg(*color)));
+                }
+            })
+    }
+}
+
+/// Couleur `#rrggbb` des autres rendus, en couleur de terminal.
+pub fn terminal_color(color: &str) -> Color {
+    let hex = color.trim_start_matches('#');
+    let component = |range: std::ops::Range<usize>| hex.get(range).and_then(|c| u8::from_str_radix(c, 16).ok());
+    match (component(0..2), component(2..4), component(4..6)) {
+        (Some(r), Some(g), Some(b)) if hex.len() == 6 => Color::Rgb(r, g, b),
+        _ => Color::Reset,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::fixtures::{two_node_network, BOB};
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn colors_are_read_from_hex() {
+        assert_eq!(terminal_color("#a3be8c"), Color::Rgb(0xa3, 0xbe, 0x8c));
+        assert_eq!(terminal_color("#fff"), Color::Reset);
+    }
+
+    #[test]
+    fn nodes_and_channels_are_drawn() {
+        let graph = two_node_network().to_terminal();
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.channels.len(), 4);
+
+        let area = Rect::new(0, 0, 80, 20);
+        let mut buffer = Buffer::empty(area);
+        graph.canvas(Block::bordered()).render(area, &mut buffer);
+        let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("● alice") && text.contains(&format!("● {}", &BOB[..8])), "{}", text);
+        assert!(text.chars().any(|c| ('\u{2801}'..='\u{28ff}').contains(&c)), "no braille line in {}", text);
+    }
+
+    #[test]
+    fn empty_graph_is_drawn_without_nodes() {
+        let graph = NetworkGraph::new().to_terminal();
+        assert!(graph.is_empty());
+        let area = Rect::new(0, 0, 20, 5);
+        graph.canvas(Block::bordered()).render(area, &mut Buffer::empty(area));
+    }
+}